@@ -0,0 +1,333 @@
+use crate::logger::{info, warn};
+use crate::models::LockEntry;
+use crate::util::{find_plugin_jars, resolve_plugins_dir, DISABLED_DIR_NAME};
+use crate::{lockfile, metadata};
+use anyhow::{anyhow, Result};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Folder under `plugins/` holding replaced jars: `.backups/<id>/<timestamp>_<version>/<file>.jar`.
+pub const BACKUP_DIR_NAME: &str = ".backups";
+const BACKUP_INFO_FILE: &str = "backup.json";
+const KEEP_BACKUPS: usize = 3;
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BackupInfo {
+    file_name: String,
+    id: Option<String>,
+    version: Option<String>,
+    created_at: i64,
+    was_disabled: bool,
+    lock: Option<LockEntry>,
+    /// Jar replaced by a rollback: kept, but never picked by a later rollback
+    #[serde(default)]
+    from_rollback: bool,
+}
+
+fn sanitize(s: &str) -> String {
+    s.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+// Backups are grouped by plugin id, or by file stem for jars without an id.
+fn backup_key(jar: &Path, id: Option<&str>) -> String {
+    match id {
+        Some(id) => sanitize(id),
+        None => sanitize(
+            jar.file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("unknown"),
+        ),
+    }
+}
+
+// Backup folders of a plugin, oldest first.
+fn backups_of(plugins_dir: &Path, key: &str) -> Vec<PathBuf> {
+    let dir = plugins_dir.join(BACKUP_DIR_NAME).join(key);
+    let mut out: Vec<(i64, PathBuf)> = match fs::read_dir(&dir) {
+        Ok(rd) => rd
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.is_dir())
+            .filter_map(|p| {
+                let ts = p
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .and_then(|n| n.split('_').next())
+                    .and_then(|t| t.parse::<i64>().ok())?;
+                Some((ts, p))
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    out.sort_by_key(|(ts, _)| *ts);
+    out.into_iter().map(|(_, p)| p).collect()
+}
+
+fn read_info(backup: &Path) -> Result<BackupInfo> {
+    let s = fs::read_to_string(backup.join(BACKUP_INFO_FILE))?;
+    Ok(serde_json::from_str(&s)?)
+}
+
+// Moves an installed jar into a new backup folder, together with its plugins.lock entry.
+fn backup_jar(plugins_dir: &Path, jar: &Path, from_rollback: bool) -> Result<PathBuf> {
+    let meta = metadata::extract_metadata_from_jar(&jar.to_path_buf()).ok();
+    let id = meta.as_ref().and_then(|m| m.id.clone());
+    let version = meta.as_ref().and_then(|m| m.version.clone());
+    let file_name = jar
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| anyhow!("Invalid file name"))?
+        .to_string();
+    let key = backup_key(jar, id.as_deref());
+    let mut created_at = chrono::Utc::now().timestamp_millis();
+    let backup_dir = |ts: i64| {
+        plugins_dir.join(BACKUP_DIR_NAME).join(&key).join(format!(
+            "{}_{}",
+            ts,
+            sanitize(version.as_deref().unwrap_or("unknown"))
+        ))
+    };
+    // Several jars may be backed up within the same millisecond
+    while backup_dir(created_at).exists() {
+        created_at += 1;
+    }
+    let dir = backup_dir(created_at);
+    fs::create_dir_all(&dir)?;
+    let info_file = BackupInfo {
+        file_name: file_name.clone(),
+        id,
+        version,
+        created_at,
+        was_disabled: jar.parent() == Some(plugins_dir.join(DISABLED_DIR_NAME).as_path()),
+        lock: lockfile::find(plugins_dir, &file_name),
+        from_rollback,
+    };
+    fs::write(
+        dir.join(BACKUP_INFO_FILE),
+        serde_json::to_string_pretty(&info_file)?,
+    )?;
+    let dest = dir.join(&file_name);
+    fs::rename(jar, &dest)?;
+    Ok(dest)
+}
+
+// Puts jars moved by `backup_jar` back where they were and drops their backup folders.
+fn restore_moved(moved: &[(PathBuf, PathBuf)]) {
+    for (orig, backup) in moved.iter() {
+        if let Err(e) = fs::rename(backup, orig) {
+            warn(
+                "backup",
+                &format!("Cannot restore {}: {}", orig.display(), e),
+            );
+        } else if let Some(parent) = backup.parent() {
+            let _ = fs::remove_dir_all(parent);
+        }
+    }
+}
+
+// Backup keys (plugin folders under `.backups`) of moved jars, without duplicates.
+fn moved_keys(moved: &[(PathBuf, PathBuf)]) -> BTreeSet<String> {
+    moved
+        .iter()
+        .filter_map(|(_, backup)| {
+            let key_dir = backup.parent()?.parent()?;
+            key_dir.file_name()?.to_str().map(|s| s.to_string())
+        })
+        .collect()
+}
+
+fn prune(plugins_dir: &Path, key: &str) {
+    let backups = backups_of(plugins_dir, key);
+    if backups.len() <= KEEP_BACKUPS {
+        return;
+    }
+    for old in backups.iter().take(backups.len() - KEEP_BACKUPS) {
+        if let Err(e) = fs::remove_dir_all(old) {
+            warn(
+                "backup",
+                &format!("Cannot remove old backup {}: {}", old.display(), e),
+            );
+        }
+    }
+}
+
+/// Installs a staged jar (written next to its destination under a non-`.jar` name) as
+/// `dest_dir/file_name`. Every installed jar with the same file name or plugin id is moved
+/// to the backup area first; if anything fails, the previous jars are put back.
+pub fn install_atomic(
+    plugins_dir: &Path,
+    staged: &Path,
+    dest_dir: &Path,
+    file_name: &str,
+    emit: &dyn Fn(&str),
+) -> Result<PathBuf> {
+    let id = metadata::extract_metadata_from_jar(&staged.to_path_buf())
+        .ok()
+        .and_then(|m| m.id);
+    let mut previous: Vec<PathBuf> = Vec::new();
+    for dir in [
+        plugins_dir.to_path_buf(),
+        plugins_dir.join(DISABLED_DIR_NAME),
+    ] {
+        let same_name = dir.join(file_name);
+        if same_name.is_file() {
+            previous.push(same_name);
+        }
+        if let Some(id) = &id {
+            for p in find_plugin_jars(&dir, id)? {
+                if !previous.contains(&p) {
+                    previous.push(p);
+                }
+            }
+        }
+    }
+
+    let mut moved: Vec<(PathBuf, PathBuf)> = Vec::new();
+    for prev in previous.iter() {
+        match backup_jar(plugins_dir, prev, false) {
+            Ok(backup) => {
+                emit(&format!(
+                    "Backup: {} -> {}",
+                    prev.file_name().and_then(|n| n.to_str()).unwrap_or("?"),
+                    backup.display()
+                ));
+                moved.push((prev.clone(), backup));
+            }
+            Err(e) => {
+                restore_moved(&moved);
+                let _ = fs::remove_file(staged);
+                return Err(anyhow!("Backup failed for {}: {}", prev.display(), e));
+            }
+        }
+    }
+
+    fs::create_dir_all(dest_dir)?;
+    let dest = dest_dir.join(file_name);
+    if let Err(e) = fs::rename(staged, &dest) {
+        restore_moved(&moved);
+        let _ = fs::remove_file(staged);
+        return Err(anyhow!("Install failed, previous version restored: {}", e));
+    }
+    for (orig, _) in moved.iter() {
+        let name = orig.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if name != file_name {
+            if let Err(e) = lockfile::remove(plugins_dir, name) {
+                warn("backup", &format!("Cannot update plugins.lock: {}", e));
+            }
+        }
+    }
+    for key in moved_keys(&moved) {
+        prune(plugins_dir, &key);
+    }
+    Ok(dest)
+}
+
+/// Restores the most recent backup of a plugin (`target` is a plugin id or an installed jar
+/// file name). The currently installed jars for that plugin are backed up in turn, marked so
+/// that a second rollback skips them and goes one version further back.
+pub fn rollback_plugin(target: String) -> Result<String> {
+    if target.contains('/') || target.contains('\\') || target.trim().is_empty() {
+        anyhow::bail!("Invalid name");
+    }
+    let plugins_dir = resolve_plugins_dir();
+    let disabled_dir = plugins_dir.join(DISABLED_DIR_NAME);
+    let mut current: Vec<PathBuf> = Vec::new();
+    for dir in [&plugins_dir, &disabled_dir] {
+        current.extend(find_plugin_jars(dir, &target)?);
+    }
+    let key = if plugins_dir
+        .join(BACKUP_DIR_NAME)
+        .join(sanitize(&target))
+        .is_dir()
+    {
+        sanitize(&target)
+    } else {
+        let jar = current.first().ok_or_else(|| anyhow!("Plugin not found"))?;
+        let id = metadata::extract_metadata_from_jar(jar)
+            .ok()
+            .and_then(|m| m.id);
+        backup_key(jar, id.as_deref())
+    };
+    let (backup, info_file) = backups_of(&plugins_dir, &key)
+        .into_iter()
+        .rev()
+        .filter_map(|b| read_info(&b).ok().map(|i| (b, i)))
+        .find(|(_, i)| !i.from_rollback)
+        .ok_or_else(|| anyhow!("No backup available for {}", target))?;
+    let saved_jar = backup.join(&info_file.file_name);
+    if !saved_jar.is_file() {
+        anyhow::bail!("Backup is incomplete: {}", backup.display());
+    }
+    // Current jars of the plugin: by id when known, plus whatever holds the restored file name
+    if let Some(id) = &info_file.id {
+        for dir in [&plugins_dir, &disabled_dir] {
+            for p in find_plugin_jars(dir, id)? {
+                if !current.contains(&p) {
+                    current.push(p);
+                }
+            }
+        }
+    }
+    let dest_dir = if info_file.was_disabled {
+        &disabled_dir
+    } else {
+        &plugins_dir
+    };
+    let dest = dest_dir.join(&info_file.file_name);
+    if dest.is_file() && !current.contains(&dest) {
+        current.push(dest.clone());
+    }
+
+    fs::create_dir_all(dest_dir)?;
+    let staged = dest_dir.join(format!("{}.restore", info_file.file_name));
+    fs::copy(&saved_jar, &staged)?;
+    let mut moved: Vec<(PathBuf, PathBuf)> = Vec::new();
+    for p in current.iter() {
+        match backup_jar(&plugins_dir, p, true) {
+            Ok(backup) => moved.push((p.clone(), backup)),
+            Err(e) => {
+                restore_moved(&moved);
+                let _ = fs::remove_file(&staged);
+                return Err(anyhow!("Backup failed for {}: {}", p.display(), e));
+            }
+        }
+    }
+    if let Err(e) = fs::rename(&staged, &dest) {
+        restore_moved(&moved);
+        let _ = fs::remove_file(&staged);
+        return Err(anyhow!("Rollback failed: {}", e));
+    }
+    for (orig, _) in moved.iter() {
+        if let Some(name) = orig.file_name().and_then(|n| n.to_str()) {
+            let _ = lockfile::remove(&plugins_dir, name);
+        }
+    }
+    match info_file.lock {
+        Some(entry) => lockfile::record(&plugins_dir, entry)?,
+        None => lockfile::remove(&plugins_dir, &info_file.file_name)?,
+    }
+    let _ = fs::remove_dir_all(&backup);
+    for key in moved_keys(&moved) {
+        prune(&plugins_dir, &key);
+    }
+    let msg = format!(
+        "Rolled back {} to {}",
+        info_file.id.as_deref().unwrap_or(&info_file.file_name),
+        info_file
+            .version
+            .as_deref()
+            .unwrap_or("the previous version")
+    );
+    info("backup", &msg);
+    Ok(msg)
+}
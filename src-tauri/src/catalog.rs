@@ -0,0 +1,299 @@
+use crate::download::{self, DownloadOptions, DownloadTask};
+use crate::logger::{emit_app_log, info, Level};
+use crate::models::{CatalogPlugin, CatalogPluginVersion, InstallFromUrlResult, LockSource};
+use crate::semver::{Version, VersionReq};
+use crate::util::{resolve_plugins_dir, DISABLED_DIR_NAME};
+use crate::{backup, lockfile, metadata, versions};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::fs;
+use tauri::{Emitter, Window};
+
+// Default public catalog published next to the versions manifest (override with AVRIX_CATALOG_URL)
+const DEFAULT_CATALOG_URL: &str = "https://s3.storage.skymunt.com/avrix-loader/catalog.json";
+const MAX_SIZE: u64 = 25 * 1024 * 1024;
+
+#[derive(serde::Deserialize)]
+struct Catalog {
+    plugins: Vec<CatalogPlugin>,
+}
+
+fn get_catalog_url() -> String {
+    if let Ok(s) = std::env::var("AVRIX_CATALOG_URL") {
+        let s = s.trim().to_string();
+        if !s.is_empty() {
+            return s;
+        }
+    }
+    DEFAULT_CATALOG_URL.to_string()
+}
+
+// Fetches the catalog with versions sorted newest-first and installed versions filled in.
+fn fetch_catalog() -> Result<Vec<CatalogPlugin>> {
+    let bytes = versions::http_get(&get_catalog_url())?;
+    let catalog: Catalog = serde_json::from_slice(&bytes)?;
+    let installed = installed_versions();
+    let mut plugins = catalog.plugins;
+    for p in plugins.iter_mut() {
+        p.versions
+            .sort_by(|a, b| crate::semver::compare_str(&b.version, &a.version));
+        p.installed_version = installed.get(&p.id).cloned();
+    }
+    plugins.sort_by_key(|p| p.name.to_lowercase());
+    Ok(plugins)
+}
+
+// Plugin id -> version of every installed jar (enabled or disabled).
+fn installed_versions() -> HashMap<String, String> {
+    let plugins_dir = resolve_plugins_dir();
+    let mut out = HashMap::new();
+    for dir in [plugins_dir.clone(), plugins_dir.join(DISABLED_DIR_NAME)] {
+        let rd = match fs::read_dir(&dir) {
+            Ok(rd) => rd,
+            Err(_) => continue,
+        };
+        for entry in rd.flatten() {
+            let path = entry.path();
+            if !path.is_file() || path.extension().and_then(|e| e.to_str()) != Some("jar") {
+                continue;
+            }
+            if let Ok(m) = metadata::extract_metadata_from_jar(&path) {
+                if let (Some(id), Some(v)) = (m.id, m.version) {
+                    out.entry(id).or_insert(v);
+                }
+            }
+        }
+    }
+    out
+}
+
+// Exact version first (`1.2` matches `1.2.0`), then the highest stable version matching a range;
+// without a version, the newest stable release (or the newest one if only pre-releases exist).
+fn pick_version<'a>(
+    plugin: &'a CatalogPlugin,
+    version: Option<&str>,
+) -> Option<&'a CatalogPluginVersion> {
+    let wanted = match version.map(|v| v.trim()).filter(|v| !v.is_empty()) {
+        Some(v) => v,
+        None => {
+            return plugin
+                .versions
+                .iter()
+                .find(|v| {
+                    Version::parse(&v.version)
+                        .map(|x| !x.is_prerelease())
+                        .unwrap_or(false)
+                })
+                .or(plugin.versions.first());
+        }
+    };
+    if let Some(exact) = Version::parse(wanted) {
+        return plugin.versions.iter().find(|v| {
+            Version::parse(&v.version)
+                .map(|x| x == exact)
+                .unwrap_or(false)
+        });
+    }
+    let req = VersionReq::parse(wanted).ok()?;
+    let best =
+        crate::semver::max_satisfying(plugin.versions.iter().map(|v| v.version.as_str()), &req)?;
+    plugin.versions.iter().find(|v| v.version == best)
+}
+
+fn matches_query(p: &CatalogPlugin, query: &str) -> bool {
+    let q = query.to_lowercase();
+    [
+        Some(&p.id),
+        Some(&p.name),
+        p.description.as_ref(),
+        p.author.as_ref(),
+    ]
+    .iter()
+    .flatten()
+    .any(|s| s.to_lowercase().contains(&q))
+}
+
+/// Lists catalog plugins whose id, name, description or author contains `query`,
+/// optionally restricted to an environment (`client`, `server`; `both` always matches).
+#[tauri::command]
+pub fn search_catalog(
+    query: Option<String>,
+    environment: Option<String>,
+    window: Window,
+) -> Result<Vec<CatalogPlugin>, String> {
+    info(
+        "catalog",
+        &format!(
+            "search_catalog invoked (query={:?}, environment={:?})",
+            query, environment
+        ),
+    );
+    let plugins = fetch_catalog().map_err(|e| {
+        let msg = format!("Catalogue introuvable ou inaccessible: {}", e);
+        let _ = window.emit("catalog-log", msg.clone());
+        msg
+    })?;
+    let query = query.unwrap_or_default();
+    let env = environment.map(|e| e.to_lowercase());
+    let out: Vec<CatalogPlugin> = plugins
+        .into_iter()
+        .filter(|p| query.trim().is_empty() || matches_query(p, query.trim()))
+        .filter(
+            |p| match (&env, p.environment.as_deref().map(|e| e.to_lowercase())) {
+                (None, _) | (_, None) => true,
+                (Some(want), Some(have)) => have == *want || have == "both",
+            },
+        )
+        .collect();
+    let _ = window.emit(
+        "catalog-log",
+        format!("{} plugins depuis le catalogue", out.len()),
+    );
+    Ok(out)
+}
+
+#[tauri::command]
+pub fn get_catalog_plugin(id: String) -> Result<CatalogPlugin, String> {
+    info(
+        "catalog",
+        &format!("get_catalog_plugin invoked (id={})", id),
+    );
+    fetch_catalog()
+        .map_err(|e| format!("Catalogue introuvable ou inaccessible: {}", e))?
+        .into_iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| "Plugin introuvable dans le catalogue".to_string())
+}
+
+/// Downloads a catalog version, checks its size and sha256 against the catalog entry and its
+/// metadata id, then moves it into the plugins folder.
+#[tauri::command]
+pub async fn install_catalog_plugin(
+    id: String,
+    version: Option<String>,
+    window: Window,
+) -> Result<InstallFromUrlResult, String> {
+    info(
+        "catalog",
+        &format!(
+            "install_catalog_plugin invoked (id={}, version={:?})",
+            id, version
+        ),
+    );
+    tauri::async_runtime::spawn_blocking(move || {
+        install_from_catalog(&id, version.as_deref(), &window).map_err(|e| {
+            let msg = e.to_string();
+            let _ = window.emit("plugin-install-log", msg.clone());
+            let _ = emit_app_log(&window, Level::Error, "catalog", &msg);
+            msg
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+fn install_from_catalog(
+    id: &str,
+    version: Option<&str>,
+    window: &Window,
+) -> Result<InstallFromUrlResult> {
+    let emit = |m: &str| {
+        info("catalog", m);
+        let _ = window.emit("plugin-install-log", m.to_string());
+        let _ = emit_app_log(window, Level::Info, "catalog", m);
+    };
+    let plugins = fetch_catalog()?;
+    let plugin = plugins
+        .iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| anyhow!("Plugin introuvable dans le catalogue: {}", id))?;
+    let entry = pick_version(plugin, version).ok_or_else(|| {
+        anyhow!(
+            "Version {} introuvable pour {}",
+            version.unwrap_or("latest"),
+            id
+        )
+    })?;
+    let expected_sha = entry.sha256.as_deref().ok_or_else(|| {
+        anyhow!(
+            "Le catalogue ne fournit pas de sha256 pour {}@{}",
+            id,
+            entry.version
+        )
+    })?;
+    emit(&format!(
+        "[CATALOG] {}@{}: {}",
+        id, entry.version, entry.url
+    ));
+    if entry.size.map(|s| s > MAX_SIZE).unwrap_or(false) {
+        return Err(anyhow!("Downloaded file exceeds maximum allowed size"));
+    }
+    let plugins_dir = resolve_plugins_dir();
+    fs::create_dir_all(&plugins_dir)?;
+    let file_name = entry
+        .file_name
+        .clone()
+        .unwrap_or_else(|| format!("{}-{}.jar", id, entry.version));
+    if file_name.contains(['/', '\\']) || !file_name.to_lowercase().ends_with(".jar") {
+        return Err(anyhow!("Nom de fichier invalide: {}", file_name));
+    }
+    // Streamed next to the destination, then renamed, so a failed install never leaves a
+    // partial jar; size and hash are checked before the download is kept
+    let tmp = plugins_dir.join(format!("{}.download", file_name));
+    let task = DownloadTask::start();
+    let downloaded = download::download(
+        &DownloadOptions::new(&entry.url, &tmp)
+            .max_size(MAX_SIZE)
+            .expect(Some(expected_sha), entry.size),
+        &task,
+        &download::window_progress(window),
+    )?;
+    emit(&format!("Received {} bytes", downloaded.size));
+    emit(&format!("SHA-256 verified: {}…", &downloaded.sha256[..16]));
+
+    let meta = match metadata::extract_metadata_from_jar(&tmp) {
+        Ok(m) => m,
+        Err(_) => {
+            let _ = fs::remove_file(&tmp);
+            return Err(anyhow!("metadata.yml not found in archive"));
+        }
+    };
+    if meta.id.as_deref() != Some(id) {
+        let _ = fs::remove_file(&tmp);
+        return Err(anyhow!(
+            "Le jar déclare l'id {} au lieu de {}",
+            meta.id.as_deref().unwrap_or("?"),
+            id
+        ));
+    }
+    emit(&format!(
+        "Final copy to {}",
+        plugins_dir.join(&file_name).to_string_lossy()
+    ));
+    let dest = backup::install_atomic(&plugins_dir, &tmp, &plugins_dir, &file_name, &emit)?;
+    match lockfile::entry_for(
+        &dest,
+        LockSource::Url {
+            url: entry.url.clone(),
+        },
+    ) {
+        Ok(lock_entry) => {
+            if let Err(e) = lockfile::record(&plugins_dir, lock_entry) {
+                crate::logger::error("catalog", &format!("plugins.lock update failed: {}", e));
+            }
+        }
+        Err(e) => crate::logger::error("catalog", &format!("Hash error: {}", e)),
+    }
+    emit("Done");
+    Ok(InstallFromUrlResult {
+        message: format!(
+            "Plugin downloaded and installed: {}",
+            dest.to_string_lossy()
+        ),
+        size: downloaded.size,
+        sha256: downloaded.sha256,
+        name: meta.name.clone(),
+        version: meta.version.clone(),
+        environment: meta.environment.clone(),
+    })
+}
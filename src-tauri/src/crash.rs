@@ -0,0 +1,368 @@
+use crate::environment::LaunchMode;
+use crate::logger::{info, warn};
+use crate::models::{CrashCulprit, CrashKind, CrashReport};
+use crate::sessions;
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// Enough to hold the last stack traces without keeping a whole session in memory
+const TAIL_LINES: usize = 2000;
+const MAX_EVIDENCE: usize = 8;
+
+// `at com.example.Foo.bar(Foo.java:12)`, optionally with a module prefix (`java.base/`)
+static STACK_FRAME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*at\s+(?:\S+/)?([A-Za-z_$][\w$]*(?:\.[\w$]+)+)\.[\w$<>]+\(").unwrap()
+});
+// Java frames of an hs_err file: `j  com.example.Foo.bar()V+5`, `J 123 c2 com.example.Foo.bar(I)V`
+static HS_ERR_FRAME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^#?\s*[jJ]\s+(?:\d+\s+\S+\s+)?([A-Za-z_$][\w$]*(?:\.[\w$]+)+)\.[\w$<>]+\(")
+        .unwrap()
+});
+static MISSING_CLASS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?:ClassNotFoundException|NoClassDefFoundError):\s*([\w$./]+)").unwrap()
+});
+
+/// Last lines printed by a process (stdout and stderr), kept for the exit analysis.
+#[derive(Default)]
+pub struct OutputTail {
+    lines: Mutex<VecDeque<String>>,
+}
+
+impl OutputTail {
+    pub fn push(&self, line: &str) {
+        if let Ok(mut lines) = self.lines.lock() {
+            if lines.len() >= TAIL_LINES {
+                lines.pop_front();
+            }
+            lines.push_back(line.to_string());
+        }
+    }
+
+    fn snapshot(&self) -> Vec<String> {
+        self.lines
+            .lock()
+            .map(|l| l.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// What is known about a process once it has exited.
+pub struct ExitContext<'a> {
+    pub pid: u32,
+    pub mode: LaunchMode,
+    pub exit_code: Option<i32>,
+    pub work_dir: &'a Path,
+    pub max_heap_mb: Option<u64>,
+    pub output: &'a OutputTail,
+    pub session_log: Option<String>,
+}
+
+/// `-Xmx` of a launch command, in MB.
+pub fn max_heap_mb(args: &[String]) -> Option<u64> {
+    args.iter()
+        .rev()
+        .find_map(|a| crate::launch_options::heap_mb(a, "-Xmx"))
+}
+
+// The JVM writes hs_err_pid<pid>.log in its working directory, or in the temp dir when it can't.
+fn find_hs_err(work_dir: &Path, pid: u32) -> Option<PathBuf> {
+    let name = format!("hs_err_pid{}.log", pid);
+    [work_dir.to_path_buf(), std::env::temp_dir()]
+        .into_iter()
+        .map(|d| d.join(&name))
+        .find(|p| p.is_file())
+}
+
+// Package -> (jar file name, plugin id) for every enabled external jar of the plugins folder.
+fn plugin_packages(plugins_dir: &Path) -> HashMap<String, (String, Option<String>)> {
+    let mut out = HashMap::new();
+    let rd = match fs::read_dir(plugins_dir) {
+        Ok(rd) => rd,
+        Err(_) => return out,
+    };
+    for entry in rd.flatten() {
+        let path = entry.path();
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if !path.is_file()
+            || !file_name.to_lowercase().ends_with(".jar")
+            || file_name.starts_with("Avrix-Core")
+        {
+            continue;
+        }
+        let mut archive = match fs::File::open(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|f| zip::ZipArchive::new(f).map_err(anyhow::Error::from))
+        {
+            Ok(a) => a,
+            Err(_) => continue,
+        };
+        let id = crate::metadata::extract_metadata_from_jar(&path)
+            .ok()
+            .and_then(|m| m.id);
+        for i in 0..archive.len() {
+            let name = match archive.by_index(i) {
+                Ok(f) => f.name().to_string(),
+                Err(_) => continue,
+            };
+            if !name.ends_with(".class") || name.starts_with("META-INF/") {
+                continue;
+            }
+            if let Some((dir, _)) = name.rsplit_once('/') {
+                out.entry(dir.replace('/', "."))
+                    .or_insert_with(|| (file_name.clone(), id.clone()));
+            }
+        }
+    }
+    out
+}
+
+// First frame (top of the stack) whose package belongs to an installed plugin.
+fn find_culprit(
+    frames: &[String],
+    packages: &HashMap<String, (String, Option<String>)>,
+) -> Option<CrashCulprit> {
+    frames.iter().find_map(|class| {
+        let (package, _) = class.rsplit_once('.')?;
+        let (file_name, plugin_id) = packages.get(package)?;
+        Some(CrashCulprit {
+            plugin_id: plugin_id.clone(),
+            file_name: file_name.clone(),
+            package: package.to_string(),
+            frame: class.clone(),
+        })
+    })
+}
+
+fn frames_of(lines: &[String], re: &Regex) -> Vec<String> {
+    lines
+        .iter()
+        .filter_map(|l| re.captures(l).map(|c| c[1].to_string()))
+        .collect()
+}
+
+// Suggested -Xmx after an OutOfMemoryError: +50% (at least 1 GB more), within 75% of the RAM.
+fn suggested_heap_mb(current: u64) -> Option<u64> {
+    use sysinfo::{MemoryRefreshKind, RefreshKind, System};
+    let mut sys = System::new_with_specifics(
+        RefreshKind::nothing().with_memory(MemoryRefreshKind::everything()),
+    );
+    sys.refresh_memory();
+    let total_mb = sys.total_memory() / (1024 * 1024);
+    let wanted = (current + (current / 2).max(1024)).div_ceil(512) * 512;
+    let cap = total_mb * 3 / 4 / 512 * 512;
+    let target = if total_mb == 0 {
+        wanted
+    } else {
+        wanted.min(cap)
+    };
+    (target > current).then_some(target)
+}
+
+/// Classifies an exit: `None` for a clean exit (code 0 and no JVM crash file).
+pub fn analyze(ctx: &ExitContext) -> Option<CrashReport> {
+    let hs_err = find_hs_err(ctx.work_dir, ctx.pid);
+    let abnormal = matches!(ctx.exit_code, Some(c) if c != 0) || hs_err.is_some();
+    if !abnormal {
+        return None;
+    }
+    let output = ctx.output.snapshot();
+    let hs_err_lines: Vec<String> = hs_err
+        .as_ref()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .map(|s| s.lines().map(|l| l.to_string()).collect())
+        .unwrap_or_default();
+    let first = |needle: &str| output.iter().find(|l| l.contains(needle)).cloned();
+    let hs_err_has = |needle: &str| hs_err_lines.iter().any(|l| l.contains(needle));
+
+    let oom = first("java.lang.OutOfMemoryError");
+    let native_oom =
+        hs_err_has("insufficient memory for the Java Runtime") || hs_err_has("Out of Memory Error");
+    let unsatisfied = first("java.lang.UnsatisfiedLinkError");
+    let missing = first("ClassNotFoundException").or_else(|| first("NoClassDefFoundError"));
+
+    let mut evidence: Vec<String> = Vec::new();
+    let mut suggestions: Vec<String> = Vec::new();
+    let heap = ctx
+        .max_heap_mb
+        .map(|m| format!("{} MB", m))
+        .unwrap_or_else(|| "the current value".into());
+    let (kind, summary) = if oom.is_some() || native_oom {
+        if let Some(l) = &oom {
+            evidence.push(l.trim().to_string());
+        }
+        if native_oom {
+            evidence.push("hs_err: the JVM could not allocate native memory".into());
+            suggestions.push(
+                "The system ran out of native memory: close other applications or lower -Xmx so the game keeps room outside the heap.".into(),
+            );
+        } else if oom
+            .as_deref()
+            .map(|l| l.contains("Metaspace"))
+            .unwrap_or(false)
+        {
+            suggestions.push(
+                "Metaspace is exhausted: a plugin may be loading classes in a loop; try without recently added plugins.".into(),
+            );
+        } else {
+            match ctx.max_heap_mb.and_then(suggested_heap_mb) {
+                Some(target) => suggestions.push(format!(
+                    "Raise -Xmx from {} to {} MB in the memory settings.",
+                    heap, target
+                )),
+                None => suggestions.push(format!(
+                    "The heap ({}) is already close to the installed RAM: reduce loaded mods or the render distance.",
+                    heap
+                )),
+            }
+        }
+        (
+            CrashKind::OutOfMemory,
+            format!(
+                "The {} ran out of memory (-Xmx {})",
+                ctx.mode.as_str(),
+                heap
+            ),
+        )
+    } else if let Some(l) = &unsatisfied {
+        evidence.push(l.trim().to_string());
+        suggestions.push(
+            "A native library could not be loaded: check that the game root is complete and that a 64-bit Java matching the game is used.".into(),
+        );
+        (
+            CrashKind::UnsatisfiedLink,
+            "A native library failed to load (UnsatisfiedLinkError)".into(),
+        )
+    } else if let Some(l) = &missing {
+        evidence.push(l.trim().to_string());
+        let class = MISSING_CLASS
+            .captures(l)
+            .map(|c| c[1].replace('/', "."))
+            .unwrap_or_else(|| "?".into());
+        suggestions.push(format!(
+            "Class {} is missing: a plugin dependency may not be installed, or a plugin targets another Avrix core or game version.",
+            class
+        ));
+        (
+            CrashKind::ClassNotFound,
+            format!("A required class was not found: {}", class),
+        )
+    } else if hs_err.is_some() {
+        if let Some(pos) = hs_err_lines
+            .iter()
+            .position(|l| l.starts_with("# Problematic frame:"))
+        {
+            if let Some(frame) = hs_err_lines.get(pos + 1) {
+                evidence.push(format!(
+                    "Problematic frame: {}",
+                    frame.trim_start_matches('#').trim()
+                ));
+            }
+        }
+        suggestions.push(
+            "The JVM crashed in native code: update the graphics drivers and check the hs_err file for the failing library.".into(),
+        );
+        (
+            CrashKind::NativeCrash,
+            "The Java virtual machine crashed (hs_err report written)".into(),
+        )
+    } else {
+        (
+            CrashKind::AbnormalExit,
+            format!(
+                "The {} exited with code {}",
+                ctx.mode.as_str(),
+                ctx.exit_code.unwrap_or(-1)
+            ),
+        )
+    };
+
+    let mut frames = frames_of(&output, &STACK_FRAME);
+    // The last trace printed is the one that took the process down
+    if let Some(last_trace) = output
+        .iter()
+        .rposition(|l| l.contains("Exception") || l.contains("Error"))
+    {
+        let mut tail = frames_of(&output[last_trace..], &STACK_FRAME);
+        tail.append(&mut frames);
+        frames = tail;
+    }
+    if kind == CrashKind::NativeCrash || frames.is_empty() {
+        let mut hs = frames_of(&hs_err_lines, &HS_ERR_FRAME);
+        hs.append(&mut frames);
+        frames = hs;
+    }
+    let culprit = find_culprit(
+        &frames,
+        &plugin_packages(&crate::util::resolve_plugins_dir()),
+    );
+    if let Some(c) = &culprit {
+        evidence.push(format!(
+            "Stack frame {} belongs to {}",
+            c.frame, c.file_name
+        ));
+        suggestions.push(format!(
+            "Disable {} and launch again to confirm it causes the crash, then look for an update.",
+            c.plugin_id.as_deref().unwrap_or(&c.file_name)
+        ));
+    }
+    if kind == CrashKind::AbnormalExit && culprit.is_none() {
+        suggestions.push("See the session log for the last messages printed by the game.".into());
+    }
+    evidence.truncate(MAX_EVIDENCE);
+
+    Some(CrashReport {
+        kind,
+        mode: ctx.mode.as_str().to_string(),
+        exit_code: ctx.exit_code,
+        summary,
+        culprit,
+        evidence,
+        suggestions,
+        hs_err_file: hs_err.map(|p| p.to_string_lossy().to_string()),
+        session_log: ctx.session_log.clone(),
+        created_at: chrono::Utc::now().timestamp(),
+    })
+}
+
+/// Stores the report next to its session log (`<session>.crash.json`).
+pub fn save_report(report: &CrashReport) {
+    let session = match &report.session_log {
+        Some(s) => s,
+        None => return,
+    };
+    let path = sessions::report_path(&sessions::sessions_dir().join(session));
+    let res = serde_json::to_string_pretty(report)
+        .map_err(anyhow::Error::from)
+        .and_then(|s| fs::write(&path, s).map_err(anyhow::Error::from));
+    match res {
+        Ok(_) => info(
+            "crash",
+            &format!("Crash report written: {}", path.display()),
+        ),
+        Err(e) => warn("crash", &format!("Cannot write crash report: {}", e)),
+    }
+}
+
+fn load_report(file_name: &str) -> Result<CrashReport> {
+    let log = sessions::session_path(file_name)?;
+    let path = sessions::report_path(&log);
+    if !path.is_file() {
+        return Err(anyhow!("No crash report for {}", file_name));
+    }
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+/// Crash report of a session, by session log file name.
+#[tauri::command]
+pub fn get_crash_report(file_name: String) -> Result<CrashReport, String> {
+    info(
+        "crash",
+        &format!("get_crash_report invoked (file_name={})", file_name),
+    );
+    load_report(&file_name).map_err(|e| e.to_string())
+}
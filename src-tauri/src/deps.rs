@@ -0,0 +1,186 @@
+use crate::models::{DependencyIssue, DependencyIssueKind, DependencyReport, PluginEntry};
+use crate::semver::{Version, VersionReq};
+use std::collections::HashMap;
+
+/// Resolves the `dependencies` map declared by every plugin against the given set
+/// (external jars, the core jar and its embedded internal plugins).
+/// A requirement prefixed with `?` (e.g. `?>=1.2`) is optional: it is reported but never blocks a launch.
+/// Disabled plugins are ignored, both as dependents and as providers.
+pub fn resolve(plugins: &[PluginEntry]) -> DependencyReport {
+    let plugins: Vec<&PluginEntry> = plugins
+        .iter()
+        .filter(|p| p.enabled != Some(false))
+        .collect();
+    // First entry wins when several jars declare the same id
+    let mut by_id: HashMap<&str, &PluginEntry> = HashMap::new();
+    for p in plugins.iter().copied() {
+        if let Some(id) = p.id.as_deref() {
+            by_id.entry(id).or_insert(p);
+        }
+    }
+
+    let mut issues: Vec<DependencyIssue> = Vec::new();
+    for p in plugins.iter() {
+        let deps = match &p.dependencies {
+            Some(d) if !d.is_empty() => d,
+            _ => continue,
+        };
+        let plugin_id = p.id.clone().unwrap_or_else(|| p.name.clone());
+        let plugin_name = p.display_name.clone().unwrap_or_else(|| p.name.clone());
+        let mut dep_ids: Vec<&String> = deps.keys().collect();
+        dep_ids.sort();
+        for dep_id in dep_ids {
+            let raw_req = deps[dep_id].trim();
+            let optional = raw_req.starts_with('?');
+            let required = raw_req.trim_start_matches('?').trim().to_string();
+            let issue = |kind: DependencyIssueKind, found: Option<String>, message: String| {
+                DependencyIssue {
+                    plugin_id: plugin_id.clone(),
+                    plugin_name: plugin_name.clone(),
+                    dependency: dep_id.clone(),
+                    required: required.clone(),
+                    found,
+                    optional,
+                    kind,
+                    message,
+                }
+            };
+            match by_id.get(dep_id.as_str()) {
+                None => issues.push(issue(
+                    DependencyIssueKind::Missing,
+                    None,
+                    format!(
+                        "{} requires {} ({}) which is not installed",
+                        plugin_name,
+                        dep_id,
+                        display_req(&required)
+                    ),
+                )),
+                Some(dep) => {
+                    let found = dep.version.clone();
+                    let req = match VersionReq::parse(&required) {
+                        Ok(r) => r,
+                        Err(e) => {
+                            issues.push(issue(
+                                DependencyIssueKind::VersionMismatch,
+                                found.clone(),
+                                format!("{} declares {} with {}", plugin_name, dep_id, e),
+                            ));
+                            continue;
+                        }
+                    };
+                    let ok = match found.as_deref().and_then(Version::parse) {
+                        Some(v) => req.matches(&v),
+                        // Unknown or unparsable version only satisfies a wildcard requirement
+                        None => is_wildcard(&required),
+                    };
+                    if !ok {
+                        issues.push(issue(
+                            DependencyIssueKind::VersionMismatch,
+                            found.clone(),
+                            format!(
+                                "{} requires {} {} but {} is installed",
+                                plugin_name,
+                                dep_id,
+                                display_req(&required),
+                                found.as_deref().unwrap_or("an unknown version")
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    for cycle in find_cycles(&by_id) {
+        let path = cycle.join(" -> ");
+        for (i, id) in cycle.iter().take(cycle.len() - 1).enumerate() {
+            let p = by_id[id.as_str()];
+            let next = &cycle[i + 1];
+            issues.push(DependencyIssue {
+                plugin_id: id.clone(),
+                plugin_name: p.display_name.clone().unwrap_or_else(|| p.name.clone()),
+                dependency: next.clone(),
+                required: p
+                    .dependencies
+                    .as_ref()
+                    .and_then(|d| d.get(next))
+                    .map(|r| r.trim_start_matches('?').trim().to_string())
+                    .unwrap_or_default(),
+                found: by_id.get(next.as_str()).and_then(|d| d.version.clone()),
+                optional: false,
+                kind: DependencyIssueKind::Cycle,
+                message: format!("Dependency cycle: {}", path),
+            });
+        }
+    }
+
+    let satisfied = !issues.iter().any(|i| !i.optional);
+    DependencyReport { satisfied, issues }
+}
+
+/// One line per blocking issue, suitable for launch logs and error messages.
+pub fn format_report(report: &DependencyReport) -> String {
+    report
+        .issues
+        .iter()
+        .filter(|i| !i.optional)
+        .map(|i| format!(" - [{}] {}", i.plugin_id, i.message))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Returns each elementary cycle once, as a closed path (first id repeated at the end).
+fn find_cycles(by_id: &HashMap<&str, &PluginEntry>) -> Vec<Vec<String>> {
+    fn visit<'a>(
+        id: &'a str,
+        by_id: &HashMap<&'a str, &'a PluginEntry>,
+        stack: &mut Vec<&'a str>,
+        done: &mut std::collections::HashSet<&'a str>,
+        out: &mut Vec<Vec<String>>,
+    ) {
+        if let Some(pos) = stack.iter().position(|s| *s == id) {
+            let mut cycle: Vec<String> = stack[pos..].iter().map(|s| s.to_string()).collect();
+            cycle.push(id.to_string());
+            out.push(cycle);
+            return;
+        }
+        if done.contains(id) {
+            return;
+        }
+        stack.push(id);
+        if let Some(deps) = by_id.get(id).and_then(|p| p.dependencies.as_ref()) {
+            let mut keys: Vec<&String> = deps.keys().collect();
+            keys.sort();
+            for dep in keys {
+                if let Some((k, _)) = by_id.get_key_value(dep.as_str()) {
+                    visit(k, by_id, stack, done, out);
+                }
+            }
+        }
+        stack.pop();
+        done.insert(id);
+    }
+
+    let mut ids: Vec<&str> = by_id.keys().copied().collect();
+    ids.sort();
+    let mut done = std::collections::HashSet::new();
+    let mut out = Vec::new();
+    for id in ids {
+        visit(id, by_id, &mut Vec::new(), &mut done, &mut out);
+    }
+    out
+}
+
+fn is_wildcard(req: &str) -> bool {
+    let r = req.trim();
+    r.is_empty() || r == "*" || r.eq_ignore_ascii_case("any")
+}
+
+fn display_req(req: &str) -> String {
+    if is_wildcard(req) {
+        "any version".into()
+    } else {
+        req.to_string()
+    }
+}
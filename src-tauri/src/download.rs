@@ -0,0 +1,1070 @@
+use crate::logger::{info, warn};
+use crate::models::{DownloadProgress, DownloadState};
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Window};
+
+const USER_AGENT: &str = "AvrixLauncher/1.0";
+/// A download is considered stalled when nothing arrives for this long.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+const CANCEL_POLL: Duration = Duration::from_millis(100);
+/// A request lives at most this many idle timeouts; a transfer still going by then carries on
+/// with a `Range` request, so a reader stuck on a dead socket always ends.
+const ATTEMPT_SPAN: u32 = 10;
+const CHUNK_SIZE: usize = 64 * 1024;
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+/// Cap for in-memory fetches (manifests, catalogs).
+const FETCH_MAX_SIZE: u64 = 10 * 1024 * 1024;
+
+static TASKS: Lazy<Mutex<HashMap<String, Arc<AtomicBool>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+static ACTIVE_TARGETS: Lazy<Mutex<HashSet<PathBuf>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+static NEXT_TASK_ID: AtomicU64 = AtomicU64::new(1);
+static NEXT_SCRATCH_ID: AtomicU64 = AtomicU64::new(1);
+static LIVE_READERS: AtomicUsize = AtomicUsize::new(0);
+
+/// A running download that `cancel_download` can stop; unregistered when dropped.
+pub struct DownloadTask {
+    pub id: String,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl DownloadTask {
+    pub fn start() -> DownloadTask {
+        let id = format!("dl-{}", NEXT_TASK_ID.fetch_add(1, Ordering::Relaxed));
+        let cancelled = Arc::new(AtomicBool::new(false));
+        if let Ok(mut tasks) = TASKS.lock() {
+            tasks.insert(id.clone(), cancelled.clone());
+        }
+        DownloadTask { id, cancelled }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for DownloadTask {
+    fn drop(&mut self) {
+        if let Ok(mut tasks) = TASKS.lock() {
+            tasks.remove(&self.id);
+        }
+    }
+}
+
+/// Flags a running download as cancelled. Returns false when no such task is running.
+pub fn cancel(task_id: &str) -> bool {
+    match TASKS.lock().ok().and_then(|t| t.get(task_id).cloned()) {
+        Some(flag) => {
+            flag.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    }
+}
+
+pub struct DownloadOptions {
+    pub url: String,
+    pub dest: PathBuf,
+    pub max_size: Option<u64>,
+    /// Expected SHA-256 (hex) and size; a mismatching file is discarded instead of moved to `dest`
+    pub sha256: Option<String>,
+    pub size: Option<u64>,
+    pub headers: Vec<(String, String)>,
+    /// Extra attempts after a network error, a truncated body or a 408/429/5xx answer
+    pub retries: u32,
+    /// Wait before the first retry, doubled for each following one
+    pub backoff: Duration,
+    /// Longest wait for the next bytes before the attempt counts as stalled
+    pub idle_timeout: Duration,
+}
+
+impl DownloadOptions {
+    pub fn new(url: &str, dest: &Path) -> DownloadOptions {
+        DownloadOptions {
+            url: url.to_string(),
+            dest: dest.to_path_buf(),
+            max_size: None,
+            sha256: None,
+            size: None,
+            headers: Vec::new(),
+            retries: 3,
+            backoff: Duration::from_secs(1),
+            idle_timeout: IDLE_TIMEOUT,
+        }
+    }
+
+    pub fn max_size(mut self, bytes: u64) -> DownloadOptions {
+        self.max_size = Some(bytes);
+        self
+    }
+
+    pub fn expect(mut self, sha256: Option<&str>, size: Option<u64>) -> DownloadOptions {
+        self.sha256 = sha256.map(|s| s.trim().to_ascii_lowercase());
+        self.size = size;
+        self
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> DownloadOptions {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Downloaded {
+    pub path: PathBuf,
+    pub size: u64,
+    pub sha256: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+/// Validators of a partial download, kept next to it so a later attempt (or a later launcher
+/// run) only resumes when the remote file is unchanged.
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+struct PartInfo {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+/// The body is larger than [`DownloadOptions::max_size`]. `size` is the announced length, or
+/// what had arrived when the transfer was stopped.
+#[derive(Debug)]
+pub struct TooLarge {
+    pub size: u64,
+    pub max: u64,
+}
+
+impl std::fmt::Display for TooLarge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Downloaded file exceeds maximum allowed size ({} > {} bytes)",
+            self.size, self.max
+        )
+    }
+}
+
+impl std::error::Error for TooLarge {}
+
+enum Failure {
+    Retry(anyhow::Error),
+    Fatal(anyhow::Error),
+}
+
+fn suffixed(path: &Path, suffix: &str) -> PathBuf {
+    let mut s = path.as_os_str().to_os_string();
+    s.push(suffix);
+    PathBuf::from(s)
+}
+
+fn load_part_info(info_path: &Path) -> Option<PartInfo> {
+    serde_json::from_slice(&fs::read(info_path).ok()?).ok()
+}
+
+fn file_name_of(url: &str) -> String {
+    url.split(['?', '#'])
+        .next()
+        .and_then(|u| u.rsplit('/').next())
+        .filter(|n| !n.is_empty())
+        .unwrap_or("download")
+        .to_string()
+}
+
+/// One-off temp file for `url`, unique to this call so concurrent downloads never share or
+/// resume each other's bytes. The file and any partial data are removed when dropped.
+pub struct Scratch(PathBuf);
+
+impl Scratch {
+    pub fn new(url: &str) -> Scratch {
+        Scratch(std::env::temp_dir().join(format!(
+            "avrix-scratch-{}-{}-{}",
+            std::process::id(),
+            NEXT_SCRATCH_ID.fetch_add(1, Ordering::SeqCst),
+            file_name_of(url)
+        )))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let part = suffixed(&self.0, ".part");
+        for p in [suffixed(&part, ".json"), part, self.0.clone()] {
+            if p.exists() {
+                let _ = fs::remove_file(p);
+            }
+        }
+    }
+}
+
+/// Hold on a destination for the length of a download, so two downloads never write to or
+/// resume from the same `.part` file (e.g. two installs of one release via [`temp_target`]).
+struct TargetClaim(PathBuf);
+
+impl TargetClaim {
+    fn take(dest: &Path) -> Result<TargetClaim> {
+        let mut active = ACTIVE_TARGETS.lock().unwrap();
+        if !active.insert(dest.to_path_buf()) {
+            return Err(anyhow!(
+                "A download to {} is already in progress",
+                dest.display()
+            ));
+        }
+        Ok(TargetClaim(dest.to_path_buf()))
+    }
+}
+
+impl Drop for TargetClaim {
+    fn drop(&mut self) {
+        ACTIVE_TARGETS.lock().unwrap().remove(&self.0);
+    }
+}
+
+/// Stable temp location for `url`, so an interrupted download is resumed by the next call.
+pub fn temp_target(url: &str) -> PathBuf {
+    let key = hex::encode(Sha256::digest(url.as_bytes()));
+    std::env::temp_dir().join(format!(
+        "avrix-download-{}-{}",
+        &key[..16],
+        file_name_of(url)
+    ))
+}
+
+struct Tracker<'a> {
+    task: &'a DownloadTask,
+    url: &'a str,
+    file_name: String,
+    on_progress: &'a dyn Fn(&DownloadProgress),
+    attempt: u32,
+    last_emit: Option<Instant>,
+}
+
+impl Tracker<'_> {
+    fn emit(
+        &mut self,
+        state: DownloadState,
+        downloaded: u64,
+        total: Option<u64>,
+        speed_bps: u64,
+        message: Option<String>,
+    ) {
+        self.last_emit = Some(Instant::now());
+        let eta_secs = match (total, speed_bps) {
+            (Some(t), s) if s > 0 && t >= downloaded => Some((t - downloaded) / s),
+            _ => None,
+        };
+        (self.on_progress)(&DownloadProgress {
+            task_id: self.task.id.clone(),
+            url: self.url.to_string(),
+            file_name: self.file_name.clone(),
+            state,
+            downloaded,
+            total,
+            speed_bps,
+            eta_secs,
+            attempt: self.attempt,
+            message,
+        });
+    }
+
+    fn due(&self) -> bool {
+        self.last_emit
+            .map(|t| t.elapsed() >= PROGRESS_INTERVAL)
+            .unwrap_or(true)
+    }
+}
+
+fn content_range_total(value: &str) -> Option<u64> {
+    value.rsplit('/').next()?.trim().parse().ok()
+}
+
+fn check_size(size: u64, max: Option<u64>) -> std::result::Result<(), Failure> {
+    match max {
+        Some(max) if size > max => Err(Failure::Fatal(TooLarge { size, max }.into())),
+        _ => Ok(()),
+    }
+}
+
+/// What the connection thread reports.
+enum Chunk {
+    Head {
+        status: i32,
+        headers: HashMap<String, String>,
+    },
+    Data(Vec<u8>),
+    End,
+    Failed(String),
+}
+
+/// Counts the connection threads still alive, including those an attempt gave up on.
+struct ReaderGuard;
+
+impl ReaderGuard {
+    fn enter() -> ReaderGuard {
+        LIVE_READERS.fetch_add(1, Ordering::SeqCst);
+        ReaderGuard
+    }
+}
+
+impl Drop for ReaderGuard {
+    fn drop(&mut self) {
+        LIVE_READERS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// A response read on its own thread. minreq's timeout is a deadline for the whole request,
+/// not a per-read one, so stalls are detected here instead: an attempt fails when no chunk
+/// arrives for `idle`. The request deadline is only a backstop of `ATTEMPT_SPAN` idle
+/// timeouts that lets an abandoned thread blocked in `read` give up its socket.
+struct Connection {
+    rx: Receiver<Chunk>,
+    idle: Duration,
+}
+
+impl Connection {
+    fn open(req: minreq::Request, idle: Duration) -> Connection {
+        let deadline = (idle * ATTEMPT_SPAN).as_secs_f64().ceil().max(1.0) as u64;
+        let req = req.with_timeout(deadline);
+        let (tx, rx) = mpsc::sync_channel(8);
+        std::thread::spawn(move || {
+            let _alive = ReaderGuard::enter();
+            let mut resp = match req.send_lazy() {
+                Ok(r) => r,
+                Err(e) => {
+                    let _ = tx.send(Chunk::Failed(format!("Request error: {}", e)));
+                    return;
+                }
+            };
+            let head = Chunk::Head {
+                status: resp.status_code,
+                headers: std::mem::take(&mut resp.headers),
+            };
+            if tx.send(head).is_err() {
+                return;
+            }
+            let mut buf = vec![0u8; CHUNK_SIZE];
+            loop {
+                let chunk = match resp.read(&mut buf) {
+                    Ok(0) => Chunk::End,
+                    Ok(n) => Chunk::Data(buf[..n].to_vec()),
+                    Err(e) => Chunk::Failed(format!("Connection error: {}", e)),
+                };
+                let last = !matches!(chunk, Chunk::Data(_));
+                // The receiver is gone once the attempt gave up
+                if tx.send(chunk).is_err() || last {
+                    return;
+                }
+            }
+        });
+        Connection { rx, idle }
+    }
+
+    fn next(&self, task: &DownloadTask) -> std::result::Result<Chunk, Failure> {
+        let stalled_at = Instant::now() + self.idle;
+        loop {
+            if task.is_cancelled() {
+                return Err(Failure::Fatal(anyhow!("Download cancelled")));
+            }
+            match self.rx.recv_timeout(CANCEL_POLL) {
+                Ok(Chunk::Failed(e)) => return Err(Failure::Retry(anyhow!(e))),
+                Ok(chunk) => return Ok(chunk),
+                Err(RecvTimeoutError::Timeout) if Instant::now() < stalled_at => {}
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(Failure::Retry(anyhow!(
+                        "No data received for {} s",
+                        self.idle.as_secs_f32()
+                    )))
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(Failure::Retry(anyhow!("Connection lost")))
+                }
+            }
+        }
+    }
+}
+
+/// One request: resumes `part` with a `Range` request when it holds data, else starts over.
+/// `own_part` tells that `part` was written by this same download.
+fn attempt(
+    opts: &DownloadOptions,
+    part: &Path,
+    validators: &mut PartInfo,
+    own_part: bool,
+    tracker: &mut Tracker,
+) -> std::result::Result<Downloaded, Failure> {
+    let mut offset = fs::metadata(part).map(|m| m.len()).unwrap_or(0);
+    if offset > 0 && !own_part && validators.etag.is_none() && validators.last_modified.is_none() {
+        // Left by an earlier run, and without a validator the server cannot tell us the file
+        // changed since: start over
+        offset = 0;
+    }
+    let mut req = minreq::get(opts.url.as_str()).with_header("User-Agent", USER_AGENT);
+    for (k, v) in opts.headers.iter() {
+        req = req.with_header(k.as_str(), v.as_str());
+    }
+    if offset > 0 {
+        req = req.with_header("Range", format!("bytes={}-", offset));
+        if let Some(v) = validators.etag.clone().or(validators.last_modified.clone()) {
+            req = req.with_header("If-Range", v);
+        }
+    }
+    let conn = Connection::open(req, opts.idle_timeout);
+    let (status, headers) = match conn.next(tracker.task)? {
+        Chunk::Head { status, headers } => (status, headers),
+        _ => return Err(Failure::Retry(anyhow!("Connection lost"))),
+    };
+    let content_length = headers
+        .get("content-length")
+        .and_then(|v| v.parse::<u64>().ok());
+    let total = match status {
+        206 if offset > 0 => headers
+            .get("content-range")
+            .and_then(|v| content_range_total(v))
+            .or(content_length.map(|l| l + offset)),
+        200..=299 => {
+            // Full body: the server ignored the range or the remote file changed
+            offset = 0;
+            content_length
+        }
+        416 if offset > 0 => {
+            let _ = fs::remove_file(part);
+            return Err(Failure::Retry(anyhow!("Partial download no longer valid")));
+        }
+        code @ (408 | 429 | 500..=599) => {
+            return Err(Failure::Retry(anyhow!("HTTP {}", code)));
+        }
+        code => return Err(Failure::Fatal(anyhow!("Download failed (status {})", code))),
+    };
+    if let Some(t) = total {
+        check_size(t, opts.max_size)?;
+    }
+    if let (Some(t), Some(expected)) = (total, opts.size) {
+        if t != expected {
+            return Err(Failure::Fatal(anyhow!(
+                "Size mismatch: expected {} bytes, server announces {}",
+                expected,
+                t
+            )));
+        }
+    }
+    validators.url = opts.url.clone();
+    validators.etag = headers.get("etag").cloned();
+    validators.last_modified = headers.get("last-modified").cloned();
+    if let Ok(bytes) = serde_json::to_vec(validators) {
+        let _ = fs::write(suffixed(part, ".json"), bytes);
+    }
+
+    let mut hasher = Sha256::new();
+    let file = if offset > 0 {
+        let mut existing = fs::File::open(part).map_err(|e| Failure::Fatal(anyhow!(e)))?;
+        std::io::copy(&mut existing, &mut hasher).map_err(|e| Failure::Fatal(anyhow!(e)))?;
+        OpenOptions::new().append(true).open(part)
+    } else {
+        fs::File::create(part)
+    }
+    .map_err(|e| Failure::Fatal(anyhow!("Cannot write {}: {}", part.display(), e)))?;
+    let mut out = BufWriter::new(file);
+    if offset > 0 {
+        info(
+            "download",
+            &format!("Resuming {} at {} bytes", opts.url, offset),
+        );
+    }
+
+    let started = Instant::now();
+    let mut downloaded = offset;
+    tracker.emit(DownloadState::Downloading, downloaded, total, 0, None);
+    loop {
+        let buf = match conn.next(tracker.task) {
+            Ok(Chunk::Data(buf)) => buf,
+            Ok(_) => break,
+            Err(e) => {
+                // Keep what was received for the next attempt
+                let _ = out.flush();
+                return Err(e);
+            }
+        };
+        out.write_all(&buf)
+            .map_err(|e| Failure::Fatal(anyhow!(e)))?;
+        hasher.update(&buf);
+        downloaded += buf.len() as u64;
+        check_size(downloaded, opts.max_size)?;
+        if tracker.due() {
+            let elapsed = started.elapsed().as_secs_f64();
+            let speed = if elapsed > 0.0 {
+                ((downloaded - offset) as f64 / elapsed) as u64
+            } else {
+                0
+            };
+            tracker.emit(DownloadState::Downloading, downloaded, total, speed, None);
+        }
+    }
+    out.flush().map_err(|e| Failure::Fatal(anyhow!(e)))?;
+    if let Some(t) = total {
+        if downloaded < t {
+            return Err(Failure::Retry(anyhow!(
+                "Connection closed after {} of {} bytes",
+                downloaded,
+                t
+            )));
+        }
+    }
+    if let Some(expected) = opts.size.filter(|s| *s != downloaded) {
+        return Err(Failure::Fatal(anyhow!(
+            "Size mismatch: expected {} bytes, got {}",
+            expected,
+            downloaded
+        )));
+    }
+    let sha256 = hex::encode(hasher.finalize());
+    if let Some(expected) = opts.sha256.as_ref().filter(|s| **s != sha256) {
+        return Err(Failure::Fatal(anyhow!(
+            "Checksum mismatch: expected {}, got {}",
+            expected,
+            sha256
+        )));
+    }
+    Ok(Downloaded {
+        path: opts.dest.clone(),
+        size: downloaded,
+        sha256,
+        etag: validators.etag.clone(),
+        last_modified: validators.last_modified.clone(),
+    })
+}
+
+/// Waits `delay`, waking up early when the task is cancelled.
+fn wait(delay: Duration, task: &DownloadTask) {
+    let until = Instant::now() + delay;
+    while Instant::now() < until && !task.is_cancelled() {
+        std::thread::sleep(Duration::from_millis(50).min(until - Instant::now()));
+    }
+}
+
+/// Streams `opts.url` to `opts.dest` through `<dest>.part`, hashing on the fly. Interrupted
+/// transfers are resumed with an HTTP `Range` request, right away when data was still coming
+/// in, else after an exponential backoff that only `retries` failures in a row get; when those
+/// run out the partial file is kept so the next call picks it up.
+/// Cancellation or a fatal error (4xx, size limit, checksum mismatch) removes it.
+pub fn download(
+    opts: &DownloadOptions,
+    task: &DownloadTask,
+    on_progress: &dyn Fn(&DownloadProgress),
+) -> Result<Downloaded> {
+    let _claim = TargetClaim::take(&opts.dest)?;
+    let part = suffixed(&opts.dest, ".part");
+    let info_path = suffixed(&part, ".json");
+    let mut validators = match load_part_info(&info_path) {
+        Some(i) if i.url == opts.url && part.is_file() => i,
+        _ => {
+            let _ = fs::remove_file(&part);
+            PartInfo::default()
+        }
+    };
+    if let Some(parent) = opts.dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut tracker = Tracker {
+        task,
+        url: &opts.url,
+        file_name: file_name_of(&opts.url),
+        on_progress,
+        attempt: 1,
+        last_emit: None,
+    };
+    tracker.emit(DownloadState::Started, 0, None, 0, None);
+    let mut failures = 0;
+    let mut own_part = false;
+    loop {
+        let before = fs::metadata(&part).map(|m| m.len()).unwrap_or(0);
+        let result = attempt(opts, &part, &mut validators, own_part, &mut tracker);
+        let kept = fs::metadata(&part).map(|m| m.len()).unwrap_or(0);
+        // An attempt that brought data in is picked up right away and not counted as a failure
+        let progressed = kept > before;
+        match result {
+            Ok(done) => {
+                fs::rename(&part, &opts.dest).map_err(|e| {
+                    anyhow!("Cannot move download to {}: {}", opts.dest.display(), e)
+                })?;
+                let _ = fs::remove_file(&info_path);
+                tracker.emit(
+                    DownloadState::Completed,
+                    done.size,
+                    Some(done.size),
+                    0,
+                    None,
+                );
+                info(
+                    "download",
+                    &format!("Downloaded {} ({} bytes)", opts.url, done.size),
+                );
+                return Ok(done);
+            }
+            Err(Failure::Retry(e))
+                if (progressed || failures < opts.retries) && !task.is_cancelled() =>
+            {
+                own_part |= progressed;
+                let delay = if progressed {
+                    Duration::ZERO
+                } else {
+                    failures += 1;
+                    opts.backoff * 2u32.pow(failures - 1)
+                };
+                warn(
+                    "download",
+                    &format!(
+                        "{} (attempt {}), retrying in {} ms",
+                        e,
+                        tracker.attempt,
+                        delay.as_millis()
+                    ),
+                );
+                tracker.emit(DownloadState::Retrying, kept, None, 0, Some(e.to_string()));
+                wait(delay, task);
+                tracker.attempt += 1;
+            }
+            Err(Failure::Retry(e)) if !task.is_cancelled() => {
+                tracker.emit(DownloadState::Failed, 0, None, 0, Some(e.to_string()));
+                return Err(e);
+            }
+            Err(Failure::Retry(e)) | Err(Failure::Fatal(e)) => {
+                let _ = fs::remove_file(&part);
+                let _ = fs::remove_file(&info_path);
+                let state = if task.is_cancelled() {
+                    DownloadState::Cancelled
+                } else {
+                    DownloadState::Failed
+                };
+                tracker.emit(state, 0, None, 0, Some(e.to_string()));
+                if task.is_cancelled() {
+                    return Err(anyhow!("Download cancelled"));
+                }
+                return Err(e);
+            }
+        }
+    }
+}
+
+/// Sends each progress update as a `download-progress` event to the window.
+pub fn window_progress(window: &Window) -> impl Fn(&DownloadProgress) + '_ {
+    move |p: &DownloadProgress| {
+        let _ = window.emit("download-progress", p.clone());
+    }
+}
+
+/// Small downloads read in memory (manifests, catalogs), with the same retries as file
+/// downloads but no progress events and nothing kept between calls.
+pub fn fetch_bytes(url: &str, headers: &[(&str, &str)]) -> Result<Vec<u8>> {
+    let scratch = Scratch::new(url);
+    let mut opts = DownloadOptions::new(url, scratch.path()).max_size(FETCH_MAX_SIZE);
+    for (k, v) in headers {
+        opts = opts.header(k, v);
+    }
+    let task = DownloadTask::start();
+    download(&opts, &task, &|_| {})?;
+    Ok(fs::read(scratch.path())?)
+}
+
+// Async so it is not queued behind the command running the download
+#[tauri::command(async)]
+pub fn cancel_download(task_id: String) -> Result<bool, String> {
+    info(
+        "download",
+        &format!("cancel_download invoked (task_id={})", task_id),
+    );
+    Ok(cancel(&task_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufRead;
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::atomic::AtomicUsize;
+
+    /// What the stand-in server does for the n-th request (0-based), given the `Range` start.
+    type Handler = dyn Fn(usize, Option<u64>, &mut TcpStream) + Send + Sync;
+
+    /// Local HTTP stand-in: answers each connection with `handler`, records `Range` starts.
+    struct Server {
+        url: String,
+        hits: Arc<AtomicUsize>,
+        ranges: Arc<Mutex<Vec<Option<u64>>>>,
+    }
+
+    impl Server {
+        fn start(handler: Box<Handler>) -> Server {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!(
+                "http://{}/files/payload.bin",
+                listener.local_addr().unwrap()
+            );
+            let hits = Arc::new(AtomicUsize::new(0));
+            let ranges = Arc::new(Mutex::new(Vec::new()));
+            let (h, r) = (hits.clone(), ranges.clone());
+            std::thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let Ok(mut stream) = stream else { break };
+                    let mut reader = std::io::BufReader::new(stream.try_clone().unwrap());
+                    let mut range = None;
+                    let mut line = String::new();
+                    while reader.read_line(&mut line).unwrap_or(0) > 0 && line != "\r\n" {
+                        if let Some(v) = line.to_lowercase().strip_prefix("range: bytes=") {
+                            range = v.trim().trim_end_matches('-').parse().ok();
+                        }
+                        line.clear();
+                    }
+                    r.lock().unwrap().push(range);
+                    let n = h.fetch_add(1, Ordering::SeqCst);
+                    handler(n, range, &mut stream);
+                }
+            });
+            Server { url, hits, ranges }
+        }
+    }
+
+    fn payload() -> Vec<u8> {
+        (0..300_000u32).map(|i| (i % 251) as u8).collect()
+    }
+
+    fn respond(stream: &mut TcpStream, status: &str, headers: &str, body: &[u8]) {
+        let head = format!(
+            "HTTP/1.1 {}\r\nContent-Length: {}\r\n{}\r\n",
+            status,
+            body.len(),
+            headers
+        );
+        let _ = stream.write_all(head.as_bytes());
+        let _ = stream.write_all(body);
+    }
+
+    /// Serves `body` from the requested offset, with `206` for ranges.
+    fn serve_range(stream: &mut TcpStream, body: &[u8], from: Option<u64>) {
+        match from {
+            Some(start) => {
+                let start = start as usize;
+                let headers = format!(
+                    "Content-Range: bytes {}-{}/{}\r\nETag: \"v1\"\r\n",
+                    start,
+                    body.len() - 1,
+                    body.len()
+                );
+                respond(stream, "206 Partial Content", &headers, &body[start..]);
+            }
+            None => respond(stream, "200 OK", "ETag: \"v1\"\r\n", body),
+        }
+    }
+
+    fn target(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "avrix-download-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("payload.bin")
+    }
+
+    fn options(url: &str, dest: &Path) -> DownloadOptions {
+        let mut opts = DownloadOptions::new(url, dest);
+        opts.backoff = Duration::from_millis(10);
+        opts
+    }
+
+    fn sha(bytes: &[u8]) -> String {
+        hex::encode(Sha256::digest(bytes))
+    }
+
+    #[test]
+    fn downloads_to_file_with_progress() {
+        let body = payload();
+        let served = body.clone();
+        let server = Server::start(Box::new(move |_, from, s| serve_range(s, &served, from)));
+        let dest = target("full");
+        let states = Mutex::new(Vec::new());
+        let task = DownloadTask::start();
+        let done = download(&options(&server.url, &dest), &task, &|p| {
+            states
+                .lock()
+                .unwrap()
+                .push((p.state, p.downloaded, p.total));
+        })
+        .unwrap();
+        assert_eq!(fs::read(&dest).unwrap(), body);
+        assert_eq!(done.sha256, sha(&body));
+        assert_eq!(done.etag.as_deref(), Some("\"v1\""));
+        let states = states.into_inner().unwrap();
+        assert_eq!(states.first().unwrap().0, DownloadState::Started);
+        assert_eq!(
+            *states.last().unwrap(),
+            (
+                DownloadState::Completed,
+                body.len() as u64,
+                Some(body.len() as u64)
+            )
+        );
+        assert!(!suffixed(&dest, ".part").exists());
+        let _ = fs::remove_dir_all(dest.parent().unwrap());
+    }
+
+    #[test]
+    fn resumes_after_interruption() {
+        let body = payload();
+        let served = body.clone();
+        let server = Server::start(Box::new(move |n, from, s| {
+            if n == 0 {
+                // Announce the whole file, then drop the connection halfway
+                let head = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nETag: \"v1\"\r\n\r\n",
+                    served.len()
+                );
+                let _ = s.write_all(head.as_bytes());
+                let _ = s.write_all(&served[..120_000]);
+            } else {
+                serve_range(s, &served, from);
+            }
+        }));
+        let dest = target("resume");
+        let task = DownloadTask::start();
+        let done = download(&options(&server.url, &dest), &task, &|_| {}).unwrap();
+        assert_eq!(fs::read(&dest).unwrap(), body);
+        assert_eq!(done.sha256, sha(&body));
+        assert_eq!(*server.ranges.lock().unwrap(), vec![None, Some(120_000)]);
+        let _ = fs::remove_dir_all(dest.parent().unwrap());
+    }
+
+    #[test]
+    fn stale_partial_without_validators_starts_over() {
+        let body = payload();
+        let served = body.clone();
+        let server = Server::start(Box::new(move |n, _, s| match n {
+            0 => {
+                // No ETag or Last-Modified, connection dropped halfway
+                let head = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n",
+                    served.len()
+                );
+                let _ = s.write_all(head.as_bytes());
+                let _ = s.write_all(&served[..120_000]);
+            }
+            1 => respond(s, "503 Service Unavailable", "", b"busy"),
+            _ => respond(s, "200 OK", "", &served),
+        }));
+        let dest = target("no-validators");
+        let task = DownloadTask::start();
+        let mut opts = options(&server.url, &dest);
+        opts.retries = 0;
+        // The same download resumes its own bytes, a later one does not
+        assert!(download(&opts, &task, &|_| {}).is_err());
+        assert!(suffixed(&dest, ".part").exists());
+        download(&opts, &task, &|_| {}).unwrap();
+        assert_eq!(fs::read(&dest).unwrap(), body);
+        assert_eq!(
+            *server.ranges.lock().unwrap(),
+            vec![None, Some(120_000), None]
+        );
+        let _ = fs::remove_dir_all(dest.parent().unwrap());
+    }
+
+    #[test]
+    fn slow_transfers_finish_but_stalls_are_retried() {
+        let body = payload();
+        let served = body.clone();
+        let server = Server::start(Box::new(move |n, from, s| {
+            if n == 0 {
+                // Trickle the body: each gap is short, the whole transfer outlasts the timeout
+                let head = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nETag: \"v1\"\r\n\r\n",
+                    served.len()
+                );
+                let _ = s.write_all(head.as_bytes());
+                for piece in served.chunks(served.len() / 8 + 1) {
+                    std::thread::sleep(Duration::from_millis(100));
+                    let _ = s.write_all(piece);
+                }
+            } else if n == 1 {
+                // Send half the body, then hang past the timeout (connections are served
+                // one at a time, so the retry is answered once this returns)
+                let head = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nETag: \"v1\"\r\n\r\n",
+                    served.len()
+                );
+                let _ = s.write_all(head.as_bytes());
+                let _ = s.write_all(&served[..150_000]);
+                std::thread::sleep(Duration::from_millis(800));
+            } else {
+                serve_range(s, &served, from);
+            }
+        }));
+        let task = DownloadTask::start();
+
+        let dest = target("trickle");
+        let mut opts = options(&server.url, &dest);
+        opts.idle_timeout = Duration::from_millis(500);
+        let started = Instant::now();
+        download(&opts, &task, &|_| {}).unwrap();
+        assert!(started.elapsed() > opts.idle_timeout);
+        assert_eq!(fs::read(&dest).unwrap(), body);
+        assert_eq!(server.hits.load(Ordering::SeqCst), 1);
+
+        let dest = target("stall");
+        let mut opts = options(&server.url, &dest);
+        opts.idle_timeout = Duration::from_millis(500);
+        download(&opts, &task, &|_| {}).unwrap();
+        assert_eq!(fs::read(&dest).unwrap(), body);
+        assert_eq!(server.hits.load(Ordering::SeqCst), 3);
+        assert_eq!(server.ranges.lock().unwrap()[2], Some(150_000));
+        let _ = fs::remove_dir_all(dest.parent().unwrap());
+        let _ = fs::remove_dir_all(target("trickle").parent().unwrap());
+    }
+
+    #[test]
+    fn abandoned_readers_end_with_the_request_deadline() {
+        let body = payload();
+        let served = body.clone();
+        let server = Server::start(Box::new(move |n, from, s| {
+            if n == 0 {
+                // Half the body, then the connection stays open without sending anything
+                let head = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nETag: \"v1\"\r\n\r\n",
+                    served.len()
+                );
+                let _ = s.write_all(head.as_bytes());
+                let _ = s.write_all(&served[..150_000]);
+                let held = s.try_clone().unwrap();
+                std::thread::spawn(move || {
+                    std::thread::sleep(Duration::from_secs(30));
+                    drop(held);
+                });
+            } else {
+                serve_range(s, &served, from);
+            }
+        }));
+        let dest = target("abandoned");
+        let mut opts = options(&server.url, &dest);
+        opts.idle_timeout = Duration::from_millis(200);
+        let task = DownloadTask::start();
+        download(&opts, &task, &|_| {}).unwrap();
+        assert_eq!(fs::read(&dest).unwrap(), body);
+        assert_eq!(server.hits.load(Ordering::SeqCst), 2);
+        // The first reader is still blocked on the silent socket
+        assert!(LIVE_READERS.load(Ordering::SeqCst) >= 1);
+        let until = Instant::now() + Duration::from_secs(10);
+        while LIVE_READERS.load(Ordering::SeqCst) > 0 && Instant::now() < until {
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        assert_eq!(LIVE_READERS.load(Ordering::SeqCst), 0);
+        let _ = fs::remove_dir_all(dest.parent().unwrap());
+    }
+
+    #[test]
+    fn retries_server_errors_then_gives_up() {
+        let server = Server::start(Box::new(|_, _, s| {
+            respond(s, "503 Service Unavailable", "", b"busy")
+        }));
+        let dest = target("retry");
+        let mut opts = options(&server.url, &dest);
+        opts.retries = 2;
+        let task = DownloadTask::start();
+        let err = download(&opts, &task, &|_| {}).unwrap_err();
+        assert!(err.to_string().contains("503"), "{}", err);
+        assert_eq!(server.hits.load(Ordering::SeqCst), 3);
+        assert!(!dest.exists());
+        let _ = fs::remove_dir_all(dest.parent().unwrap());
+    }
+
+    #[test]
+    fn client_errors_and_size_limit_are_not_retried() {
+        let server = Server::start(Box::new(|_, _, s| respond(s, "404 Not Found", "", b"")));
+        let dest = target("404");
+        let task = DownloadTask::start();
+        assert!(download(&options(&server.url, &dest), &task, &|_| {}).is_err());
+        assert_eq!(server.hits.load(Ordering::SeqCst), 1);
+
+        let body = payload();
+        let server = Server::start(Box::new(move |_, from, s| serve_range(s, &body, from)));
+        let err =
+            download(&options(&server.url, &dest).max_size(1000), &task, &|_| {}).unwrap_err();
+        let too_large = err.downcast_ref::<TooLarge>().unwrap();
+        assert_eq!((too_large.size, too_large.max), (300_000, 1000));
+        assert_eq!(server.hits.load(Ordering::SeqCst), 1);
+        assert!(!suffixed(&dest, ".part").exists());
+        let _ = fs::remove_dir_all(dest.parent().unwrap());
+    }
+
+    #[test]
+    fn checksum_mismatch_is_rejected() {
+        let body = payload();
+        let served = body.clone();
+        let server = Server::start(Box::new(move |_, from, s| serve_range(s, &served, from)));
+        let dest = target("checksum");
+        let task = DownloadTask::start();
+        let err = download(
+            &options(&server.url, &dest).expect(Some(&sha(b"other")), None),
+            &task,
+            &|_| {},
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Checksum mismatch"), "{}", err);
+        assert!(!dest.exists());
+        assert!(!suffixed(&dest, ".part").exists());
+
+        let expected = sha(&body).to_ascii_uppercase();
+        let opts = options(&server.url, &dest).expect(Some(&expected), Some(body.len() as u64));
+        download(&opts, &task, &|_| {}).unwrap();
+        assert_eq!(fs::read(&dest).unwrap(), body);
+        let _ = fs::remove_dir_all(dest.parent().unwrap());
+    }
+
+    #[test]
+    fn one_download_per_destination() {
+        let body = payload();
+        let server = Server::start(Box::new(move |_, from, s| serve_range(s, &body, from)));
+        let dest = target("claimed");
+        let task = DownloadTask::start();
+        let claim = TargetClaim::take(&dest).unwrap();
+        let err = download(&options(&server.url, &dest), &task, &|_| {}).unwrap_err();
+        assert!(err.to_string().contains("already in progress"), "{}", err);
+        assert_eq!(server.hits.load(Ordering::SeqCst), 0);
+        drop(claim);
+        download(&options(&server.url, &dest), &task, &|_| {}).unwrap();
+        let _ = fs::remove_dir_all(dest.parent().unwrap());
+    }
+
+    #[test]
+    fn cancellation_stops_and_cleans_up() {
+        let body = payload();
+        let server = Server::start(Box::new(move |_, from, s| serve_range(s, &body, from)));
+        let dest = target("cancel");
+        let task = DownloadTask::start();
+        let err = download(&options(&server.url, &dest), &task, &|p| {
+            if p.state == DownloadState::Started {
+                assert!(cancel(&p.task_id));
+            }
+        })
+        .unwrap_err();
+        assert_eq!(err.to_string(), "Download cancelled");
+        assert!(!dest.exists());
+        assert!(!suffixed(&dest, ".part").exists());
+        drop(task);
+        assert!(!cancel("dl-unknown"));
+        let _ = fs::remove_dir_all(dest.parent().unwrap());
+    }
+}
@@ -0,0 +1,146 @@
+use crate::logger::info;
+use crate::models::{DuplicateKind, DuplicatePolicy, PluginEntry, PluginWarning};
+use crate::store::settings_store;
+use anyhow::{anyhow, Result};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use tauri::{AppHandle, Manager, Window};
+
+const STORE_POLICY_KEY: &str = "duplicatePolicy";
+
+// Core and embedded internal plugins are not jars of the plugins folder and cannot be moved.
+fn is_external(p: &PluginEntry) -> bool {
+    p.name.to_lowercase().ends_with(".jar") && !p.name.starts_with("Avrix-Core")
+}
+
+/// Groups enabled plugins by id and marks every member of a group with more than one entry,
+/// either as a plain duplicate (same version) or as a version conflict.
+/// Returns one warning per group; disabled jars are never reported.
+pub fn detect(plugins: &mut [PluginEntry]) -> Vec<PluginWarning> {
+    let mut groups: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (i, p) in plugins.iter().enumerate() {
+        if p.enabled == Some(false) {
+            continue;
+        }
+        if let Some(id) = &p.id {
+            groups.entry(id.clone()).or_default().push(i);
+        }
+    }
+    let mut warnings = Vec::new();
+    for (id, idx) in groups.into_iter().filter(|(_, idx)| idx.len() > 1) {
+        let mut versions: Vec<String> = idx
+            .iter()
+            .map(|i| plugins[*i].version.clone().unwrap_or_else(|| "?".into()))
+            .collect();
+        versions.sort_by(|a, b| crate::semver::compare_str(a, b));
+        versions.dedup();
+        let kind = if versions.len() > 1 {
+            DuplicateKind::VersionConflict
+        } else {
+            DuplicateKind::Duplicate
+        };
+        let files: Vec<String> = idx.iter().map(|i| plugins[*i].name.clone()).collect();
+        for i in idx.iter() {
+            plugins[*i].duplicate = Some(kind);
+        }
+        let message = match kind {
+            DuplicateKind::Duplicate => format!(
+                "Plugin {} is installed {} times: {}",
+                id,
+                files.len(),
+                files.join(", ")
+            ),
+            DuplicateKind::VersionConflict => format!(
+                "Plugin {} is installed with conflicting versions ({}): {}",
+                id,
+                versions.join(", "),
+                files.join(", ")
+            ),
+        };
+        warnings.push(PluginWarning {
+            kind,
+            id,
+            files,
+            versions,
+            message,
+        });
+    }
+    warnings
+}
+
+/// Orders the members of a duplicate group, best first. Core and internal plugins always come
+/// first; ties are broken by file name so the choice is deterministic.
+fn rank(a: &PluginEntry, b: &PluginEntry, policy: DuplicatePolicy) -> Ordering {
+    let by_version = || {
+        crate::semver::compare_str(
+            b.version.as_deref().unwrap_or(""),
+            a.version.as_deref().unwrap_or(""),
+        )
+    };
+    let by_modified = || b.modified.cmp(&a.modified);
+    is_external(a)
+        .cmp(&is_external(b))
+        .then_with(|| match policy {
+            DuplicatePolicy::NewestFile => by_modified().then_with(by_version),
+            _ => by_version().then_with(by_modified),
+        })
+        .then_with(|| a.name.cmp(&b.name))
+}
+
+/// For each warning, the external jars that lose under `policy` (empty for `Refuse`).
+pub fn losers(
+    plugins: &[PluginEntry],
+    warnings: &[PluginWarning],
+    policy: DuplicatePolicy,
+) -> Vec<(String, String)> {
+    if policy == DuplicatePolicy::Refuse {
+        return Vec::new();
+    }
+    let mut out = Vec::new();
+    for w in warnings.iter() {
+        let mut members: Vec<&PluginEntry> = plugins
+            .iter()
+            .filter(|p| w.files.contains(&p.name) && p.id.as_deref() == Some(w.id.as_str()))
+            .collect();
+        members.sort_by(|a, b| rank(a, b, policy));
+        let keep = match members.first() {
+            Some(k) => k.name.clone(),
+            None => continue,
+        };
+        for m in members.iter().skip(1).filter(|m| is_external(m)) {
+            out.push((m.name.clone(), keep.clone()));
+        }
+    }
+    out
+}
+
+pub fn load_policy(app: &AppHandle) -> DuplicatePolicy {
+    settings_store(app)
+        .ok()
+        .and_then(|s| s.get(STORE_POLICY_KEY))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+fn save_policy(app: &AppHandle, policy: DuplicatePolicy) -> Result<()> {
+    let store = settings_store(app)?;
+    store.set(STORE_POLICY_KEY, serde_json::to_value(policy)?);
+    store.save().map_err(|e| anyhow!(e.to_string()))
+}
+
+#[tauri::command]
+pub fn get_duplicate_policy(window: Window) -> Result<DuplicatePolicy, String> {
+    info("duplicates", "get_duplicate_policy invoked");
+    Ok(load_policy(window.app_handle()))
+}
+
+/// `refuse` blocks the launch while duplicates are enabled; `highestVersion` and `newestFile`
+/// keep one jar per id and leave the others out of that launch, without disabling them.
+#[tauri::command]
+pub fn set_duplicate_policy(policy: DuplicatePolicy, window: Window) -> Result<(), String> {
+    info(
+        "duplicates",
+        &format!("set_duplicate_policy invoked (policy={:?})", policy),
+    );
+    save_policy(window.app_handle(), policy).map_err(|e| e.to_string())
+}
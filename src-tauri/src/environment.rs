@@ -0,0 +1,190 @@
+use crate::logger::{info, warn};
+use crate::models::PluginEntry;
+use crate::util::SKIPPED_DIR_NAME;
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Launch mode passed to the core as `-Davrix.mode`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LaunchMode {
+    Client,
+    Server,
+}
+
+impl LaunchMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LaunchMode::Client => "client",
+            LaunchMode::Server => "server",
+        }
+    }
+}
+
+/// Whether a plugin declaring `environment` is loaded in `mode`. A missing, `both` or
+/// unrecognized environment loads everywhere (invalid values are reported by validation).
+pub fn runs_in(environment: Option<&str>, mode: LaunchMode) -> bool {
+    match environment.map(|e| e.trim().to_lowercase()).as_deref() {
+        Some("client") => mode == LaunchMode::Client,
+        Some("server") => mode == LaunchMode::Server,
+        _ => true,
+    }
+}
+
+/// Enabled external jars that must not be loaded in `mode`, with the reason.
+pub fn excluded(plugins: &[PluginEntry], mode: LaunchMode) -> Vec<(String, String)> {
+    plugins
+        .iter()
+        .filter(|p| p.enabled != Some(false))
+        .filter(|p| p.name.to_lowercase().ends_with(".jar") && !p.name.starts_with("Avrix-Core"))
+        .filter(|p| !runs_in(p.environment.as_deref(), mode))
+        .map(|p| {
+            (
+                p.name.clone(),
+                format!(
+                    "environment: {}, not loaded in {} mode",
+                    p.environment.as_deref().unwrap_or("?"),
+                    mode.as_str()
+                ),
+            )
+        })
+        .collect()
+}
+
+/// Jars one launch moved out of `plugins/`, kept in its own `plugins/.skipped/<launch>/`
+/// folder until that instance exits.
+pub struct SetAside {
+    plugins_dir: PathBuf,
+    dir: PathBuf,
+    names: Vec<String>,
+    /// Excluded jars that could not be moved and stay in `plugins/`
+    pub left_in_place: Vec<String>,
+}
+
+impl SetAside {
+    /// Puts back the jars this launch set aside. Jars a later launch took over are no longer
+    /// in this folder and are left alone.
+    pub fn restore(&self) {
+        for name in self.names.iter() {
+            restore_jar(&self.dir.join(name), &self.plugins_dir.join(name));
+        }
+        let _ = fs::remove_dir(&self.dir);
+    }
+}
+
+static NEXT_LAUNCH: AtomicU64 = AtomicU64::new(1);
+
+/// Jars currently set aside by running instances, with their full path.
+pub fn skipped_jars(plugins_dir: &Path) -> Vec<PathBuf> {
+    let Ok(rd) = fs::read_dir(plugins_dir.join(SKIPPED_DIR_NAME)) else {
+        return Vec::new();
+    };
+    let mut out = Vec::new();
+    for p in rd.flatten().map(|e| e.path()) {
+        if p.is_dir() {
+            if let Ok(inner) = fs::read_dir(&p) {
+                out.extend(inner.flatten().map(|e| e.path()).filter(|p| p.is_file()));
+            }
+        } else if p.is_file() {
+            out.push(p);
+        }
+    }
+    out
+}
+
+/// Moves the given jars out of `plugins/` for the duration of a launch. Jars other running
+/// instances set aside are taken over when this launch excludes them too, and put back in
+/// `plugins/` otherwise, so the folder matches what this launch should load. A jar that cannot
+/// be moved (on Windows, one a running game still holds open) is left where it is rather than
+/// failing the launch halfway, so nothing is stranded in `.skipped`.
+pub fn set_aside(plugins_dir: &Path, names: &[String]) -> Result<SetAside> {
+    let dir = plugins_dir.join(SKIPPED_DIR_NAME).join(format!(
+        "{}-{}",
+        std::process::id(),
+        NEXT_LAUNCH.fetch_add(1, Ordering::SeqCst)
+    ));
+    fs::create_dir_all(&dir)?;
+    let mut moved = Vec::new();
+    let mut left_in_place = Vec::new();
+    for other in skipped_jars(plugins_dir) {
+        let Some(name) = other.file_name().and_then(|n| n.to_str()).map(String::from) else {
+            continue;
+        };
+        if names.contains(&name) && !moved.contains(&name) {
+            // Still out of `plugins/` when this fails, only owned by the other launch
+            match fs::rename(&other, dir.join(&name)) {
+                Ok(_) => moved.push(name),
+                Err(e) => warn(
+                    "environment",
+                    &format!("Cannot take over {}: {}", other.display(), e),
+                ),
+            }
+        } else {
+            restore_jar(&other, &plugins_dir.join(&name));
+        }
+    }
+    for name in names {
+        let src = plugins_dir.join(name);
+        if !src.is_file() || moved.contains(name) {
+            continue;
+        }
+        match fs::rename(&src, dir.join(name)) {
+            Ok(_) => moved.push(name.clone()),
+            Err(e) => {
+                warn(
+                    "environment",
+                    &format!("Cannot set aside {}: {}", src.display(), e),
+                );
+                left_in_place.push(name.clone());
+            }
+        }
+    }
+    Ok(SetAside {
+        plugins_dir: plugins_dir.to_path_buf(),
+        dir,
+        names: moved,
+        left_in_place,
+    })
+}
+
+fn restore_jar(src: &Path, dest: &Path) {
+    if !src.is_file() {
+        return;
+    }
+    if dest.exists() {
+        warn(
+            "environment",
+            &format!(
+                "Cannot restore {}: {} already exists",
+                src.display(),
+                dest.display()
+            ),
+        );
+        return;
+    }
+    match fs::rename(src, dest) {
+        Ok(_) => info("environment", &format!("Restored {}", dest.display())),
+        Err(e) => warn(
+            "environment",
+            &format!("Cannot restore {}: {}", src.display(), e),
+        ),
+    }
+}
+
+/// Puts back every jar left in `plugins/.skipped` by sessions that did not exit cleanly.
+/// Only call it when no instance is running.
+pub fn restore_skipped(plugins_dir: &Path) {
+    for src in skipped_jars(plugins_dir) {
+        if let Some(name) = src.file_name() {
+            restore_jar(&src, &plugins_dir.join(name));
+        }
+    }
+    let skipped_dir = plugins_dir.join(SKIPPED_DIR_NAME);
+    if let Ok(rd) = fs::read_dir(&skipped_dir) {
+        for p in rd.flatten().map(|e| e.path()).filter(|p| p.is_dir()) {
+            let _ = fs::remove_dir(p);
+        }
+    }
+    let _ = fs::remove_dir(&skipped_dir);
+}
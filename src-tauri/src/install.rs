@@ -1,7 +1,8 @@
+use crate::download::{self, DownloadOptions, DownloadTask};
 use crate::logger::{emit_app_log, error, info};
 use crate::{
-    metadata,
-    models::{InstallFromUrlResult, ValidationMetadata},
+    backup, lockfile, metadata,
+    models::{InstallFromUrlResult, LockSource, ValidationMetadata},
     util::find_game_root,
 };
 use anyhow::{anyhow, Result};
@@ -45,20 +46,55 @@ pub fn install_plugin_local(path: String, window: tauri::Window) -> Result<Strin
         .file_name()
         .and_then(|n| n.to_str())
         .ok_or_else(|| anyhow!("Nom de fichier invalide"))?;
-    let dest = plugins_dir.join(file_name);
-    emit(&format!("Copy to {}", dest.to_string_lossy()));
-    std::fs::copy(&src, &dest).map_err(|e| {
+    // Copy next to the destination first, then swap it in (previous versions are backed up)
+    let staged = plugins_dir.join(format!("{}.download", file_name));
+    emit(&format!(
+        "Copy to {}",
+        plugins_dir.join(file_name).to_string_lossy()
+    ));
+    std::fs::copy(&src, &staged).map_err(|e| {
         error("install", &format!("Copy error: {}", e));
+        let _ = std::fs::remove_file(&staged);
         anyhow!(e)
     })?;
+    let dest = backup::install_atomic(&plugins_dir, &staged, &plugins_dir, file_name, &emit)?;
+    let workshop_id = infer_workshop_id(&src);
+    let source = match &workshop_id {
+        Some(ws_id) => LockSource::Workshop {
+            workshop_id: ws_id.clone(),
+            path: path.clone(),
+        },
+        None => LockSource::Local { path: path.clone() },
+    };
+    let source_sha256 = match lockfile::entry_for(&dest, source.clone()) {
+        Ok(entry) => {
+            emit(&format!("SHA-256: {}…", &entry.sha256[..16]));
+            let sha = entry.sha256.clone();
+            if let Err(e) = lockfile::record(&plugins_dir, entry) {
+                error("install", &format!("plugins.lock update failed: {}", e));
+            }
+            Some(sha)
+        }
+        Err(e) => {
+            error("install", &format!("Hash error: {}", e));
+            None
+        }
+    };
     // Persist workshop mapping and embed workshopId into metadata if source is from a workshop directory.
     // IMPORTANT: Rewrite is done asynchronously to avoid blocking the UI thread.
-    if let Some(ws_id) = infer_workshop_id(&src) {
+    if let Some(ws_id) = workshop_id {
         let _ = persist_workshop_mapping(&plugins_dir, dest.file_name().and_then(|n| n.to_str()).unwrap_or(""), &ws_id);
         let dest_clone = dest.clone();
         let ws_clone = ws_id.clone();
+        let plugins_dir_clone = plugins_dir.clone();
         std::thread::spawn(move || {
-            let _ = rewrite_metadata_workshop_id(&dest_clone, &ws_clone);
+            if rewrite_metadata_workshop_id(&dest_clone, &ws_clone).is_ok() {
+                // The rewrite changes the jar content: re-pin the new hash
+                if let Ok(mut entry) = lockfile::entry_for(&dest_clone, source) {
+                    entry.source_sha256 = source_sha256;
+                    let _ = lockfile::record(&plugins_dir_clone, entry);
+                }
+            }
         });
     }
     emit("Done");
@@ -73,73 +109,63 @@ pub fn install_plugin_from_url(url: String, window: tauri::Window) -> Result<Ins
     };
     emit(&format!("[URL] Download: {}", url));
     const MAX_SIZE: u64 = 25 * 1024 * 1024;
-    if let Ok(head) = minreq::head(&url).send() {
-        if let Some(len) = head
-            .headers
-            .get("content-length")
-            .and_then(|v| v.parse::<u64>().ok())
-        {
-            emit(&format!("HEAD size={} bytes", len));
-            if len > MAX_SIZE {
-                let msg = "Downloaded file exceeds maximum allowed size";
-                error("install", msg);
-                return Err(anyhow!(msg));
-            }
-        }
-    }
-    let resp = minreq::get(&url).send().map_err(|e| {
-        error("install", &format!("Request error: {}", e));
-        anyhow!(e)
-    })?;
-    let code = resp.status_code;
-    if code < 200 || code >= 300 {
-        error("install", &format!("HTTP {}", code));
-        return Err(anyhow!(format!("Download failed (status {})", code)));
-    }
-    let bytes = resp.as_bytes();
-    if bytes.len() as u64 > MAX_SIZE {
-        return Err(anyhow!("Fichier dépasse la taille maximale autorisée"));
-    }
-    emit(&format!("Received {} bytes", bytes.len()));
-    use sha2::{Digest, Sha256};
-    let mut hasher = Sha256::new();
-    hasher.update(bytes);
-    let sha256 = hex::encode(hasher.finalize());
-    emit(&format!("SHA-256: {}…", &sha256[..16]));
-    let mut tmp_path = std::env::temp_dir();
     let file_name = url.split('/').last().unwrap_or("plugin.jar");
     let file_name = if file_name.ends_with(".jar") {
         file_name
     } else {
         "downloaded-plugin.jar"
     };
-    tmp_path.push(file_name);
+    let base = std::env::current_dir()?;
+    let game_root = find_game_root(&base).unwrap_or(base.clone());
+    emit(&format!("Game root: {}", game_root.to_string_lossy()));
+    let plugins_dir = crate::util::resolve_plugins_dir();
+    std::fs::create_dir_all(&plugins_dir)?;
+    // Streamed next to the destination so the final swap is a rename
+    let tmp_path = plugins_dir.join(format!("{}.download", file_name));
     emit(&format!(
         "Writing temp file: {}",
         tmp_path.to_string_lossy()
     ));
-    std::fs::write(&tmp_path, bytes).map_err(|e| {
-        error("install", &format!("Write error: {}", e));
-        anyhow!(e)
+    let task = DownloadTask::start();
+    let downloaded = download::download(
+        &DownloadOptions::new(&url, &tmp_path).max_size(MAX_SIZE),
+        &task,
+        &download::window_progress(&window),
+    )
+    .map_err(|e| {
+        error("install", &format!("Download error: {}", e));
+        e
     })?;
+    emit(&format!("Received {} bytes", downloaded.size));
+    let sha256 = downloaded.sha256.clone();
+    emit(&format!("SHA-256: {}…", &sha256[..16]));
     let meta = metadata::extract_metadata_from_jar(&tmp_path).ok();
     if meta.is_none() {
+        let _ = std::fs::remove_file(&tmp_path);
         let msg = "metadata.yml not found in archive";
         error("install", msg);
         return Err(anyhow!(msg));
     }
-    let base = std::env::current_dir()?;
-    let game_root = find_game_root(&base).unwrap_or(base.clone());
-    emit(&format!("Game root: {}", game_root.to_string_lossy()));
-    let plugins_dir = crate::util::resolve_plugins_dir();
-    std::fs::create_dir_all(&plugins_dir)?;
-    let dest = plugins_dir.join(file_name);
-    emit(&format!("Final copy to {}", dest.to_string_lossy()));
-    std::fs::copy(&tmp_path, &dest).map_err(|e| {
-        emit(&format!("Erreur copie: {}", e));
-        anyhow!(e)
-    })?;
-    let size = bytes.len() as u64;
+    emit(&format!(
+        "Final copy to {}",
+        plugins_dir.join(file_name).to_string_lossy()
+    ));
+    let dest = backup::install_atomic(&plugins_dir, &tmp_path, &plugins_dir, file_name, &emit)
+        .map_err(|e| {
+            emit(&format!("Erreur copie: {}", e));
+            e
+        })?;
+    match lockfile::entry_for(&dest, LockSource::Url { url: url.clone() }) {
+        Ok(mut entry) => {
+            entry.etag = downloaded.etag.clone();
+            entry.last_modified = downloaded.last_modified.clone();
+            if let Err(e) = lockfile::record(&plugins_dir, entry) {
+                error("install", &format!("plugins.lock update failed: {}", e));
+            }
+        }
+        Err(e) => error("install", &format!("Hash error: {}", e)),
+    }
+    let size = downloaded.size;
     emit("Done");
     Ok(InstallFromUrlResult {
         message: format!(
@@ -165,6 +191,7 @@ pub fn validate_plugin_local(path: String) -> Result<ValidationMetadata> {
             size: 0,
             sha256: None,
             message: "Fichier introuvable".into(),
+            diagnostics: Vec::new(),
         });
     }
     if p.extension()
@@ -181,6 +208,7 @@ pub fn validate_plugin_local(path: String) -> Result<ValidationMetadata> {
             size: 0,
             sha256: None,
             message: "Extension non .jar".into(),
+            diagnostics: Vec::new(),
         });
     }
     let data = std::fs::read(&p)?;
@@ -190,97 +218,72 @@ pub fn validate_plugin_local(path: String) -> Result<ValidationMetadata> {
     let sha256 = hex::encode(hasher.finalize());
     let size = data.len() as u64;
     let meta = metadata::extract_metadata_from_jar(&p).ok();
-    if let Some(m) = &meta {
-        return Ok(ValidationMetadata {
-            valid: true,
-            name: m.name.clone(),
-            version: m.version.clone(),
-            environment: m.environment.clone(),
-            size,
-            sha256: Some(sha256),
-            message: "Valide".into(),
-        });
-    }
+    let diagnostics = metadata::validate_jar(&p);
+    let errors = metadata::summarize(&diagnostics);
     Ok(ValidationMetadata {
-        valid: false,
-        name: None,
-        version: None,
-        environment: None,
+        valid: meta.is_some() && errors.is_empty(),
+        name: meta.as_ref().and_then(|m| m.name.clone()),
+        version: meta.as_ref().and_then(|m| m.version.clone()),
+        environment: meta.as_ref().and_then(|m| m.environment.clone()),
         size,
         sha256: Some(sha256),
-        message: "metadata.yml invalide".into(),
+        message: if meta.is_some() && errors.is_empty() {
+            "Valide".into()
+        } else {
+            format!("metadata.yml invalide: {}", errors)
+        },
+        diagnostics,
     })
 }
 
 pub fn validate_plugin_from_url(url: String) -> Result<ValidationMetadata> {
     const MAX_SIZE: u64 = 25 * 1024 * 1024;
-    if let Ok(head) = minreq::head(&url).send() {
-        if let Some(len) = head
-            .headers
-            .get("content-length")
-            .and_then(|v| v.parse::<u64>().ok())
-        {
-            if len > MAX_SIZE {
-                return Ok(ValidationMetadata {
-                    valid: false,
-                    name: None,
-                    version: None,
-                    environment: None,
-                    size: len,
-                    sha256: None,
-                    message: "File too large".into(),
-                });
-            }
+    let scratch = download::Scratch::new(&url);
+    let tmp = scratch.path().to_path_buf();
+    let task = DownloadTask::start();
+    let downloaded = match download::download(
+        &DownloadOptions::new(&url, &tmp).max_size(MAX_SIZE),
+        &task,
+        &|_| {},
+    ) {
+        Ok(d) => d,
+        Err(e) => {
+            let Some(too_large) = e.downcast_ref::<download::TooLarge>() else {
+                return Err(e);
+            };
+            return Ok(ValidationMetadata {
+                valid: false,
+                name: None,
+                version: None,
+                environment: None,
+                size: too_large.size,
+                sha256: None,
+                message: "File too large".into(),
+                diagnostics: Vec::new(),
+            });
         }
-    }
-    let resp = minreq::get(&url).send()?;
-    let code = resp.status_code;
-    if code < 200 || code >= 300 {
-        return Err(anyhow!(format!("HTTP status {}", code)));
-    }
-    let bytes = resp.as_bytes();
-    if bytes.len() as u64 > MAX_SIZE {
-        return Ok(ValidationMetadata {
-            valid: false,
-            name: None,
-            version: None,
-            environment: None,
-            size: bytes.len() as u64,
-            sha256: None,
-            message: "File size exceeds limit".into(),
-        });
-    }
-    use sha2::{Digest, Sha256};
-    let mut hasher = Sha256::new();
-    hasher.update(bytes);
-    let sha256 = hex::encode(hasher.finalize());
-    let mut tmp = std::env::temp_dir();
-    tmp.push("_validate_url_plugin.jar");
-    std::fs::write(&tmp, bytes)?;
+    };
+    let sha256 = downloaded.sha256.clone();
     let meta = metadata::extract_metadata_from_jar(&tmp).ok();
-    if let Some(m) = &meta {
-        return Ok(ValidationMetadata {
-            valid: true,
-            name: m.name.clone(),
-            version: m.version.clone(),
-            environment: m.environment.clone(),
-            size: bytes.len() as u64,
-            sha256: Some(sha256),
-            message: "Valid".into(),
-        });
-    }
+    let diagnostics = metadata::validate_jar(&tmp);
+    let errors = metadata::summarize(&diagnostics);
     Ok(ValidationMetadata {
-        valid: false,
-        name: None,
-        version: None,
-        environment: None,
-        size: bytes.len() as u64,
+        valid: meta.is_some() && errors.is_empty(),
+        name: meta.as_ref().and_then(|m| m.name.clone()),
+        version: meta.as_ref().and_then(|m| m.version.clone()),
+        environment: meta.as_ref().and_then(|m| m.environment.clone()),
+        size: downloaded.size,
         sha256: Some(sha256),
-        message: "Invalid metadata.yml".into(),
+        message: if meta.is_some() && errors.is_empty() {
+            "Valid".into()
+        } else {
+            format!("Invalid metadata.yml: {}", errors)
+        },
+        diagnostics,
     })
 }
 
-fn infer_workshop_id(path: &std::path::Path) -> Option<String> {
+pub fn infer_workshop_id(path: &std::path::Path) -> Option<String> {
     let comps: Vec<String> = path
         .components()
         .filter_map(|c| c.as_os_str().to_str().map(|s| s.to_string()))
@@ -298,18 +301,31 @@ fn infer_workshop_id(path: &std::path::Path) -> Option<String> {
     None
 }
 
-fn persist_workshop_mapping(plugins_dir: &std::path::Path, file_name: &str, id: &str) -> Result<()> {
+fn read_workshop_map(plugins_dir: &std::path::Path) -> serde_json::Map<String, serde_json::Value> {
     let map_path = plugins_dir.join("workshop-map.json");
-    let mut map: serde_json::Map<String, serde_json::Value> = serde_json::Map::new();
-    if map_path.exists() {
-        if let Ok(data) = std::fs::read_to_string(&map_path) {
-            if let Ok(v) = serde_json::from_str::<serde_json::Value>(&data) {
-                if let Some(obj) = v.as_object() {
-                    map = obj.clone();
-                }
+    if let Ok(data) = std::fs::read_to_string(&map_path) {
+        if let Ok(v) = serde_json::from_str::<serde_json::Value>(&data) {
+            if let Some(obj) = v.as_object() {
+                return obj.clone();
             }
         }
     }
+    serde_json::Map::new()
+}
+
+/// Installed jar file name -> workshop item id, as recorded by `persist_workshop_mapping`.
+pub fn load_workshop_mapping(
+    plugins_dir: &std::path::Path,
+) -> std::collections::BTreeMap<String, String> {
+    read_workshop_map(plugins_dir)
+        .into_iter()
+        .filter_map(|(k, v)| v.as_str().map(|id| (k, id.to_string())))
+        .collect()
+}
+
+pub fn persist_workshop_mapping(plugins_dir: &std::path::Path, file_name: &str, id: &str) -> Result<()> {
+    let map_path = plugins_dir.join("workshop-map.json");
+    let mut map = read_workshop_map(plugins_dir);
     map.insert(file_name.to_string(), serde_json::Value::String(id.to_string()));
     let s = serde_json::to_string_pretty(&serde_json::Value::Object(map))?;
     let mut f = fs::File::create(&map_path)?;
@@ -317,7 +333,7 @@ fn persist_workshop_mapping(plugins_dir: &std::path::Path, file_name: &str, id:
     Ok(())
 }
 
-fn rewrite_metadata_workshop_id(jar_path: &std::path::Path, workshop_id: &str) -> Result<()> {
+pub fn rewrite_metadata_workshop_id(jar_path: &std::path::Path, workshop_id: &str) -> Result<()> {
     // Open the existing jar
     let file = fs::File::open(jar_path)?;
     let mut zip = ZipArchive::new(file)?;
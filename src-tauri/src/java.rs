@@ -0,0 +1,515 @@
+use crate::logger::{info, warn};
+use crate::models::{JavaRuntime, JavaRuntimesResult, JavaSource};
+use crate::platform::GamePlatform;
+use crate::store::settings_store;
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tauri::{AppHandle, Manager, Window};
+
+const STORE_PINNED_KEY: &str = "javaPath";
+/// Required when the core jar does not declare `java` in its metadata.yml.
+pub const DEFAULT_MIN_JAVA: u32 = 17;
+
+// Probing runs `java`, so results are kept per home and `release`/binary modification time
+type ProbeCache = HashMap<PathBuf, (Option<SystemTime>, JavaRuntime)>;
+static PROBE_CACHE: Lazy<Mutex<ProbeCache>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn exe_names() -> (&'static str, &'static str) {
+    if cfg!(windows) {
+        ("javaw.exe", "java.exe")
+    } else {
+        ("java", "java")
+    }
+}
+
+/// Binary used for launch in a Java home (`javaw.exe` preferred on Windows).
+pub fn launch_binary(home: &Path) -> Option<PathBuf> {
+    let (exe_name, fallback) = exe_names();
+    [exe_name, fallback]
+        .iter()
+        .map(|n| home.join("bin").join(n))
+        .find(|p| p.is_file())
+}
+
+// `java.exe` rather than `javaw.exe`: only the console binary prints the settings
+fn console_binary(home: &Path) -> Option<PathBuf> {
+    let (_, console) = exe_names();
+    let p = home.join("bin").join(console);
+    p.is_file().then_some(p)
+}
+
+/// Java home of a path given by the user: the home itself, or a binary in `<home>/bin`.
+fn home_of(path: &Path) -> Option<PathBuf> {
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    if path.is_dir() {
+        return launch_binary(&path).map(|_| path);
+    }
+    let home = path.parent()?.parent()?.to_path_buf();
+    launch_binary(&home).map(|_| home)
+}
+
+/// Major version of a `java.version` string: `1.8.0_292` -> 8, `17.0.8` -> 17, `21` -> 21.
+pub fn major_of(version: &str) -> Option<u32> {
+    let mut parts = version.trim().trim_matches('"').split(['.', '_', '-', '+']);
+    let first: u32 = parts.next()?.parse().ok()?;
+    if first == 1 {
+        parts.next()?.parse().ok()
+    } else {
+        Some(first)
+    }
+}
+
+/// `java` entry of a metadata.yml: `21`, `"21"`, `">=21"` or `"21+"`.
+pub fn parse_requirement(value: &serde_yaml::Value) -> Option<u32> {
+    match value {
+        serde_yaml::Value::Number(n) => n.as_u64().map(|v| v as u32),
+        serde_yaml::Value::String(s) => {
+            let s = s.trim().trim_start_matches(">=").trim_end_matches('+');
+            major_of(s.trim())
+        }
+        _ => None,
+    }
+    .filter(|v| *v > 0)
+}
+
+/// Minimum Java major version and where it comes from.
+pub fn required_major(core_jar: Option<&Path>) -> (u32, String) {
+    let declared = core_jar.and_then(|jar| {
+        let meta = crate::metadata::extract_metadata_from_jar(&jar.to_path_buf()).ok()?;
+        let major = parse_requirement(meta.java.as_ref()?)?;
+        Some((
+            major,
+            format!(
+                "{} {}",
+                jar.file_name().and_then(|n| n.to_str()).unwrap_or("core"),
+                meta.version.as_deref().unwrap_or("")
+            )
+            .trim()
+            .to_string(),
+        ))
+    });
+    declared.unwrap_or((DEFAULT_MIN_JAVA, "launcher default".into()))
+}
+
+// `KEY="value"` lines of `<home>/release`
+fn parse_release(text: &str) -> HashMap<String, String> {
+    text.lines()
+        .filter_map(|l| {
+            let (k, v) = l.split_once('=')?;
+            Some((k.trim().to_string(), v.trim().trim_matches('"').to_string()))
+        })
+        .collect()
+}
+
+// `    key = value` lines printed by `-XshowSettings:properties`
+fn parse_properties(text: &str) -> HashMap<String, String> {
+    text.lines()
+        .filter_map(|l| {
+            let (k, v) = l.trim().split_once(" = ")?;
+            Some((k.trim().to_string(), v.trim().to_string()))
+        })
+        .collect()
+}
+
+fn normalize_arch(arch: &str) -> String {
+    match arch.trim().to_lowercase().as_str() {
+        "amd64" | "x86_64" | "x64" => "x86_64".into(),
+        "x86" | "i386" | "i586" | "i686" => "x86".into(),
+        "aarch64" | "arm64" => "aarch64".into(),
+        other => other.to_string(),
+    }
+}
+
+fn bits_of(arch: &str) -> Option<u32> {
+    match arch {
+        "x86_64" | "aarch64" | "ppc64le" | "s390x" | "riscv64" => Some(64),
+        "x86" | "arm" => Some(32),
+        _ => None,
+    }
+}
+
+fn run_settings(binary: &Path) -> Result<String> {
+    let mut command = Command::new(binary);
+    command.args(["-XshowSettings:properties", "-version"]);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(0x08000000);
+    }
+    let output = command.output()?;
+    // The settings go to stderr
+    Ok(format!(
+        "{}\n{}",
+        String::from_utf8_lossy(&output.stderr),
+        String::from_utf8_lossy(&output.stdout)
+    ))
+}
+
+// Version, vendor and architecture from the `release` file, or by asking the JVM.
+fn probe_uncached(home: &Path, source: JavaSource) -> JavaRuntime {
+    let mut rt = JavaRuntime {
+        path: launch_binary(home)
+            .unwrap_or_else(|| home.join("bin").join(exe_names().0))
+            .to_string_lossy()
+            .to_string(),
+        home: home.to_string_lossy().to_string(),
+        source,
+        version: None,
+        major: None,
+        vendor: None,
+        arch: None,
+        bits: None,
+        meets_requirement: false,
+        problem: None,
+    };
+    let release = fs::read_to_string(home.join("release"))
+        .map(|t| parse_release(&t))
+        .unwrap_or_default();
+    if let Some(v) = release.get("JAVA_VERSION") {
+        rt.version = Some(v.clone());
+        rt.vendor = release
+            .get("IMPLEMENTOR")
+            .or_else(|| release.get("JAVA_VENDOR"))
+            .cloned();
+        rt.arch = release.get("OS_ARCH").map(|a| normalize_arch(a));
+    }
+    if rt.version.is_none() || rt.arch.is_none() {
+        match console_binary(home).map(|b| run_settings(&b)) {
+            Some(Ok(text)) => {
+                let props = parse_properties(&text);
+                if let Some(v) = props.get("java.version") {
+                    rt.version = Some(v.clone());
+                    rt.vendor = rt.vendor.or_else(|| props.get("java.vendor").cloned());
+                    rt.arch = props.get("os.arch").map(|a| normalize_arch(a));
+                    rt.bits = props
+                        .get("sun.arch.data.model")
+                        .and_then(|b| b.parse().ok());
+                } else {
+                    rt.problem = Some("Could not read the Java version".into());
+                }
+            }
+            Some(Err(e)) => rt.problem = Some(format!("Cannot run java: {}", e)),
+            None => rt.problem = Some("No java binary in bin/".into()),
+        }
+    }
+    rt.major = rt.version.as_deref().and_then(major_of);
+    rt.bits = rt.bits.or_else(|| rt.arch.as_deref().and_then(bits_of));
+    rt
+}
+
+fn probe(home: &Path, source: JavaSource) -> JavaRuntime {
+    let stamp = fs::metadata(home.join("release"))
+        .or_else(|_| fs::metadata(home.join("bin").join(exe_names().1)))
+        .and_then(|m| m.modified())
+        .ok();
+    if let Ok(cache) = PROBE_CACHE.lock() {
+        if let Some((s, rt)) = cache.get(home) {
+            if *s == stamp {
+                let mut rt = rt.clone();
+                rt.source = source;
+                return rt;
+            }
+        }
+    }
+    let rt = probe_uncached(home, source);
+    if let Ok(mut cache) = PROBE_CACHE.lock() {
+        cache.insert(home.to_path_buf(), (stamp, rt.clone()));
+    }
+    rt
+}
+
+// Subfolders of the usual installation directories that look like a Java home
+fn system_homes() -> Vec<PathBuf> {
+    let mut roots: Vec<PathBuf> = Vec::new();
+    if cfg!(windows) {
+        for var in ["ProgramFiles", "ProgramFiles(x86)"] {
+            if let Ok(pf) = std::env::var(var) {
+                for vendor in [
+                    "Java",
+                    "Eclipse Adoptium",
+                    "Eclipse Foundation",
+                    "Microsoft",
+                    "Zulu",
+                    "Amazon Corretto",
+                    "BellSoft",
+                ] {
+                    roots.push(PathBuf::from(&pf).join(vendor));
+                }
+            }
+        }
+    } else if cfg!(target_os = "macos") {
+        roots.push(PathBuf::from("/Library/Java/JavaVirtualMachines"));
+        if let Some(home) = std::env::var_os("HOME") {
+            roots.push(PathBuf::from(home).join("Library/Java/JavaVirtualMachines"));
+        }
+    } else {
+        roots.push(PathBuf::from("/usr/lib/jvm"));
+        roots.push(PathBuf::from("/usr/java"));
+        roots.push(PathBuf::from("/opt/java"));
+    }
+    let mut out = Vec::new();
+    for root in roots {
+        let rd = match fs::read_dir(&root) {
+            Ok(rd) => rd,
+            Err(_) => continue,
+        };
+        let mut found: Vec<PathBuf> = rd
+            .flatten()
+            .map(|e| e.path())
+            .map(|p| {
+                // macOS bundles: <name>.jdk/Contents/Home
+                let mac = p.join("Contents").join("Home");
+                if mac.is_dir() {
+                    mac
+                } else {
+                    p
+                }
+            })
+            .filter(|p| launch_binary(p).is_some())
+            .collect();
+        found.sort();
+        out.extend(found);
+    }
+    out
+}
+
+/// Every Java home the launcher knows about, in lookup order: selected version `jre`, working
+/// directory `jre`, launcher `jre`, the game's own runtime, `JAVA_HOME`, `PATH`, then the usual
+/// system installation folders.
+fn candidate_homes(app: &AppHandle, version_dir: Option<&PathBuf>) -> Vec<(PathBuf, JavaSource)> {
+    let mut out: Vec<(PathBuf, JavaSource)> = Vec::new();
+    if let Some(vd) = version_dir {
+        out.push((vd.join("jre"), JavaSource::VersionJre));
+    }
+    if let Ok(cur) = std::env::current_dir() {
+        out.push((cur.join("jre"), JavaSource::WorkingDirJre));
+    }
+    if let Ok(exe_path) = std::env::current_exe() {
+        if let Some(base) = exe_path.parent() {
+            out.push((base.join("jre"), JavaSource::BundledJre));
+            if let Some(parent) = base.parent() {
+                out.push((parent.join("jre"), JavaSource::BundledJre));
+            }
+        }
+    }
+    let game_root = crate::util::get_effective_game_root(app);
+    for home in crate::platform::game_jre_homes(&game_root, GamePlatform::current()) {
+        out.push((home, JavaSource::GameJre));
+    }
+    if let Ok(home) = std::env::var("JAVA_HOME") {
+        out.push((PathBuf::from(home), JavaSource::JavaHome));
+    }
+    let (exe_name, fallback) = exe_names();
+    for name in [exe_name, fallback] {
+        if let Ok(bin) = which::which(name) {
+            if let Some(home) = home_of(&bin) {
+                out.push((home, JavaSource::Path));
+            }
+        }
+    }
+    for home in system_homes() {
+        out.push((home, JavaSource::System));
+    }
+    let mut seen = HashSet::new();
+    out.into_iter()
+        .filter(|(home, _)| launch_binary(home).is_some())
+        .filter(|(home, _)| seen.insert(fs::canonicalize(home).unwrap_or(home.clone())))
+        .collect()
+}
+
+fn load_pinned(app: &AppHandle) -> Option<PathBuf> {
+    settings_store(app)
+        .ok()?
+        .get(STORE_PINNED_KEY)
+        .and_then(|v| v.as_str().map(PathBuf::from))
+}
+
+fn check(rt: &mut JavaRuntime, required: u32) {
+    rt.meets_requirement = match rt.major {
+        Some(m) if m < required => {
+            rt.problem = Some(format!(
+                "Java {} is too old (Java {}+ required)",
+                m, required
+            ));
+            false
+        }
+        Some(_) => {
+            // A 32-bit JVM cannot load the 64-bit natives of the game
+            if cfg!(target_pointer_width = "64") && rt.bits == Some(32) {
+                rt.problem = Some("32-bit Java on a 64-bit system".into());
+                false
+            } else {
+                true
+            }
+        }
+        None => {
+            if rt.problem.is_none() {
+                rt.problem = Some("Unknown Java version".into());
+            }
+            false
+        }
+    };
+}
+
+/// All runtimes found, with the pinned one first when set.
+pub fn discover(app: &AppHandle, version_dir: Option<&PathBuf>, required: u32) -> Vec<JavaRuntime> {
+    let mut homes = candidate_homes(app, version_dir);
+    if let Some(pinned) = load_pinned(app).and_then(|p| home_of(&p)) {
+        let canon = fs::canonicalize(&pinned).unwrap_or(pinned.clone());
+        homes.retain(|(h, _)| fs::canonicalize(h).unwrap_or(h.clone()) != canon);
+        homes.insert(0, (pinned, JavaSource::Pinned));
+    }
+    homes
+        .into_iter()
+        .map(|(home, source)| {
+            let mut rt = probe(&home, source);
+            check(&mut rt, required);
+            rt
+        })
+        .collect()
+}
+
+/// Java used for launch: the pinned runtime when set, else the first runtime meeting the
+/// requirement in lookup order.
+pub fn select(
+    app: &AppHandle,
+    version_dir: Option<&PathBuf>,
+    core_jar: Option<&Path>,
+) -> Result<JavaRuntime> {
+    let (required, origin) = required_major(core_jar);
+    if let Some(pinned) = load_pinned(app) {
+        let home = home_of(&pinned).ok_or_else(|| {
+            anyhow!(
+                "[Java] Pinned runtime not found: {} (unpin it or pick another one)",
+                pinned.display()
+            )
+        })?;
+        let mut rt = probe(&home, JavaSource::Pinned);
+        check(&mut rt, required);
+        if !rt.meets_requirement {
+            return Err(anyhow!(
+                "[Java] Pinned runtime {} cannot be used: {} (requirement from {})",
+                rt.path,
+                rt.problem.as_deref().unwrap_or("unknown version"),
+                origin
+            ));
+        }
+        return Ok(rt);
+    }
+    let runtimes = discover(app, version_dir, required);
+    if let Some(rt) = runtimes.iter().find(|r| r.meets_requirement) {
+        info(
+            "java",
+            &format!(
+                "Selected Java {} ({:?}) at {}",
+                rt.version.as_deref().unwrap_or("?"),
+                rt.source,
+                rt.path
+            ),
+        );
+        return Ok(rt.clone());
+    }
+    if runtimes.is_empty() {
+        return Err(anyhow!(
+            "java not found (version ./jre, bundled ./jre, game jre64, JAVA_HOME, PATH or system installs)"
+        ));
+    }
+    let found = runtimes
+        .iter()
+        .map(|r| {
+            format!(
+                " - {} ({})",
+                r.path,
+                r.problem
+                    .as_deref()
+                    .or(r.version.as_deref())
+                    .unwrap_or("unknown")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    Err(anyhow!(
+        "[Java] No suitable Java runtime: Java {}+ required (from {}). Found:\n{}",
+        required,
+        origin,
+        found
+    ))
+}
+
+#[tauri::command]
+pub fn list_java_runtimes(window: Window) -> Result<JavaRuntimesResult, String> {
+    info("java", "list_java_runtimes invoked");
+    let app = window.app_handle();
+    let version_dir = crate::versions::resolve_selected_version_dir(app);
+    let core_jar = crate::launch::current_core_jar(app);
+    let (required, origin) = required_major(core_jar.as_deref());
+    let runtimes = discover(app, version_dir.as_ref(), required);
+    let selected = select(app, version_dir.as_ref(), core_jar.as_deref())
+        .map(|r| r.path)
+        .ok();
+    Ok(JavaRuntimesResult {
+        runtimes,
+        required_major: required,
+        required_by: origin,
+        pinned: load_pinned(app).map(|p| p.to_string_lossy().to_string()),
+        selected,
+    })
+}
+
+/// Pins a Java home or binary for every launch; `None` goes back to automatic selection.
+#[tauri::command]
+pub fn pin_java_runtime(
+    path: Option<String>,
+    window: Window,
+) -> Result<Option<JavaRuntime>, String> {
+    info(
+        "java",
+        &format!("pin_java_runtime invoked (path={:?})", path),
+    );
+    let app = window.app_handle();
+    let run = || -> Result<Option<JavaRuntime>> {
+        let store = settings_store(app)?;
+        let path = match path.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+            Some(p) => p,
+            None => {
+                store.delete(STORE_PINNED_KEY);
+                store.save().map_err(|e| anyhow!(e.to_string()))?;
+                return Ok(None);
+            }
+        };
+        let home =
+            home_of(Path::new(path)).ok_or_else(|| anyhow!("Not a Java runtime: {}", path))?;
+        let rt = probe(&home, JavaSource::Pinned);
+        if rt.major.is_none() {
+            return Err(anyhow!(
+                "Cannot use {}: {}",
+                path,
+                rt.problem.as_deref().unwrap_or("unknown Java version")
+            ));
+        }
+        store.set(STORE_PINNED_KEY, home.to_string_lossy().to_string());
+        store.save().map_err(|e| anyhow!(e.to_string()))?;
+        let (required, origin) = required_major(crate::launch::current_core_jar(app).as_deref());
+        let mut rt = rt;
+        check(&mut rt, required);
+        if !rt.meets_requirement {
+            warn(
+                "java",
+                &format!(
+                    "Pinned {} does not meet the requirement from {}: {}",
+                    rt.path,
+                    origin,
+                    rt.problem.as_deref().unwrap_or("?")
+                ),
+            );
+        }
+        Ok(Some(rt))
+    };
+    run().map_err(|e| e.to_string())
+}
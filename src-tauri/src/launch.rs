@@ -1,53 +1,355 @@
+use crate::crash::{self, ExitContext, OutputTail};
+use crate::environment::{self, LaunchMode};
+use crate::launch_options;
 use crate::logger::{emit_app_log, error, info, Level};
+use crate::models::{
+    ClassPathEntry, ClassPathOrigin, DependencyReport, DuplicatePolicy, GameInstance,
+    InstanceStatus, JavaRuntime, JavaSource, LaunchPreset, LaunchRequest, RejectedCandidate,
+    ServerLaunchOptions,
+};
+use crate::platform::{self, GamePlatform};
+use crate::processes::{self, ProcessRegistry};
+use crate::sessions::{SessionHeader, SessionLog};
+use crate::workshop_sync;
 use anyhow::{anyhow, Result};
 use std::{
+    collections::BTreeMap,
     fs,
     io::{BufRead, BufReader},
     path::{Path, PathBuf},
     process::{Command, Stdio},
+    sync::{Arc, Mutex},
 };
 use tauri::{AppHandle, Emitter, Manager, Window};
 
-pub fn launch_game(window: Window, steam: bool, mem_mb: Option<u64>) -> Result<String> {
+pub fn launch_game(
+    window: Window,
+    steam: bool,
+    mem_mb: Option<u64>,
+    allow_multiple: bool,
+) -> Result<String> {
     info(
         "launch",
         &format!("launch_game invoked (steam={}, mem_mb={:?})", steam, mem_mb),
     );
+    let mode = LaunchMode::Client;
+    ensure_not_running(&window, None, allow_multiple)?;
     let ctx = resolve_launch_context(window.app_handle().clone())?;
     let core_jar = ctx
         .core_jar
+        .clone()
         .ok_or_else(|| anyhow!("[Error] Avrix-Core.jar not found."))?;
     // Preflight: ensure Project Zomboid binaries are present in the game root
-    ensure_game_binaries_exist(&ctx.work_dir)?;
-    let emit = |lvl: Level, m: &str| {
-        match lvl {
-            Level::Info => info("launch", m),
-            Level::Warn => crate::logger::warn("launch", m),
-            Level::Error => error("launch", m),
-        }
-        let _ = window.emit("launch-log", m.to_string());
-        let _ = emit_app_log(&window, lvl, "launch", m);
-    };
+    ensure_game_binaries_exist(&ctx.game_root)?;
+    let emit = launch_emitter(&window);
     emit(
         Level::Info,
         &format!("[JarLookup] Jar chosen : {}", core_jar.display()),
     );
+
+    sync_workshop_before_launch(&window, &emit);
+    // No background workshop sync may replace jars until the process is registered
+    let registry = window.app_handle().state::<ProcessRegistry>();
+    let _plugins = registry.lock_plugins();
+    let excluded = prepare_plugins(&window, &emit, &core_jar, mode)?;
     emit(Level::Info, &format!("[Classpath] {}", ctx.class_path));
     emit(Level::Info, &format!("[LibPath] {}", ctx.library_path));
 
-    let is_64 = cfg!(target_pointer_width = "64");
-    let mut args: Vec<String> = vec![
-        "-Djdk.attach.allowAttachSelf=true".into(),
-        "-XX:+EnableDynamicAgentLoading".into(),
+    let request = LaunchRequest::Client { steam, mem_mb };
+    let command = prepare_command(window.app_handle(), &emit, &ctx, &request)?;
+    emit(
+        Level::Info,
+        &format!("Java used  : {}", ctx.java_path.display()),
+    );
+    let command_line = format!("{} {}", ctx.java_path.display(), command.shown.join(" "));
+    emit(Level::Info, &format!("Command: {}", command_line));
+    let instance = spawn_and_stream(
+        &window,
+        &ctx,
+        &command,
+        &command_line,
+        excluded,
+        request,
+        allow_multiple,
+    )?;
+    emit(
+        Level::Info,
+        &format!("Process spawned successfully (pid {})", instance.pid),
+    );
+    Ok("[Launch in progress – live stream]".into())
+}
+
+/// Starts a dedicated server from the game root: same core jar, classpath and Java as the
+/// client, with `-Davrix.mode=server`, the server arguments and the server plugin set.
+pub fn launch_server(
+    window: Window,
+    options: ServerLaunchOptions,
+    allow_multiple: bool,
+) -> Result<String> {
+    info(
+        "launch",
+        &format!(
+            "launch_server invoked (server_name={:?}, port={:?}, steam={}, mem_mb={:?})",
+            options.server_name, options.port, options.steam, options.mem_mb
+        ),
+    );
+    let mode = LaunchMode::Server;
+    ensure_not_running(&window, None, allow_multiple)?;
+    let ctx = resolve_launch_context(window.app_handle().clone())?;
+    let core_jar = ctx
+        .core_jar
+        .clone()
+        .ok_or_else(|| anyhow!("[Error] Avrix-Core.jar not found."))?;
+    ensure_server_binaries_exist(&ctx.game_root)?;
+    let emit = launch_emitter(&window);
+    emit(
+        Level::Info,
+        &format!("[JarLookup] Jar chosen : {}", core_jar.display()),
+    );
+
+    sync_workshop_before_launch(&window, &emit);
+    // No background workshop sync may replace jars until the process is registered
+    let registry = window.app_handle().state::<ProcessRegistry>();
+    let _plugins = registry.lock_plugins();
+    let excluded = prepare_plugins(&window, &emit, &core_jar, mode)?;
+    emit(Level::Info, &format!("[Classpath] {}", ctx.class_path));
+    emit(Level::Info, &format!("[LibPath] {}", ctx.library_path));
+
+    let request = LaunchRequest::Server(options);
+    let command = prepare_command(window.app_handle(), &emit, &ctx, &request)?;
+    emit(
+        Level::Info,
+        &format!("Java used  : {}", ctx.java_path.display()),
+    );
+    let command_line = format!("{} {}", ctx.java_path.display(), command.shown.join(" "));
+    emit(Level::Info, &format!("Command: {}", command_line));
+    let instance = spawn_and_stream(
+        &window,
+        &ctx,
+        &command,
+        &command_line,
+        excluded,
+        request,
+        allow_multiple,
+    )?;
+    emit(
+        Level::Info,
+        &format!("Server process spawned successfully (pid {})", instance.pid),
+    );
+    Ok("[Server launch in progress – live stream]".into())
+}
+
+/// Relaunches a running instance with the parameters it was started with, once the old
+/// one has exited and its watcher has put its plugins back and reported the exit.
+pub fn restart_game(window: Window, id: u64) -> Result<String> {
+    let registry = window.app_handle().state::<ProcessRegistry>();
+    let allow_multiple = registry.allows_multiple(id);
+    // Checked before stopping, so a relaunch that would be refused leaves the instance alone
+    ensure_not_running(&window, Some(id), allow_multiple)?;
+    let instance = processes::stop(registry.inner(), id)?;
+    registry.join_watcher(id);
+    match instance.request {
+        LaunchRequest::Client { steam, mem_mb } => {
+            launch_game(window, steam, mem_mb, allow_multiple)
+        }
+        LaunchRequest::Server(options) => launch_server(window, options, allow_multiple),
+    }
+}
+
+// Client and server share `plugins/`, and each launch sets aside the jars it excludes: without
+// `allow_multiple`, any running instance (other than `except`) blocks the launch.
+fn ensure_not_running(window: &Window, except: Option<u64>, allow_multiple: bool) -> Result<()> {
+    if allow_multiple {
+        return Ok(());
+    }
+    let registry = window.app_handle().state::<ProcessRegistry>();
+    if let Some(other) = registry.list().iter().find(|i| Some(i.id) != except) {
+        return Err(anyhow!(
+            "[AlreadyRunning] Une instance {} est déjà en cours d'exécution.",
+            processes::mode_of(&other.request).as_str()
+        ));
+    }
+    Ok(())
+}
+
+fn server_args(options: &ServerLaunchOptions) -> Result<Vec<String>> {
+    let mut args = Vec::new();
+    if let Some(name) = options
+        .server_name
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+    {
+        if !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(anyhow!(
+                "[Server] Invalid server name '{}' (letters, digits, '-' and '_' only)",
+                name
+            ));
+        }
+        args.push("-servername".into());
+        args.push(name.to_string());
+    }
+    if let Some(pw) = options.admin_password.as_deref().filter(|s| !s.is_empty()) {
+        args.push("-adminpassword".into());
+        args.push(pw.to_string());
+    }
+    if let Some(port) = options.port {
+        if port == 0 {
+            return Err(anyhow!("[Server] Invalid port 0"));
+        }
+        args.push("-port".into());
+        args.push(port.to_string());
+    }
+    Ok(args)
+}
+
+pub type Emit<'a> = Box<dyn Fn(Level, &str) + 'a>;
+
+fn launch_emitter(window: &Window) -> Emit<'_> {
+    Box::new(move |lvl: Level, m: &str| {
+        match lvl {
+            Level::Info => info("launch", m),
+            Level::Warn => crate::logger::warn("launch", m),
+            Level::Error => error("launch", m),
+        }
+        let _ = window.emit("launch-log", m.to_string());
+        let _ = emit_app_log(window, lvl, "launch", m);
+    })
+}
+
+/// Refreshes workshop-installed plugins when enabled in the settings. A failed sync is
+/// reported but never blocks the launch.
+fn sync_workshop_before_launch(window: &Window, emit: &Emit) {
+    if !workshop_sync::load_settings(window.app_handle()).before_launch {
+        return;
+    }
+    match workshop_sync::sync(window.app_handle(), false) {
+        Ok(r) if r.updated > 0 || r.flagged > 0 => emit(
+            Level::Info,
+            &format!(
+                "[Workshop] {} plugin(s) updated, {} flagged",
+                r.updated, r.flagged
+            ),
+        ),
+        Ok(_) => {}
+        Err(e) => emit(Level::Warn, &format!("[Workshop] Sync skipped: {}", e)),
+    }
+}
+
+// Scans plugins against the launch core jar, applies the duplicate policy, filters by
+// environment and checks dependencies. Returns the jars to set aside for this launch:
+// duplicate losers and plugins for the other environment. Enable states are left untouched.
+fn prepare_plugins(
+    window: &Window,
+    emit: &Emit,
+    core_jar: &Path,
+    mode: LaunchMode,
+) -> Result<Vec<String>> {
+    let plugins_dir = crate::util::resolve_plugins_dir();
+    // Jars left aside by a previous session that did not exit cleanly
+    if window
+        .app_handle()
+        .state::<ProcessRegistry>()
+        .list()
+        .is_empty()
+    {
+        environment::restore_skipped(&plugins_dir);
+    }
+    let mut scanned = crate::util::scan_plugins_with_core(window, core_jar)?;
+    let mut skipped: Vec<String> = Vec::new();
+    if !scanned.warnings.is_empty() {
+        let policy = crate::duplicates::load_policy(window.app_handle());
+        if policy == DuplicatePolicy::Refuse {
+            let details = scanned
+                .warnings
+                .iter()
+                .map(|w| format!(" - {}", w.message))
+                .collect::<Vec<_>>()
+                .join("\n");
+            emit(
+                Level::Error,
+                &format!("[Duplicates] Duplicate plugins enabled:\n{}", details),
+            );
+            return Err(anyhow!(
+                "[Duplicates] Duplicate plugins enabled:\n{}",
+                details
+            ));
+        }
+        for (loser, kept) in crate::duplicates::losers(&scanned.plugins, &scanned.warnings, policy)
+        {
+            emit(
+                Level::Warn,
+                &format!("[Duplicates] Skipped {} (keeping {})", loser, kept),
+            );
+            if let Some(p) = scanned.plugins.iter_mut().find(|p| p.name == loser) {
+                p.enabled = Some(false);
+            }
+            skipped.push(loser);
+        }
+    }
+    let excluded = environment::excluded(&scanned.plugins, mode);
+    for (name, reason) in excluded.iter() {
+        emit(
+            Level::Info,
+            &format!("[Environment] Skipped {} ({})", name, reason),
+        );
+        if let Some(p) = scanned.plugins.iter_mut().find(|p| &p.name == name) {
+            p.enabled = Some(false);
+        }
+    }
+    let report = crate::deps::resolve(&scanned.plugins);
+    for issue in report.issues.iter().filter(|i| i.optional) {
+        emit(
+            Level::Warn,
+            &format!(
+                "[Dependencies] (optional) [{}] {}",
+                issue.plugin_id, issue.message
+            ),
+        );
+    }
+    if !report.satisfied {
+        let details = crate::deps::format_report(&report);
+        emit(
+            Level::Error,
+            &format!(
+                "[Dependencies] Unsatisfied plugin dependencies:\n{}",
+                details
+            ),
+        );
+        return Err(anyhow!(
+            "[Dependencies] Unsatisfied plugin dependencies:\n{}",
+            details
+        ));
+    }
+    skipped.extend(excluded.into_iter().map(|(n, _)| n));
+    Ok(skipped)
+}
+
+// Flags depend on the selected runtime: `EnableDynamicAgentLoading` only exists from Java 21,
+// ZGC needs a 64-bit JVM and Java 15+.
+fn jvm_args(ctx: &LaunchContext, mode: LaunchMode, steam: bool, mem_mb: u64) -> Vec<String> {
+    let is_64 = ctx
+        .java
+        .bits
+        .map(|b| b == 64)
+        .unwrap_or(cfg!(target_pointer_width = "64"));
+    let major = ctx.java.major.unwrap_or(crate::java::DEFAULT_MIN_JAVA);
+    let mut args: Vec<String> = vec!["-Djdk.attach.allowAttachSelf=true".into()];
+    if major >= 21 {
+        args.push("-XX:+EnableDynamicAgentLoading".into());
+    }
+    args.extend([
         "-Djava.awt.headless=true".into(),
-        "-Davrix.mode=client".into(),
+        format!("-Davrix.mode={}", mode.as_str()),
         format!("-Dzomboid.steam={}", if steam { 1 } else { 0 }),
         "-Dzomboid.znetlog=1".into(),
         format!("-Djava.library.path={}", ctx.library_path),
-    ];
-    let target_mb: u64 = mem_mb.unwrap_or_else(|| if is_64 { 3072 } else { 1200 });
-    let target_mb = target_mb.max(256);
-    if is_64 {
+    ]);
+    let target_mb = mem_mb.max(256);
+    if is_64 && major >= 15 {
         args.push("-XX:+UseZGC".into());
     } else {
         args.push("-XX:+UseG1GC".into());
@@ -55,21 +357,108 @@ pub fn launch_game(window: Window, steam: bool, mem_mb: Option<u64>) -> Result<S
     let xms_mb = std::cmp::max(256, std::cmp::min(target_mb / 2, target_mb));
     args.push(format!("-Xms{}m", xms_mb));
     args.push(format!("-Xmx{}m", target_mb));
+    args
+}
+
+pub const MAIN_CLASS: &str = "com.avrix.Launcher";
+
+/// Fully resolved command line of a launch request.
+pub struct PreparedCommand {
+    /// JVM options: launcher defaults, preset and user options.
+    pub jvm_args: Vec<String>,
+    /// Arguments after the main class: server arguments, preset and user program arguments.
+    pub program_args: Vec<String>,
+    /// Everything passed to Java.
+    pub args: Vec<String>,
+    /// `args` with the admin password masked, for logs.
+    pub shown: Vec<String>,
+    pub env: BTreeMap<String, String>,
+}
+
+// The admin password never goes to the logs
+fn mask_secrets(args: &[String]) -> Vec<String> {
+    let mut shown = Vec::with_capacity(args.len());
+    let mut hide_next = false;
+    for a in args.iter() {
+        shown.push(if hide_next {
+            "********".into()
+        } else {
+            a.clone()
+        });
+        hide_next = a == "-adminpassword";
+    }
+    shown
+}
+
+/// Launcher arguments + preset + user options, then the classpath, main class and program
+/// arguments.
+pub fn prepare_command(
+    app: &AppHandle,
+    emit: &Emit,
+    ctx: &LaunchContext,
+    request: &LaunchRequest,
+) -> Result<PreparedCommand> {
+    let is_64 = cfg!(target_pointer_width = "64");
+    let (mode, steam, mem_mb, mut program_args) = match request {
+        LaunchRequest::Client { steam, mem_mb } => (
+            LaunchMode::Client,
+            *steam,
+            mem_mb.unwrap_or(if is_64 { 3072 } else { 1200 }),
+            Vec::new(),
+        ),
+        LaunchRequest::Server(options) => (
+            LaunchMode::Server,
+            options.steam,
+            options.mem_mb.unwrap_or(4096),
+            server_args(options)?,
+        ),
+    };
+    let options = launch_options::load_options(app);
+    let mut jvm_args = jvm_args(ctx, mode, steam, mem_mb);
+    let applied = launch_options::apply(&mut jvm_args, &options, mode).inspect_err(|e| {
+        emit(Level::Error, &e.to_string());
+    })?;
+    if options.preset != LaunchPreset::Default {
+        emit(
+            Level::Info,
+            &format!("[JvmArgs] Preset: {:?}", options.preset),
+        );
+    }
+    for w in applied.warnings.iter() {
+        emit(Level::Warn, &format!("[JvmArgs] {}", w));
+    }
+    program_args.extend(applied.program_args);
+    let mut args = jvm_args.clone();
     args.push("-cp".into());
     args.push(ctx.class_path.clone());
-    args.push("com.avrix.Launcher".into());
-    emit(
-        Level::Info,
-        &format!("Java used  : {}", ctx.java_path.display()),
-    );
-    emit(
-        Level::Info,
-        &format!("Command: {} {}", ctx.java_path.display(), args.join(" ")),
-    );
+    args.push(MAIN_CLASS.into());
+    args.extend(program_args.iter().cloned());
+    Ok(PreparedCommand {
+        shown: mask_secrets(&args),
+        jvm_args,
+        program_args,
+        args,
+        env: applied.env,
+    })
+}
 
+// Spawns Java in the game root and streams stdout/stderr as `launch-log` events and to the
+// session log; `launch-exit` carries the exit code, preceded by `launch-crash` with a crash
+// report when the exit was abnormal. Jars excluded for this launch are set aside
+// until the process exits. `command_line` is the loggable form (secrets masked).
+fn spawn_and_stream(
+    window: &Window,
+    ctx: &LaunchContext,
+    prepared: &PreparedCommand,
+    command_line: &str,
+    excluded: Vec<String>,
+    request: LaunchRequest,
+    allow_multiple: bool,
+) -> Result<GameInstance> {
     let mut command = Command::new(&ctx.java_path);
     command
-        .args(&args)
+        .args(&prepared.args)
+        .envs(&prepared.env)
         .current_dir(&ctx.work_dir)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
@@ -79,95 +468,218 @@ pub fn launch_game(window: Window, steam: bool, mem_mb: Option<u64>) -> Result<S
         command.creation_flags(0x08000000);
     }
 
-    let mut child = command.spawn()?;
-    emit(Level::Info, "Process spawned successfully");
+    // A missing session log never prevents the launch
+    let running: Vec<String> = window
+        .app_handle()
+        .state::<ProcessRegistry>()
+        .list()
+        .into_iter()
+        .filter_map(|i| i.log_file)
+        .collect();
+    let session = match SessionLog::create(
+        &SessionHeader {
+            mode: processes::mode_of(&request),
+            java_path: &ctx.java_path,
+            work_dir: &ctx.work_dir,
+            class_path: &ctx.class_path,
+            command_line,
+        },
+        &running,
+    ) {
+        Ok(s) => Some(Arc::new(s)),
+        Err(e) => {
+            crate::logger::warn("launch", &format!("Cannot create session log: {}", e));
+            None
+        }
+    };
+    let plugins_dir = crate::util::resolve_plugins_dir();
+    let set_aside = environment::set_aside(&plugins_dir, &excluded)?;
+    let emit = launch_emitter(window);
+    for name in set_aside.left_in_place.iter() {
+        emit(
+            Level::Warn,
+            &format!(
+                "[Environment] Could not set aside {}, it stays loaded",
+                name
+            ),
+        );
+    }
+    let mut child = match command.spawn() {
+        Ok(c) => c,
+        Err(e) => {
+            set_aside.restore();
+            return Err(e.into());
+        }
+    };
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    let child = Arc::new(Mutex::new(child));
+    let instance = window.app_handle().state::<ProcessRegistry>().register(
+        child.clone(),
+        request,
+        session.as_ref().map(|s| s.file_name().to_string()),
+        allow_multiple,
+    );
+    let tail = Arc::new(OutputTail::default());
     let window_out = window.clone();
-    if let Some(out) = child.stdout.take() {
-        std::thread::spawn(move || {
+    let session_out = session.clone();
+    let tail_out = tail.clone();
+    let mut readers = Vec::new();
+    if let Some(out) = stdout {
+        readers.push(std::thread::spawn(move || {
             let reader = BufReader::new(out);
             for line in reader.lines().flatten() {
+                if let Some(s) = &session_out {
+                    s.write_line(&line, false);
+                }
+                tail_out.push(&line);
                 let _ = window_out.emit("launch-log", line);
             }
-        });
+        }));
     }
     let window_err = window.clone();
-    if let Some(err) = child.stderr.take() {
-        std::thread::spawn(move || {
+    let session_err = session.clone();
+    let tail_err = tail.clone();
+    if let Some(err) = stderr {
+        readers.push(std::thread::spawn(move || {
             let reader = BufReader::new(err);
             for line in reader.lines().flatten() {
+                if let Some(s) = &session_err {
+                    s.write_line(&line, true);
+                }
+                tail_err.push(&line);
                 let _ = window_err.emit("launch-log", line);
             }
-        });
+        }));
     }
     let window_exit = window.clone();
-    std::thread::spawn(move || {
-        if let Ok(status) = child.wait() {
+    let id = instance.id;
+    let pid = instance.pid;
+    let mode = processes::mode_of(&instance.request);
+    let work_dir = ctx.work_dir.clone();
+    let max_heap_mb = crash::max_heap_mb(&prepared.args);
+    let watcher = std::thread::spawn(move || {
+        let status = processes::wait_for_exit(&child);
+        // Instances stopped from the launcher are no longer `Running` here
+        let stopped = window_exit
+            .app_handle()
+            .state::<ProcessRegistry>()
+            .finish(id)
+            .map(|i| i.status != InstanceStatus::Running)
+            .unwrap_or(true);
+        for r in readers {
+            let _ = r.join();
+        }
+        set_aside.restore();
+        if let Some(s) = &session {
+            s.write_exit(status.and_then(|st| st.code()));
+        }
+        if let Some(status) = status {
+            if !stopped {
+                let report = crash::analyze(&ExitContext {
+                    pid,
+                    mode,
+                    exit_code: status.code(),
+                    work_dir: &work_dir,
+                    max_heap_mb,
+                    output: &tail,
+                    session_log: session.as_ref().map(|s| s.file_name().to_string()),
+                });
+                if let Some(report) = report {
+                    error("launch", &format!("[Crash] {}", report.summary));
+                    crash::save_report(&report);
+                    let _ = window_exit.emit("launch-crash", report);
+                }
+            }
             let code = status.code().unwrap_or(-1);
             let _ = window_exit.emit("launch-exit", code);
         }
+        window_exit
+            .app_handle()
+            .state::<ProcessRegistry>()
+            .watcher_done(id);
     });
-    Ok("[Launch in progress – live stream]".into())
+    window
+        .app_handle()
+        .state::<ProcessRegistry>()
+        .set_watcher(id, watcher);
+    Ok(instance)
 }
 
-struct LaunchContext {
-    core_jar: Option<PathBuf>,
-    work_dir: PathBuf,
-    class_path: String,
-    library_path: String,
-    java_path: PathBuf,
+pub struct LaunchContext {
+    pub core_jar: Option<PathBuf>,
+    /// Other `Avrix-Core*.jar` files found, with the reason they were not used.
+    pub rejected_core_jars: Vec<RejectedCandidate>,
+    /// Install folder picked by the user or detected; `work_dir` is where the game classes are.
+    pub game_root: PathBuf,
+    pub work_dir: PathBuf,
+    pub class_path: String,
+    pub class_path_entries: Vec<ClassPathEntry>,
+    pub library_path: String,
+    pub java_path: PathBuf,
+    pub java_source: JavaSource,
+    pub java: JavaRuntime,
 }
 
-fn resolve_launch_context(app: AppHandle) -> Result<LaunchContext> {
+pub fn resolve_launch_context(app: AppHandle) -> Result<LaunchContext> {
     let base = std::env::current_dir()?;
     // Use effective game root (override if present, else autodetect)
     let game_root = crate::util::get_effective_game_root(&app);
+    let platform = GamePlatform::current();
+    // Game classes may sit below the install folder (Linux `projectzomboid/`, macOS bundle)
+    let content_dir = platform::content_dir(&game_root, platform);
     // Try selected version folder
     let version_dir: Option<PathBuf> = crate::versions::resolve_selected_version_dir(&app);
     if let Some(ref vd) = version_dir {
         info("launch", &format!("Selected version dir: {}", vd.display()));
     }
-    let mut candidates: Vec<PathBuf> = vec![];
-    let search_dirs = [
-        version_dir.clone().unwrap_or(base.clone()),
-        base.clone(),
-        game_root.clone(),
-        base.join(".."),
-        base.join("..").join(".."),
-        base.join("libs"),
-        base.join("bin"),
-        base.join("core"),
-        base.parent().unwrap_or(&base).to_path_buf(),
-    ];
-    for d in search_dirs.iter() {
-        if d.exists() {
-            for entry in fs::read_dir(d)? {
-                if let Ok(e) = entry {
-                    let p = e.path();
-                    if let Some(name) = p.file_name().and_then(|n| n.to_str()) {
-                        if name.starts_with("Avrix-Core") && name.ends_with(".jar") {
-                            candidates.push(p);
-                        }
-                    }
-                }
-            }
-        }
-    }
-    candidates.sort_by_key(|p| fs::metadata(p).and_then(|m| m.modified()).ok());
-    let core_jar = candidates.last().cloned();
-    let mut raw_entries: Vec<String> = Vec::new();
-    raw_entries.push(game_root.to_string_lossy().to_string());
-    raw_entries.push(base.to_string_lossy().to_string());
+    let mut core_candidates = core_jar_candidates(&base, &content_dir, version_dir.as_ref())?;
+    let core_jar = core_candidates.pop();
+    let rejected_core_jars = core_candidates
+        .into_iter()
+        .rev()
+        .map(|p| RejectedCandidate {
+            path: p.to_string_lossy().to_string(),
+            reason: match &core_jar {
+                Some(c) => format!(
+                    "older than {}",
+                    c.file_name().and_then(|n| n.to_str()).unwrap_or("?")
+                ),
+                None => "not selected".into(),
+            },
+        })
+        .collect();
+    let mut raw_entries: Vec<(String, ClassPathOrigin)> = Vec::new();
+    raw_entries.push((
+        content_dir.to_string_lossy().to_string(),
+        ClassPathOrigin::GameRoot,
+    ));
+    raw_entries.push((
+        base.to_string_lossy().to_string(),
+        ClassPathOrigin::WorkingDir,
+    ));
     if let Some(vd) = &version_dir {
-        raw_entries.push(vd.to_string_lossy().to_string());
+        raw_entries.push((
+            vd.to_string_lossy().to_string(),
+            ClassPathOrigin::VersionDir,
+        ));
     }
-    for p in [version_dir.as_ref().unwrap_or(&base), &base, &game_root] {
+    let jar_dirs = [
+        match &version_dir {
+            Some(vd) => (vd, ClassPathOrigin::VersionDir),
+            None => (&base, ClassPathOrigin::WorkingDir),
+        },
+        (&base, ClassPathOrigin::WorkingDir),
+        (&content_dir, ClassPathOrigin::GameRoot),
+    ];
+    for (p, origin) in jar_dirs {
         if p.exists() {
             for entry in fs::read_dir(p)? {
                 if let Ok(e) = entry {
                     let path = e.path();
                     if let Some(n) = path.file_name().and_then(|n| n.to_str()) {
                         if n.to_lowercase().ends_with(".jar") {
-                            raw_entries.push(path.to_string_lossy().to_string());
+                            raw_entries.push((path.to_string_lossy().to_string(), origin));
                         }
                     }
                 }
@@ -175,32 +687,39 @@ fn resolve_launch_context(app: AppHandle) -> Result<LaunchContext> {
         }
     }
     let mut seen = std::collections::HashSet::new();
-    let mut dedup: Vec<String> = Vec::new();
+    let mut dedup: Vec<(String, ClassPathOrigin)> = Vec::new();
     for e in raw_entries.into_iter() {
-        if seen.insert(e.clone()) {
+        if seen.insert(e.0.clone()) {
             dedup.push(e);
         }
     }
     if let Some(j) = &core_jar {
         let cj = j.to_string_lossy().to_string();
-        dedup.retain(|v| v != &cj);
-        dedup.push(cj);
+        dedup.retain(|(v, _)| v != &cj);
+        dedup.push((cj, ClassPathOrigin::CoreJar));
     }
-    let class_path = dedup.join(if cfg!(windows) { ";" } else { ":" });
-    let win_dir = if cfg!(target_pointer_width = "64") {
-        "win64"
-    } else {
-        "win32"
-    };
+    let class_path = dedup
+        .iter()
+        .map(|(p, _)| p.as_str())
+        .collect::<Vec<_>>()
+        .join(if cfg!(windows) { ";" } else { ":" });
+    let class_path_entries = dedup
+        .into_iter()
+        .map(|(path, origin)| ClassPathEntry {
+            exists: Path::new(&path).exists(),
+            path,
+            origin,
+        })
+        .collect();
+    let native_dir = platform::native_dir_name(platform);
     let mut lib_parts: Vec<String> = vec![];
-    for p in [version_dir.as_ref().unwrap_or(&base), &base, &game_root] {
+    for p in [version_dir.as_ref().unwrap_or(&base), &base] {
         if p.exists() {
             let base_s = p.to_string_lossy().to_string();
             if !lib_parts.contains(&base_s) {
                 lib_parts.push(base_s);
             }
-            let w = p.join(win_dir);
-            if w.exists() {
+            if let Some(w) = native_dir.map(|n| p.join(n)).filter(|w| w.exists()) {
                 let w_s = w.to_string_lossy().to_string();
                 if !lib_parts.contains(&w_s) {
                     lib_parts.push(w_s);
@@ -208,29 +727,101 @@ fn resolve_launch_context(app: AppHandle) -> Result<LaunchContext> {
             }
         }
     }
+    for d in platform::native_dirs(&game_root, platform) {
+        let d_s = d.to_string_lossy().to_string();
+        if !lib_parts.contains(&d_s) {
+            lib_parts.push(d_s);
+        }
+    }
     let library_path = lib_parts.join(if cfg!(windows) { ";" } else { ":" });
-    let java_path = find_java(version_dir.as_ref())?;
-    let work_dir = game_root.clone();
+    let java = crate::java::select(&app, version_dir.as_ref(), core_jar.as_deref())?;
+    let work_dir = content_dir;
     Ok(LaunchContext {
+        game_root,
         core_jar,
+        rejected_core_jars,
         work_dir,
         class_path,
+        class_path_entries,
         library_path,
-        java_path,
+        java_path: PathBuf::from(&java.path),
+        java_source: java.source,
+        java,
     })
 }
 
-/// Ensures that in the given game root, either the 64-bit pair (exe+bat) or the 32-bit pair exists.
-fn ensure_game_binaries_exist(game_root: &Path) -> Result<()> {
-    let pz64_exe = game_root.join("ProjectZomboid64.exe");
-    let pz64_bat = game_root.join("ProjectZomboid64.bat");
-    let pz32_exe = game_root.join("ProjectZomboid32.exe");
-    let pz32_bat = game_root.join("ProjectZomboid32.bat");
+/// Every `Avrix-Core*.jar` in the search folders, oldest first; the last one is used for launch.
+fn core_jar_candidates(
+    base: &Path,
+    game_root: &Path,
+    version_dir: Option<&PathBuf>,
+) -> Result<Vec<PathBuf>> {
+    let mut candidates: Vec<PathBuf> = vec![];
+    let search_dirs = [
+        version_dir.cloned().unwrap_or(base.to_path_buf()),
+        base.to_path_buf(),
+        game_root.to_path_buf(),
+        base.join(".."),
+        base.join("..").join(".."),
+        base.join("libs"),
+        base.join("bin"),
+        base.join("core"),
+        base.parent().unwrap_or(base).to_path_buf(),
+    ];
+    let mut seen = std::collections::HashSet::new();
+    for d in search_dirs.iter() {
+        if d.exists() {
+            for entry in fs::read_dir(d)? {
+                if let Ok(e) = entry {
+                    let p = e.path();
+                    if let Some(name) = p.file_name().and_then(|n| n.to_str()) {
+                        if name.starts_with("Avrix-Core") && name.ends_with(".jar") {
+                            // `..` and the parent folder can be the same directory
+                            if seen.insert(fs::canonicalize(&p).unwrap_or(p.clone())) {
+                                candidates.push(p);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    candidates.sort_by_key(|p| fs::metadata(p).and_then(|m| m.modified()).ok());
+    Ok(candidates)
+}
+
+/// Picks the most recently modified `Avrix-Core*.jar`, looking in the selected version first.
+fn find_core_jar(
+    base: &Path,
+    game_root: &Path,
+    version_dir: Option<&PathBuf>,
+) -> Result<Option<PathBuf>> {
+    Ok(core_jar_candidates(base, game_root, version_dir)?.pop())
+}
+
+/// Core jar a launch would use, without resolving the rest of the launch context.
+pub fn current_core_jar(app: &AppHandle) -> Option<PathBuf> {
+    let base = std::env::current_dir().ok()?;
+    let game_root = crate::util::get_effective_game_root(app);
+    let version_dir = crate::versions::resolve_selected_version_dir(app);
+    find_core_jar(&base, &game_root, version_dir.as_ref())
+        .ok()
+        .flatten()
+}
 
-    let has_64 = pz64_exe.exists() && pz64_bat.exists();
-    let has_32 = pz32_exe.exists() && pz32_bat.exists();
+/// Resolves plugin dependencies against the core jar that would be used for launch.
+pub fn check_plugin_dependencies(window: &Window) -> Result<DependencyReport> {
+    let app = window.app_handle();
+    let base = std::env::current_dir()?;
+    let core_jar = current_core_jar(app).unwrap_or_else(|| base.join("Avrix-Core.jar"));
+    let scanned = crate::util::scan_plugins_with_core(window, &core_jar)?;
+    Ok(crate::deps::resolve(&scanned.plugins))
+}
 
-    if has_64 || has_32 {
+/// Ensures that the game root holds the client launcher of the current platform: the exe+bat
+/// pair on Windows, `ProjectZomboid64.sh` on Linux, the app bundle on macOS.
+pub fn ensure_game_binaries_exist(game_root: &Path) -> Result<()> {
+    if platform::has_client_launcher(game_root, GamePlatform::current()) {
         Ok(())
     } else {
         Err(anyhow!(
@@ -239,45 +830,14 @@ fn ensure_game_binaries_exist(game_root: &Path) -> Result<()> {
     }
 }
 
-fn find_java(version_dir: Option<&PathBuf>) -> Result<PathBuf> {
-    let exe_name = if cfg!(windows) { "javaw.exe" } else { "java" };
-    let fallback_exe_name = if cfg!(windows) { "java.exe" } else { "java" };
-    let mut candidates: Vec<PathBuf> = Vec::new();
-    // Prefer JRE inside selected version
-    if let Some(vd) = version_dir {
-        candidates.push(vd.join("jre").join("bin").join(exe_name));
-        candidates.push(vd.join("jre").join("bin").join(fallback_exe_name));
-    }
-    if let Ok(cur) = std::env::current_dir() {
-        candidates.push(cur.join("jre").join("bin").join(exe_name));
-        candidates.push(cur.join("jre").join("bin").join(fallback_exe_name));
-    }
-    if let Ok(exe_path) = std::env::current_exe() {
-        if let Some(base) = exe_path.parent() {
-            candidates.push(base.join("jre").join("bin").join(exe_name));
-            candidates.push(base.join("jre").join("bin").join(fallback_exe_name));
-            if let Some(parent) = base.parent() {
-                candidates.push(parent.join("jre").join("bin").join(exe_name));
-                candidates.push(parent.join("jre").join("bin").join(fallback_exe_name));
-            }
-        }
-    }
-    if let Ok(home) = std::env::var("JAVA_HOME") {
-        candidates.push(PathBuf::from(&home).join("bin").join(exe_name));
-        candidates.push(PathBuf::from(&home).join("bin").join(fallback_exe_name));
-    }
-    for c in candidates {
-        if c.exists() {
-            return Ok(c);
-        }
-    }
-    if let Ok(p) = which::which(exe_name) {
-        return Ok(p);
-    }
-    if let Ok(p) = which::which(fallback_exe_name) {
-        return Ok(p);
+/// Dedicated server files ship with the game: `StartServer64.bat`/`StartServer32.bat`
+/// (or `start-server.sh` on Linux).
+pub fn ensure_server_binaries_exist(game_root: &Path) -> Result<()> {
+    if platform::has_server_launcher(game_root, GamePlatform::current()) {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "[ServerMissing] Aucun serveur Project Zomboid trouvé sur cet emplacement (StartServer64.bat introuvable)."
+        ))
     }
-    Err(anyhow!(
-        "java not found (version ./jre, bundled ./jre, JAVA_HOME or PATH)"
-    ))
 }
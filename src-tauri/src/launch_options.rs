@@ -0,0 +1,494 @@
+use crate::environment::LaunchMode;
+use crate::logger::info;
+use crate::models::{
+    DiagnosticSeverity, LaunchOptionIssue, LaunchOptions, LaunchPreset, LaunchPresetInfo,
+};
+use crate::store::settings_store;
+use anyhow::{anyhow, Result};
+use std::collections::BTreeMap;
+use tauri::{AppHandle, Manager, Window};
+
+const STORE_OPTIONS_KEY: &str = "launchOptions";
+const DEFAULT_DEBUG_PORT: u16 = 5005;
+
+// Collectors accepted by the JVMs the game ships with or runs on
+const KNOWN_GCS: [&str; 5] = [
+    "-XX:+UseZGC",
+    "-XX:+UseG1GC",
+    "-XX:+UseParallelGC",
+    "-XX:+UseSerialGC",
+    "-XX:+UseShenandoahGC",
+];
+// Removed from the JVM (CMS in 14): the process would not start
+const REMOVED_GCS: [&str; 2] = ["-XX:+UseConcMarkSweepGC", "-XX:+UseParNewGC"];
+// Set by the launcher from the game root, the memory settings and the launch mode
+const MANAGED_PROPERTIES: [&str; 4] = [
+    "avrix.mode",
+    "zomboid.steam",
+    "java.library.path",
+    "jdk.attach.allowAttachSelf",
+];
+// Options that would break classpath handling or the core agent
+const FORBIDDEN_ARGS: [&str; 6] = [
+    "-cp",
+    "-classpath",
+    "--class-path",
+    "-jar",
+    "-XX:+DisableAttachMechanism",
+    "-XX:-EnableDynamicAgentLoading",
+];
+// Read by the JVM itself, so they bypass the checks below
+const JVM_OPTION_VARS: [&str; 3] = ["JAVA_TOOL_OPTIONS", "_JAVA_OPTIONS", "JDK_JAVA_OPTIONS"];
+
+/// Arguments and environment added to a launch by the options and their preset.
+pub struct AppliedOptions {
+    pub program_args: Vec<String>,
+    pub env: BTreeMap<String, String>,
+    pub warnings: Vec<String>,
+}
+
+pub fn load_options(app: &AppHandle) -> LaunchOptions {
+    settings_store(app)
+        .ok()
+        .and_then(|s| s.get(STORE_OPTIONS_KEY))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+fn save_options(app: &AppHandle, options: &LaunchOptions) -> Result<()> {
+    let store = settings_store(app)?;
+    store.set(STORE_OPTIONS_KEY, serde_json::to_value(options)?);
+    store.save().map_err(|e| anyhow!(e.to_string()))
+}
+
+fn issue(field: &str, severity: DiagnosticSeverity, message: String) -> LaunchOptionIssue {
+    LaunchOptionIssue {
+        field: field.to_string(),
+        severity,
+        message,
+    }
+}
+
+fn is_gc_flag(arg: &str) -> bool {
+    arg.starts_with("-XX:+Use") && arg.ends_with("GC")
+}
+
+fn is_jdwp(arg: &str) -> bool {
+    arg.starts_with("-agentlib:jdwp") || arg.starts_with("-Xrunjdwp")
+}
+
+fn property_key(arg: &str) -> Option<&str> {
+    let prop = arg.strip_prefix("-D")?;
+    Some(prop.split_once('=').map(|(k, _)| k).unwrap_or(prop))
+}
+
+/// Heap size of `-Xms512m` / `-Xmx4g` style arguments, in MB.
+pub fn heap_mb(arg: &str, prefix: &str) -> Option<u64> {
+    let v = arg.strip_prefix(prefix)?.to_lowercase();
+    let (num, factor) = match v.chars().last()? {
+        'g' => (&v[..v.len() - 1], 1024),
+        'm' => (&v[..v.len() - 1], 1),
+        'k' => return v[..v.len() - 1].parse::<u64>().ok().map(|k| k / 1024),
+        _ => return v.parse::<u64>().ok().map(|b| b / (1024 * 1024)),
+    };
+    num.parse::<u64>().ok().map(|n| n * factor)
+}
+
+fn debug_agent(options: &LaunchOptions) -> String {
+    format!(
+        "-agentlib:jdwp=transport=dt_socket,server=y,suspend=n,address=127.0.0.1:{}",
+        options.debug_port.unwrap_or(DEFAULT_DEBUG_PORT)
+    )
+}
+
+/// Checks the user-defined part of the options, before they are merged with the launcher's own
+/// arguments.
+pub fn validate(options: &LaunchOptions) -> Vec<LaunchOptionIssue> {
+    use DiagnosticSeverity::{Error, Warning};
+    let mut out = Vec::new();
+    let mut gcs = Vec::new();
+    let mut xms = 0;
+    let mut jdwp = 0;
+    for (i, arg) in options.jvm_args.iter().enumerate() {
+        let field = format!("jvmArgs[{}]", i);
+        let arg = arg.trim();
+        if arg.is_empty() || !arg.starts_with('-') {
+            out.push(issue(
+                &field,
+                Error,
+                format!(
+                    "'{}' is not a JVM option (program arguments go in programArgs)",
+                    arg
+                ),
+            ));
+            continue;
+        }
+        if arg.starts_with("-Xmx") || arg.starts_with("-XX:MaxHeapSize") {
+            out.push(issue(
+                &field,
+                Error,
+                "The maximum heap is set by the memory setting, not by a JVM argument".into(),
+            ));
+        } else if arg.starts_with("-Xms") {
+            xms += 1;
+            if heap_mb(arg, "-Xms").is_none() {
+                out.push(issue(&field, Error, format!("Invalid heap size '{}'", arg)));
+            }
+        } else if FORBIDDEN_ARGS.contains(&arg) {
+            out.push(issue(
+                &field,
+                Error,
+                format!(
+                    "{} is managed by the launcher and cannot be overridden",
+                    arg
+                ),
+            ));
+        } else if REMOVED_GCS.contains(&arg) {
+            out.push(issue(
+                &field,
+                Error,
+                format!(
+                    "{} was removed from recent JVMs; the game would not start",
+                    arg
+                ),
+            ));
+        } else if is_gc_flag(arg) {
+            if !KNOWN_GCS.contains(&arg) {
+                out.push(issue(
+                    &field,
+                    Error,
+                    format!("Unknown garbage collector {}", arg),
+                ));
+            } else if arg == "-XX:+UseZGC" && !cfg!(target_pointer_width = "64") {
+                out.push(issue(&field, Error, "ZGC requires a 64-bit JVM".into()));
+            }
+            gcs.push(arg.to_string());
+        } else if is_jdwp(arg) {
+            jdwp += 1;
+        } else if let Some(key) = property_key(arg) {
+            if MANAGED_PROPERTIES.contains(&key) {
+                out.push(issue(
+                    &field,
+                    Error,
+                    format!(
+                        "-D{} is managed by the launcher and cannot be overridden",
+                        key
+                    ),
+                ));
+            } else if options.system_properties.contains_key(key) {
+                out.push(issue(
+                    &field,
+                    Error,
+                    format!("{} is also defined in systemProperties", key),
+                ));
+            }
+        }
+    }
+    if gcs.len() > 1 {
+        out.push(issue(
+            "jvmArgs",
+            Error,
+            format!("Several garbage collectors selected: {}", gcs.join(", ")),
+        ));
+    }
+    if xms > 1 {
+        out.push(issue("jvmArgs", Error, "-Xms is set more than once".into()));
+    }
+    if jdwp > 1 || (jdwp > 0 && options.preset == LaunchPreset::Debug) {
+        out.push(issue(
+            "jvmArgs",
+            Error,
+            "The JDWP debug agent is configured more than once".into(),
+        ));
+    }
+    if options.jvm_args.iter().any(|a| {
+        is_jdwp(a) && a.contains("address=") && !a.contains("127.0.0.1") && !a.contains("localhost")
+    }) {
+        out.push(issue(
+            "jvmArgs",
+            Warning,
+            "The debug agent listens on all interfaces: anyone on the network can attach to the game".into(),
+        ));
+    }
+    for key in options.system_properties.keys() {
+        let field = format!("systemProperties.{}", key);
+        if key.trim().is_empty() || key.contains(char::is_whitespace) || key.contains('=') {
+            out.push(issue(
+                &field,
+                Error,
+                format!("Invalid property name '{}'", key),
+            ));
+        } else if MANAGED_PROPERTIES.contains(&key.as_str()) {
+            out.push(issue(
+                &field,
+                Error,
+                format!(
+                    "{} is managed by the launcher and cannot be overridden",
+                    key
+                ),
+            ));
+        }
+    }
+    for (i, arg) in options.program_args.iter().enumerate() {
+        if arg.trim().is_empty() {
+            out.push(issue(
+                &format!("programArgs[{}]", i),
+                Warning,
+                "Empty program argument".into(),
+            ));
+        }
+    }
+    for key in options.env.keys() {
+        let field = format!("env.{}", key);
+        if key.is_empty() || key.contains('=') || key.contains('\0') {
+            out.push(issue(
+                &field,
+                Error,
+                format!("Invalid environment variable name '{}'", key),
+            ));
+        } else if JVM_OPTION_VARS.contains(&key.as_str()) {
+            out.push(issue(
+                &field,
+                Warning,
+                format!(
+                    "{} passes options straight to the JVM, they are not checked",
+                    key
+                ),
+            ));
+        }
+    }
+    if options.debug_port == Some(0) {
+        out.push(issue("debugPort", Error, "Invalid debug port 0".into()));
+    }
+    out
+}
+
+// Replaces every argument starting with `prefix` by `value`, or appends it.
+fn set_flag(args: &mut Vec<String>, prefix: &str, value: String) {
+    args.retain(|a| !a.starts_with(prefix));
+    args.push(value);
+}
+
+fn set_gc(args: &mut Vec<String>, gc: &str) {
+    args.retain(|a| !is_gc_flag(a));
+    args.push(gc.to_string());
+}
+
+// JVM arguments and program arguments a preset adds on top of the defaults.
+fn apply_preset(
+    preset: LaunchPreset,
+    options: &LaunchOptions,
+    args: &mut Vec<String>,
+    program_args: &mut Vec<String>,
+) {
+    match preset {
+        LaunchPreset::Default => {}
+        LaunchPreset::LowMemory => {
+            // G1 has a smaller footprint than ZGC and a small initial heap grows on demand
+            set_gc(args, "-XX:+UseG1GC");
+            args.push("-XX:+UseStringDeduplication".into());
+            set_flag(args, "-Xms", "-Xms256m".into());
+        }
+        LaunchPreset::Performance => {
+            // Full heap committed up front: no resizing pauses while playing
+            if let Some(xmx) = args.iter().find(|a| a.starts_with("-Xmx")).cloned() {
+                set_flag(args, "-Xms", xmx.replacen("-Xmx", "-Xms", 1));
+            }
+            args.push("-XX:+AlwaysPreTouch".into());
+            args.push("-XX:+DisableExplicitGC".into());
+        }
+        LaunchPreset::Debug => {
+            args.push(debug_agent(options));
+            args.push("-Davrix.debug=true".into());
+            program_args.push("-debug".into());
+        }
+    }
+}
+
+/// Checks the final JVM argument list, launcher arguments included.
+pub fn validate_final(args: &[String]) -> Vec<LaunchOptionIssue> {
+    use DiagnosticSeverity::Error;
+    let mut out = Vec::new();
+    let xmx: Vec<&String> = args.iter().filter(|a| a.starts_with("-Xmx")).collect();
+    if xmx.len() > 1 {
+        out.push(issue(
+            "jvmArgs",
+            Error,
+            format!(
+                "-Xmx is set more than once: {}",
+                xmx.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(" ")
+            ),
+        ));
+    }
+    let gcs: Vec<&String> = args.iter().filter(|a| is_gc_flag(a)).collect();
+    if gcs.len() > 1 {
+        out.push(issue(
+            "jvmArgs",
+            Error,
+            format!(
+                "Several garbage collectors selected: {}",
+                gcs.iter()
+                    .map(|s| s.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        ));
+    }
+    let max = xmx.last().and_then(|a| heap_mb(a, "-Xmx"));
+    let min = args
+        .iter()
+        .rev()
+        .find(|a| a.starts_with("-Xms"))
+        .and_then(|a| heap_mb(a, "-Xms"));
+    if let (Some(min), Some(max)) = (min, max) {
+        if min > max {
+            out.push(issue(
+                "jvmArgs",
+                Error,
+                format!(
+                    "Initial heap (-Xms {} MB) is larger than the maximum heap ({} MB)",
+                    min, max
+                ),
+            ));
+        }
+    }
+    out
+}
+
+fn format_issues(issues: &[&LaunchOptionIssue]) -> String {
+    issues
+        .iter()
+        .map(|i| format!(" - {}: {}", i.field, i.message))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Adds the preset and the user-defined options to the launcher's JVM arguments (`args`, before
+/// `-cp`). Fails without touching anything when the options or the result are invalid.
+pub fn apply(
+    args: &mut Vec<String>,
+    options: &LaunchOptions,
+    mode: LaunchMode,
+) -> Result<AppliedOptions> {
+    let mut issues = validate(options);
+    let mut merged = args.clone();
+    let mut program_args = Vec::new();
+    apply_preset(options.preset, options, &mut merged, &mut program_args);
+    for arg in options.jvm_args.iter().map(|a| a.trim()) {
+        // An explicit collector or initial heap replaces the launcher's default
+        if is_gc_flag(arg) {
+            merged.retain(|a| !is_gc_flag(a));
+        } else if arg.starts_with("-Xms") {
+            merged.retain(|a| !a.starts_with("-Xms"));
+        }
+        merged.push(arg.to_string());
+    }
+    for (key, value) in options.system_properties.iter() {
+        merged.push(format!("-D{}={}", key, value));
+    }
+    issues.extend(validate_final(&merged));
+    let errors: Vec<&LaunchOptionIssue> = issues
+        .iter()
+        .filter(|i| i.severity == DiagnosticSeverity::Error)
+        .collect();
+    if !errors.is_empty() {
+        return Err(anyhow!(
+            "[JvmArgs] Invalid launch options:\n{}",
+            format_issues(&errors)
+        ));
+    }
+    // `-debug` is a client option of the game
+    if mode == LaunchMode::Server {
+        program_args.retain(|a| a != "-debug");
+    }
+    program_args.extend(options.program_args.iter().cloned());
+    *args = merged;
+    Ok(AppliedOptions {
+        program_args,
+        env: options.env.clone(),
+        warnings: issues
+            .iter()
+            .filter(|i| i.severity == DiagnosticSeverity::Warning)
+            .map(|i| format!("{}: {}", i.field, i.message))
+            .collect(),
+    })
+}
+
+#[tauri::command]
+pub fn get_launch_options(window: Window) -> Result<LaunchOptions, String> {
+    info("launch_options", "get_launch_options invoked");
+    Ok(load_options(window.app_handle()))
+}
+
+/// Saves the options when they contain no error; the returned warnings are saved with them.
+#[tauri::command]
+pub fn set_launch_options(
+    options: LaunchOptions,
+    window: Window,
+) -> Result<Vec<LaunchOptionIssue>, String> {
+    info(
+        "launch_options",
+        &format!("set_launch_options invoked (preset={:?})", options.preset),
+    );
+    let issues = validate(&options);
+    let errors: Vec<&LaunchOptionIssue> = issues
+        .iter()
+        .filter(|i| i.severity == DiagnosticSeverity::Error)
+        .collect();
+    if !errors.is_empty() {
+        return Err(format!(
+            "Invalid launch options:\n{}",
+            format_issues(&errors)
+        ));
+    }
+    save_options(window.app_handle(), &options).map_err(|e| e.to_string())?;
+    Ok(issues)
+}
+
+#[tauri::command]
+pub fn validate_launch_options(options: LaunchOptions) -> Vec<LaunchOptionIssue> {
+    info("launch_options", "validate_launch_options invoked");
+    validate(&options)
+}
+
+#[tauri::command]
+pub fn list_launch_presets() -> Vec<LaunchPresetInfo> {
+    info("launch_options", "list_launch_presets invoked");
+    let options = LaunchOptions::default();
+    [
+        (
+            LaunchPreset::Default,
+            "Default",
+            "ZGC on 64-bit (G1 on 32-bit), initial heap at half the maximum.",
+        ),
+        (
+            LaunchPreset::LowMemory,
+            "Low memory",
+            "G1 with string deduplication and a small initial heap, for machines with little RAM.",
+        ),
+        (
+            LaunchPreset::Performance,
+            "Performance",
+            "Whole heap reserved and touched at startup to avoid resizing pauses.",
+        ),
+        (
+            LaunchPreset::Debug,
+            "Debug",
+            "JDWP agent on 127.0.0.1 (port 5005 by default) and the game's debug mode.",
+        ),
+    ]
+    .into_iter()
+    .map(|(preset, label, description)| {
+        let mut jvm_args = Vec::new();
+        let mut program_args = Vec::new();
+        apply_preset(preset, &options, &mut jvm_args, &mut program_args);
+        LaunchPresetInfo {
+            id: preset,
+            label: label.into(),
+            description: description.into(),
+            jvm_args,
+            program_args,
+        }
+    })
+    .collect()
+}
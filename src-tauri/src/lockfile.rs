@@ -0,0 +1,157 @@
+use crate::logger::{info, warn};
+use crate::models::{LockEntry, LockSource, LockStatus, PluginLock};
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+const LOCK_FILE_NAME: &str = "plugins.lock";
+
+/// Serializes load-modify-save cycles: installs record from background threads too.
+static WRITE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+pub fn lock_path(plugins_dir: &Path) -> PathBuf {
+    plugins_dir.join(LOCK_FILE_NAME)
+}
+
+/// Reads `plugins.lock`; a missing or unreadable file yields an empty lock.
+pub fn load(plugins_dir: &Path) -> PluginLock {
+    let path = lock_path(plugins_dir);
+    if !path.exists() {
+        return PluginLock::default();
+    }
+    match fs::read_to_string(&path)
+        .map_err(anyhow::Error::from)
+        .and_then(|s| serde_json::from_str::<PluginLock>(&s).map_err(anyhow::Error::from))
+    {
+        Ok(lock) => lock,
+        Err(e) => {
+            warn(
+                "lock",
+                &format!("Ignoring unreadable {}: {}", path.display(), e),
+            );
+            PluginLock::default()
+        }
+    }
+}
+
+pub fn save(plugins_dir: &Path, lock: &PluginLock) -> Result<()> {
+    fs::create_dir_all(plugins_dir)?;
+    let path = lock_path(plugins_dir);
+    let tmp = path.with_extension(format!(
+        "lock.{}-{}.tmp",
+        std::process::id(),
+        TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let written =
+        fs::write(&tmp, serde_json::to_string_pretty(lock)?).and_then(|_| fs::rename(&tmp, &path));
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    Ok(written?)
+}
+
+/// Loads the lock, applies `change` and saves it when `change` reports a modification,
+/// all under the process-wide write lock.
+fn update(plugins_dir: &Path, change: impl FnOnce(&mut PluginLock) -> bool) -> Result<()> {
+    let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut lock = load(plugins_dir);
+    if change(&mut lock) {
+        save(plugins_dir, &lock)?;
+    }
+    Ok(())
+}
+
+/// Inserts or replaces the entry for `entry.file_name`.
+pub fn record(plugins_dir: &Path, entry: LockEntry) -> Result<()> {
+    info(
+        "lock",
+        &format!("Pinned {} sha256={}", entry.file_name, entry.sha256),
+    );
+    update(plugins_dir, |lock| {
+        lock.plugins.retain(|e| e.file_name != entry.file_name);
+        lock.plugins.push(entry);
+        lock.plugins.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+        true
+    })
+}
+
+/// Builds a lock entry for an installed jar, reading id/version from its metadata.
+/// The jar is assumed to be an untouched copy of its source (`source_sha256 == sha256`).
+pub fn entry_for(jar: &Path, source: LockSource) -> Result<LockEntry> {
+    let meta = crate::metadata::extract_metadata_from_jar(&jar.to_path_buf()).ok();
+    let sha256 = crate::util::sha256_file(jar)?;
+    Ok(LockEntry {
+        file_name: jar
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default()
+            .to_string(),
+        id: meta.as_ref().and_then(|m| m.id.clone()),
+        version: meta.as_ref().and_then(|m| m.version.clone()),
+        source,
+        source_sha256: Some(sha256.clone()),
+        sha256,
+        installed_at: chrono::Utc::now().timestamp() as u64,
+        etag: None,
+        last_modified: None,
+    })
+}
+
+pub fn find(plugins_dir: &Path, file_name: &str) -> Option<LockEntry> {
+    load(plugins_dir)
+        .plugins
+        .into_iter()
+        .find(|e| e.file_name == file_name)
+}
+
+pub fn remove(plugins_dir: &Path, file_name: &str) -> Result<()> {
+    update(plugins_dir, |lock| {
+        let before = lock.plugins.len();
+        lock.plugins.retain(|e| e.file_name != file_name);
+        lock.plugins.len() != before
+    })
+}
+
+pub fn status_of(lock: &PluginLock, file_name: &str, sha256: &str) -> LockStatus {
+    match lock.plugins.iter().find(|e| e.file_name == file_name) {
+        Some(e) if e.sha256.eq_ignore_ascii_case(sha256) => LockStatus::Locked,
+        Some(_) => LockStatus::Modified,
+        None => LockStatus::Untracked,
+    }
+}
+
+/// Pins the current content of an installed jar (enabled or disabled), keeping the
+/// recorded source when the jar was already tracked.
+pub fn pin(plugins_dir: &Path, file_name: &str) -> Result<LockEntry> {
+    if file_name.contains('/') || file_name.contains('\\') {
+        anyhow::bail!("Invalid name");
+    }
+    let mut jar = plugins_dir.join(file_name);
+    if !jar.is_file() {
+        jar = plugins_dir
+            .join(crate::util::DISABLED_DIR_NAME)
+            .join(file_name);
+    }
+    if !jar.is_file() {
+        anyhow::bail!("Plugin not found");
+    }
+    let previous = find(plugins_dir, file_name);
+    let source = previous
+        .as_ref()
+        .map(|e| e.source.clone())
+        .unwrap_or(LockSource::Local {
+            path: jar.to_string_lossy().to_string(),
+        });
+    let mut entry = entry_for(&jar, source)?;
+    if let Some(prev) = previous {
+        entry.source_sha256 = prev.source_sha256;
+        entry.etag = prev.etag;
+        entry.last_modified = prev.last_modified;
+    }
+    record(plugins_dir, entry.clone())?;
+    Ok(entry)
+}
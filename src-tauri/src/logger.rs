@@ -29,7 +29,7 @@ impl Level {
     }
 }
 
-fn logs_dir() -> PathBuf {
+pub fn logs_dir() -> PathBuf {
     let exe_dir = std::env::current_exe()
         .ok()
         .and_then(|p| p.parent().map(|d| d.to_path_buf()))
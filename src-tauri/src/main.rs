@@ -1,13 +1,32 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
+mod backup;
+mod catalog;
+mod crash;
+mod deps;
+mod download;
+mod duplicates;
+mod environment;
 mod install;
+mod java;
 mod launch;
+mod launch_options;
+mod lockfile;
 mod logger;
 mod metadata;
 mod models;
+mod plan;
+mod platform;
+mod processes;
+mod profiles;
+mod semver;
+mod sessions;
+mod steam;
 mod store;
+mod updates;
 mod util;
 mod versions;
 mod workshop;
+mod workshop_sync;
 
 use crate::logger::info;
 #[tauri::command]
@@ -17,12 +36,87 @@ fn scan_plugins(window: tauri::Window) -> Result<models::PluginsResult, String>
 }
 
 #[tauri::command]
-fn launch_game(window: tauri::Window, steam: bool, mem_mb: Option<u64>) -> Result<String, String> {
+fn launch_game(
+    window: tauri::Window,
+    steam: bool,
+    mem_mb: Option<u64>,
+    allow_multiple: Option<bool>,
+) -> Result<String, String> {
     info(
         "main",
         &format!("launch_game invoked (steam={}, mem_mb={:?})", steam, mem_mb),
     );
-    launch::launch_game(window, steam, mem_mb).map_err(|e| e.to_string())
+    launch::launch_game(window, steam, mem_mb, allow_multiple.unwrap_or(false))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn launch_server(
+    window: tauri::Window,
+    options: models::ServerLaunchOptions,
+    allow_multiple: Option<bool>,
+) -> Result<String, String> {
+    info("main", "launch_server invoked");
+    launch::launch_server(window, options, allow_multiple.unwrap_or(false))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_running_instances(
+    registry: tauri::State<'_, processes::ProcessRegistry>,
+) -> Vec<models::GameInstance> {
+    info("main", "get_running_instances invoked");
+    registry.list()
+}
+
+// Without an id, targets the most recently started instance
+fn instance_id(registry: &processes::ProcessRegistry, id: Option<u64>) -> Result<u64, String> {
+    match id {
+        Some(id) => Ok(id),
+        None => registry
+            .list()
+            .iter()
+            .map(|i| i.id)
+            .max()
+            .ok_or_else(|| "No running instance".to_string()),
+    }
+}
+
+// Can wait for the process for several seconds: run on a blocking thread so neither the UI
+// nor the async runtime's workers are held up
+#[tauri::command]
+async fn stop_game(window: tauri::Window, id: Option<u64>) -> Result<String, String> {
+    use tauri::Manager;
+    info("main", &format!("stop_game invoked (id={:?})", id));
+    let id = instance_id(window.state::<processes::ProcessRegistry>().inner(), id)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let registry = window.state::<processes::ProcessRegistry>();
+        let instance = processes::stop(registry.inner(), id).map_err(|e| e.to_string())?;
+        Ok(format!(
+            "Stopped instance {} (pid {})",
+            instance.id, instance.pid
+        ))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+async fn restart_game(window: tauri::Window, id: Option<u64>) -> Result<String, String> {
+    use tauri::Manager;
+    info("main", &format!("restart_game invoked (id={:?})", id));
+    let id = instance_id(window.state::<processes::ProcessRegistry>().inner(), id)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        launch::restart_game(window, id).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+fn check_plugin_dependencies(window: tauri::Window) -> Result<models::DependencyReport, String> {
+    info("main", "check_plugin_dependencies invoked");
+    launch::check_plugin_dependencies(&window).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -50,8 +144,10 @@ fn install_plugin_local(path: String, window: tauri::Window) -> Result<String, S
     install::install_plugin_local(path, window).map_err(|e| e.to_string())
 }
 
+// Downloads run on a blocking thread so progress events reach the UI and `cancel_download`
+// is not queued behind the download it cancels
 #[tauri::command]
-fn install_plugin_from_url(
+async fn install_plugin_from_url(
     url: String,
     window: tauri::Window,
 ) -> Result<models::InstallFromUrlResult, String> {
@@ -59,7 +155,11 @@ fn install_plugin_from_url(
         "main",
         &format!("install_plugin_from_url invoked (url={})", url),
     );
-    install::install_plugin_from_url(url, window).map_err(|e| e.to_string())
+    tauri::async_runtime::spawn_blocking(move || {
+        install::install_plugin_from_url(url, window).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
 #[tauri::command]
@@ -72,12 +172,16 @@ fn validate_plugin_local(path: String) -> Result<models::ValidationMetadata, Str
 }
 
 #[tauri::command]
-fn validate_plugin_from_url(url: String) -> Result<models::ValidationMetadata, String> {
+async fn validate_plugin_from_url(url: String) -> Result<models::ValidationMetadata, String> {
     info(
         "main",
         &format!("validate_plugin_from_url invoked (url={})", url),
     );
-    install::validate_plugin_from_url(url).map_err(|e| e.to_string())
+    tauri::async_runtime::spawn_blocking(move || {
+        install::validate_plugin_from_url(url).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
 #[tauri::command]
@@ -92,6 +196,61 @@ fn delete_plugin(name: String) -> Result<String, String> {
     util::delete_plugin(name).map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+fn enable_plugin(target: String) -> Result<String, String> {
+    info(
+        "main",
+        &format!("enable_plugin invoked (target={})", target),
+    );
+    util::set_plugin_enabled(target, true).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn disable_plugin(target: String) -> Result<String, String> {
+    info(
+        "main",
+        &format!("disable_plugin invoked (target={})", target),
+    );
+    util::set_plugin_enabled(target, false).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn lock_plugin(name: String) -> Result<models::LockEntry, String> {
+    info("main", &format!("lock_plugin invoked (name={})", name));
+    lockfile::pin(&util::resolve_plugins_dir(), &name).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn rollback_plugin(target: String) -> Result<String, String> {
+    info(
+        "main",
+        &format!("rollback_plugin invoked (target={})", target),
+    );
+    backup::rollback_plugin(target).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn check_plugin_updates(
+    window: tauri::Window,
+) -> Result<models::PluginUpdatesResult, String> {
+    info("main", "check_plugin_updates invoked");
+    tauri::async_runtime::spawn_blocking(move || {
+        updates::check_plugin_updates(&window).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+async fn update_plugin(name: String, window: tauri::Window) -> Result<String, String> {
+    info("main", &format!("update_plugin invoked (name={})", name));
+    tauri::async_runtime::spawn_blocking(move || {
+        updates::update_plugin(name, &window).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
 #[tauri::command]
 fn get_settings_path() -> Result<String, String> {
     info("main", "get_settings_path invoked");
@@ -124,7 +283,7 @@ fn get_game_root_info(app: tauri::AppHandle) -> Result<GameRootInfo, String> {
     use tauri_plugin_store::StoreExt;
     let effective = crate::util::get_effective_game_root(&app);
     let base = std::env::current_dir().unwrap_or_else(|_| std::env::temp_dir());
-    let detected = match crate::util::find_game_root(&base) {
+    let detected = match crate::util::detect_game_root(&base) {
         Some(p) => p.to_string_lossy().to_string(),
         None => String::new(),
     };
@@ -156,7 +315,7 @@ fn set_game_root(app: tauri::AppHandle, path: String) -> Result<(), String> {
     use tauri_plugin_store::StoreExt;
     let p = std::path::PathBuf::from(&path);
     if !crate::util::is_valid_game_root_dir(&p) {
-        return Err("Dossier Project Zomboid invalide: les fichiers .exe et .bat requis sont introuvables.".into());
+        return Err("Dossier Project Zomboid invalide: les fichiers du jeu ou son lanceur sont introuvables.".into());
     }
     let exe_dir = std::env::current_exe()
         .ok()
@@ -214,15 +373,22 @@ fn main() {
         .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_opener::init())
+        .manage(processes::ProcessRegistry::default())
         .setup(|app| {
             let handle = app.handle();
             logger::setup_global_handlers(&handle);
             store::setup_stores(&handle).map_err(tauri::Error::from)?;
+            workshop_sync::sync_on_startup(&handle);
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             scan_plugins,
             launch_game,
+            launch_server,
+            get_running_instances,
+            stop_game,
+            restart_game,
+            check_plugin_dependencies,
             get_memory_info,
             install_plugin_local,
             install_plugin_from_url,
@@ -230,6 +396,12 @@ fn main() {
             validate_plugin_local,
             validate_plugin_from_url,
             delete_plugin,
+            enable_plugin,
+            disable_plugin,
+            lock_plugin,
+            rollback_plugin,
+            check_plugin_updates,
+            update_plugin,
             get_settings_path,
             open_external,
             get_game_root_info,
@@ -238,13 +410,43 @@ fn main() {
             validate_game_root,
             versions::list_versions,
             versions::list_available_versions,
+            versions::get_latest_version,
+            versions::get_release_channel,
+            versions::set_release_channel,
             versions::install_version_local,
             versions::install_version_from_url,
             versions::install_version_from_release,
             versions::repair_version_from_release,
             versions::select_version,
             versions::get_selected_version,
-            versions::delete_version
+            versions::delete_version,
+            profiles::list_profiles,
+            profiles::create_profile,
+            profiles::clone_profile,
+            profiles::delete_profile,
+            profiles::activate_profile,
+            catalog::search_catalog,
+            catalog::get_catalog_plugin,
+            catalog::install_catalog_plugin,
+            duplicates::get_duplicate_policy,
+            duplicates::set_duplicate_policy,
+            sessions::list_game_sessions,
+            sessions::read_game_session,
+            sessions::tail_game_session,
+            crash::get_crash_report,
+            launch_options::get_launch_options,
+            launch_options::set_launch_options,
+            launch_options::validate_launch_options,
+            launch_options::list_launch_presets,
+            plan::plan_launch,
+            plan::export_launch_script,
+            java::list_java_runtimes,
+            java::pin_java_runtime,
+            steam::detect_steam,
+            workshop_sync::sync_workshop_plugins,
+            workshop_sync::get_workshop_sync_settings,
+            workshop_sync::set_workshop_sync_settings,
+            download::cancel_download
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
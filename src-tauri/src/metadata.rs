@@ -1,7 +1,7 @@
 use crate::logger::{error, info};
-use crate::models::RawMetadata;
+use crate::models::{DiagnosticSeverity, MetadataDiagnostic, RawMetadata};
 use anyhow::{anyhow, Result};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub fn extract_metadata_with_base_from_jar(
     path: &PathBuf,
@@ -70,3 +70,309 @@ pub fn is_valid_avrix_plugin(path: &PathBuf) -> bool {
     }
     extract_metadata_from_jar(path).is_ok()
 }
+
+const KNOWN_KEYS: &[&str] = &[
+    "name",
+    "version",
+    "environment",
+    "author",
+    "license",
+    "id",
+    "description",
+    "dependencies",
+    "image",
+    "imageUrl",
+    "internal",
+    "parent",
+    "java",
+    // Written by the launcher for workshop installs
+    "workshopId",
+    "workshop_id",
+];
+const STRING_KEYS: &[&str] = &[
+    "name",
+    "version",
+    "environment",
+    "author",
+    "license",
+    "id",
+    "description",
+    "image",
+    "imageUrl",
+    "parent",
+];
+const ENVIRONMENTS: &[&str] = &["client", "server", "both"];
+
+/// Checks the `metadata.yml` of a jar against the plugin schema and reports every problem found.
+/// Errors make the plugin invalid; warnings are informational.
+pub fn validate_jar(path: &Path) -> Vec<MetadataDiagnostic> {
+    use std::io::Read;
+    let mut out: Vec<MetadataDiagnostic> = Vec::new();
+    let mut push = |field: &str, severity: DiagnosticSeverity, message: String| {
+        out.push(MetadataDiagnostic {
+            field: field.to_string(),
+            severity,
+            message,
+        })
+    };
+    let mut archive = match std::fs::File::open(path)
+        .map_err(anyhow::Error::from)
+        .and_then(|f| zip::ZipArchive::new(f).map_err(anyhow::Error::from))
+    {
+        Ok(a) => a,
+        Err(e) => {
+            push(
+                "",
+                DiagnosticSeverity::Error,
+                format!("Unreadable archive: {}", e),
+            );
+            return out;
+        }
+    };
+    let entries: Vec<String> = archive.file_names().map(|n| n.to_string()).collect();
+    let metadata_files: Vec<&String> = entries
+        .iter()
+        .filter(|n| n.eq_ignore_ascii_case("metadata.yml") || n.ends_with("/metadata.yml"))
+        .collect();
+    let meta_name = match metadata_files.first() {
+        Some(n) => n.to_string(),
+        None => {
+            push(
+                "",
+                DiagnosticSeverity::Error,
+                "metadata.yml not found".into(),
+            );
+            return out;
+        }
+    };
+    if metadata_files.len() > 1 {
+        push(
+            "",
+            DiagnosticSeverity::Error,
+            format!(
+                "Several metadata.yml files in the archive: {}",
+                metadata_files
+                    .iter()
+                    .map(|s| s.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        );
+    }
+    let mut contents = String::new();
+    if let Err(e) = archive
+        .by_name(&meta_name)
+        .map_err(anyhow::Error::from)
+        .and_then(|mut f| f.read_to_string(&mut contents).map_err(anyhow::Error::from))
+    {
+        push(
+            "",
+            DiagnosticSeverity::Error,
+            format!("Cannot read {}: {}", meta_name, e),
+        );
+        return out;
+    }
+    let doc: serde_yaml::Value = match serde_yaml::from_str(&contents) {
+        Ok(v) => v,
+        Err(e) => {
+            push(
+                "",
+                DiagnosticSeverity::Error,
+                format!("Invalid YAML: {}", e),
+            );
+            return out;
+        }
+    };
+    let map = match doc.as_mapping() {
+        Some(m) => m,
+        None => {
+            push(
+                "",
+                DiagnosticSeverity::Error,
+                "metadata.yml must be a mapping of keys to values".into(),
+            );
+            return out;
+        }
+    };
+    let get = |key: &str| map.get(serde_yaml::Value::String(key.to_string()));
+
+    for key in map.keys() {
+        match key.as_str() {
+            Some(k) if KNOWN_KEYS.contains(&k) => {}
+            Some(k) => push(
+                k,
+                DiagnosticSeverity::Warning,
+                format!("Unknown key '{}'", k),
+            ),
+            None => push(
+                "",
+                DiagnosticSeverity::Error,
+                format!("Non-string key {:?}", key),
+            ),
+        }
+    }
+    for key in STRING_KEYS {
+        if let Some(v) = get(key) {
+            if !v.is_string() && !v.is_null() {
+                push(
+                    key,
+                    DiagnosticSeverity::Error,
+                    format!("'{}' must be a string (quote the value)", key),
+                );
+            }
+        }
+    }
+    if let Some(v) = get("internal") {
+        if !v.is_bool() {
+            push(
+                "internal",
+                DiagnosticSeverity::Error,
+                "'internal' must be true or false".into(),
+            );
+        }
+    }
+
+    let id_given = match get("id") {
+        None | Some(serde_yaml::Value::Null) => false,
+        Some(serde_yaml::Value::String(s)) => !s.trim().is_empty(),
+        // Wrong type, already reported above
+        Some(_) => true,
+    };
+    if !id_given {
+        push(
+            "id",
+            DiagnosticSeverity::Error,
+            "Missing required field 'id'".into(),
+        );
+    }
+    match get("version") {
+        None | Some(serde_yaml::Value::Null) => push(
+            "version",
+            DiagnosticSeverity::Error,
+            "Missing required field 'version'".into(),
+        ),
+        Some(serde_yaml::Value::String(v)) => {
+            if v.trim().is_empty() {
+                push(
+                    "version",
+                    DiagnosticSeverity::Error,
+                    "Missing required field 'version'".into(),
+                );
+            } else if crate::semver::Version::parse(v).is_none() {
+                push(
+                    "version",
+                    DiagnosticSeverity::Warning,
+                    format!("'{}' is not a semantic version (MAJOR.MINOR.PATCH)", v),
+                );
+            }
+        }
+        Some(_) => {}
+    }
+    if let Some(env) = get("environment").and_then(|v| v.as_str()) {
+        if !ENVIRONMENTS.contains(&env.trim().to_lowercase().as_str()) {
+            push(
+                "environment",
+                DiagnosticSeverity::Error,
+                format!(
+                    "Invalid environment '{}' (expected client, server or both)",
+                    env
+                ),
+            );
+        }
+    }
+
+    if let Some(java) = get("java") {
+        if crate::java::parse_requirement(java).is_none() {
+            push(
+                "java",
+                DiagnosticSeverity::Error,
+                format!(
+                    "Invalid Java requirement {:?} (expected a major version such as 21 or \">=21\")",
+                    java
+                ),
+            );
+        }
+    }
+
+    match get("dependencies") {
+        None | Some(serde_yaml::Value::Null) => {}
+        Some(serde_yaml::Value::Mapping(deps)) => {
+            for (k, v) in deps.iter() {
+                let dep_id = match k.as_str() {
+                    Some(id) if !id.trim().is_empty() => id,
+                    _ => {
+                        push(
+                            "dependencies",
+                            DiagnosticSeverity::Error,
+                            format!("Invalid dependency id {:?}", k),
+                        );
+                        continue;
+                    }
+                };
+                let field = format!("dependencies.{}", dep_id);
+                match v.as_str() {
+                    Some(req) => {
+                        let req = req.trim().trim_start_matches('?').trim();
+                        if let Err(e) = crate::semver::VersionReq::parse(req) {
+                            push(&field, DiagnosticSeverity::Error, e);
+                        }
+                    }
+                    None => push(
+                        &field,
+                        DiagnosticSeverity::Error,
+                        "Version requirement must be a string (quote the value)".into(),
+                    ),
+                }
+            }
+        }
+        Some(_) => push(
+            "dependencies",
+            DiagnosticSeverity::Error,
+            "'dependencies' must be a map of plugin id to version requirement".into(),
+        ),
+    }
+
+    if let Some(img) = get("image").and_then(|v| v.as_str()) {
+        let is_remote =
+            img.starts_with("http://") || img.starts_with("https://") || img.starts_with("data:");
+        if !is_remote {
+            // Same lookup as the scanner: relative to the metadata.yml folder, then anywhere
+            let base = meta_name.rfind('/').map(|i| &meta_name[..i]).unwrap_or("");
+            let rel = img.trim_start_matches('/');
+            let full = if base.is_empty() {
+                rel.to_string()
+            } else {
+                format!("{}/{}", base, rel)
+            };
+            let found = entries.iter().any(|n| {
+                n.eq_ignore_ascii_case(&full)
+                    || n.eq_ignore_ascii_case(rel)
+                    || n.to_lowercase().ends_with(&rel.to_lowercase())
+            });
+            if !found {
+                push(
+                    "image",
+                    DiagnosticSeverity::Error,
+                    format!("Image '{}' not found in the jar", img),
+                );
+            }
+        }
+    }
+    out
+}
+
+/// Short summary of the errors, e.g. `id: Missing required field 'id'; environment: ...`.
+pub fn summarize(diagnostics: &[MetadataDiagnostic]) -> String {
+    diagnostics
+        .iter()
+        .filter(|d| d.severity == DiagnosticSeverity::Error)
+        .map(|d| {
+            if d.field.is_empty() {
+                d.message.clone()
+            } else {
+                format!("{}: {}", d.field, d.message)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
@@ -1,10 +1,12 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 #[derive(Serialize, Deserialize)]
 pub struct PluginsResult {
     pub plugins: Vec<PluginEntry>,
     pub dir: String,
+    #[serde(default)]
+    pub warnings: Vec<PluginWarning>,
 }
 
 #[derive(Serialize, Deserialize, Default, Clone)]
@@ -29,6 +31,10 @@ pub struct PluginEntry {
     pub internal: Option<bool>,
     #[serde(rename = "parentId")]
     pub parent_id: Option<String>,
+    pub enabled: Option<bool>,
+    pub sha256: Option<String>,
+    pub lock_status: Option<LockStatus>,
+    pub duplicate: Option<DuplicateKind>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -58,6 +64,9 @@ pub struct RawMetadata {
     pub internal: Option<bool>,
     #[serde(default)]
     pub parent: Option<String>,
+    /// Minimum Java version (`java: 21` or `java: ">=21"`), read from the Avrix core jar.
+    #[serde(default)]
+    pub java: Option<serde_yaml::Value>,
 }
 
 #[derive(Serialize)]
@@ -79,11 +88,195 @@ pub struct ValidationMetadata {
     pub size: u64,
     pub sha256: Option<String>,
     pub message: String,
+    pub diagnostics: Vec<MetadataDiagnostic>,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+}
+
+// One metadata.yml problem; `field` is a dotted path (`dependencies.foo`), empty for the whole file
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataDiagnostic {
+    pub field: String,
+    pub severity: DiagnosticSeverity,
+    pub message: String,
+}
+
+/// Named set of JVM and program arguments applied on top of the launcher defaults.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum LaunchPreset {
+    #[default]
+    Default,
+    LowMemory,
+    Performance,
+    Debug,
+}
+
+/// User-defined launch customization, stored under `launchOptions` in the settings store.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchOptions {
+    #[serde(default)]
+    pub preset: LaunchPreset,
+    #[serde(default)]
+    pub jvm_args: Vec<String>,
+    #[serde(default)]
+    pub system_properties: BTreeMap<String, String>,
+    #[serde(default)]
+    pub program_args: Vec<String>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// JDWP port of the debug preset (5005 when unset).
+    #[serde(default)]
+    pub debug_port: Option<u16>,
+}
+
+// `field` names the offending option: `jvmArgs[2]`, `systemProperties.foo`, `env.BAR`
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchOptionIssue {
+    pub field: String,
+    pub severity: DiagnosticSeverity,
+    pub message: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchPresetInfo {
+    pub id: LaunchPreset,
+    pub label: String,
+    pub description: String,
+    pub jvm_args: Vec<String>,
+    pub program_args: Vec<String>,
 }
 
 #[derive(Serialize)]
 pub struct WorkshopScanResult {
     pub found: Vec<String>,
+    /// One entry per distinct jar (same content found twice is listed once)
+    pub items: Vec<WorkshopItem>,
+}
+
+// `mod.info` of the workshop mod shipping a plugin (`key=value` lines)
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkshopModInfo {
+    pub name: Option<String>,
+    pub id: Option<String>,
+    pub description: Option<String>,
+    pub version: Option<String>,
+    pub url: Option<String>,
+    pub poster: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkshopItem {
+    pub path: String,
+    pub file_name: String,
+    pub workshop_id: Option<String>,
+    pub item_dir: Option<String>,
+    /// Last modification of the workshop item folder (seconds since epoch)
+    pub item_modified: Option<u64>,
+    pub sha256: Option<String>,
+    pub name: Option<String>,
+    pub id: Option<String>,
+    pub version: Option<String>,
+    pub environment: Option<String>,
+    pub author: Option<String>,
+    pub description: Option<String>,
+    pub dependencies: Option<HashMap<String, String>>,
+    pub mod_info: Option<WorkshopModInfo>,
+    pub installed: bool,
+    pub installed_file: Option<String>,
+    pub installed_version: Option<String>,
+    /// Other paths holding the same jar
+    pub duplicates: Vec<String>,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum WorkshopSyncStatus {
+    UpToDate,
+    Updated,
+    /// Update available but not applied (dry run or game running)
+    Changed,
+    LocallyModified,
+    /// The workshop item folder is gone, usually after an unsubscribe
+    Unsubscribed,
+    SourceMissing,
+    NotInstalled,
+    Failed,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkshopSyncEntry {
+    pub file_name: String,
+    pub workshop_id: String,
+    pub status: WorkshopSyncStatus,
+    pub source: Option<String>,
+    pub installed_version: Option<String>,
+    pub workshop_version: Option<String>,
+    pub message: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkshopSyncResult {
+    pub entries: Vec<WorkshopSyncEntry>,
+    pub updated: usize,
+    pub flagged: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WorkshopSyncSettings {
+    pub on_startup: bool,
+    pub before_launch: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SteamInfo {
+    pub steam_dirs: Vec<String>,
+    pub libraries: Vec<String>,
+    pub game_dir: Option<String>,
+    pub build_id: Option<String>,
+    pub workshop_roots: Vec<String>,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum DownloadState {
+    Started,
+    Downloading,
+    Retrying,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+// Payload of `download-progress` events; `speedBps` is bytes per second
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadProgress {
+    pub task_id: String,
+    pub url: String,
+    pub file_name: String,
+    pub state: DownloadState,
+    pub downloaded: u64,
+    pub total: Option<u64>,
+    pub speed_bps: u64,
+    pub eta_secs: Option<u64>,
+    pub attempt: u32,
+    pub message: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -114,6 +307,39 @@ pub struct VersionsResult {
     pub selected_id: Option<String>,
 }
 
+/// Release channel of a manifest entry. Each channel also offers the more stable ones, so
+/// the order is significant.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum ReleaseChannel {
+    #[default]
+    Stable,
+    Beta,
+    Nightly,
+}
+
+impl ReleaseChannel {
+    pub const ALL: [ReleaseChannel; 3] = [
+        ReleaseChannel::Stable,
+        ReleaseChannel::Beta,
+        ReleaseChannel::Nightly,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReleaseChannel::Stable => "stable",
+            ReleaseChannel::Beta => "beta",
+            ReleaseChannel::Nightly => "nightly",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<ReleaseChannel> {
+        ReleaseChannel::ALL
+            .into_iter()
+            .find(|c| c.as_str().eq_ignore_ascii_case(name.trim()))
+    }
+}
+
 // Available Avrix versions from GitHub releases
 #[derive(Serialize, Deserialize, Clone, Default)]
 #[serde(rename_all = "camelCase")]
@@ -122,5 +348,400 @@ pub struct AvailableVersion {
     pub version: String,
     pub core_url: String,
     pub jre_url: Option<String>,
+    pub core_size: Option<u64>,
+    pub jre_size: Option<u64>,
+    pub channel: ReleaseChannel,
     pub published_at: Option<String>,
 }
+
+// Plugin dependency resolution
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum DependencyIssueKind {
+    Missing,
+    VersionMismatch,
+    Cycle,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyIssue {
+    pub plugin_id: String,
+    pub plugin_name: String,
+    pub dependency: String,
+    pub required: String,
+    pub found: Option<String>,
+    pub optional: bool,
+    pub kind: DependencyIssueKind,
+    pub message: String,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyReport {
+    pub satisfied: bool,
+    pub issues: Vec<DependencyIssue>,
+}
+
+// Plugin profiles (loadouts)
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilePlugin {
+    pub file_name: String,
+    pub id: Option<String>,
+    pub version: Option<String>,
+    pub sha256: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginProfile {
+    pub name: String,
+    pub plugins: Vec<ProfilePlugin>,
+    pub version_id: Option<String>,
+    #[serde(rename = "memoryMB")]
+    pub memory_mb: Option<u64>,
+    pub created_at: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilesResult {
+    pub profiles: Vec<PluginProfile>,
+    pub active: Option<String>,
+}
+
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileActivationResult {
+    pub profile: String,
+    pub enabled: Vec<String>,
+    pub disabled: Vec<String>,
+    pub missing: Vec<String>,
+}
+
+// Plugin lockfile (plugins.lock)
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum LockSource {
+    Local {
+        path: String,
+    },
+    Url {
+        url: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Workshop {
+        workshop_id: String,
+        path: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LockEntry {
+    pub file_name: String,
+    pub id: Option<String>,
+    pub version: Option<String>,
+    pub source: LockSource,
+    pub sha256: String,
+    pub installed_at: u64,
+    // Hash of the source content at install time (differs from `sha256` once metadata is rewritten)
+    #[serde(default)]
+    pub source_sha256: Option<String>,
+    #[serde(default)]
+    pub etag: Option<String>,
+    #[serde(default)]
+    pub last_modified: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginLock {
+    pub lock_version: u32,
+    pub plugins: Vec<LockEntry>,
+}
+
+impl Default for PluginLock {
+    fn default() -> Self {
+        PluginLock {
+            lock_version: 1,
+            plugins: Vec::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum LockStatus {
+    Locked,
+    Modified,
+    Untracked,
+}
+
+// Plugin update checks
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginUpdateInfo {
+    pub file_name: String,
+    pub id: Option<String>,
+    pub installed_version: Option<String>,
+    pub available_version: Option<String>,
+    pub source: LockSource,
+    pub outdated: bool,
+    pub reason: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginUpdatesResult {
+    pub updates: Vec<PluginUpdateInfo>,
+    pub errors: Vec<String>,
+}
+
+// Remote plugin catalog
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogPluginVersion {
+    pub version: String,
+    pub url: String,
+    pub sha256: Option<String>,
+    #[serde(default)]
+    pub size: Option<u64>,
+    #[serde(default)]
+    pub file_name: Option<String>,
+    #[serde(default)]
+    pub dependencies: Option<HashMap<String, String>>,
+    #[serde(default)]
+    pub published_at: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogPlugin {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub environment: Option<String>,
+    #[serde(default)]
+    pub image_url: Option<String>,
+    #[serde(default)]
+    pub homepage: Option<String>,
+    #[serde(default)]
+    pub versions: Vec<CatalogPluginVersion>,
+    // Filled by the launcher, not read from the catalog
+    #[serde(default)]
+    pub installed_version: Option<String>,
+}
+
+// Several enabled plugins declaring the same id
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum DuplicateKind {
+    // Same id and same version
+    Duplicate,
+    // Same id, different versions
+    VersionConflict,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginWarning {
+    pub kind: DuplicateKind,
+    pub id: String,
+    pub files: Vec<String>,
+    pub versions: Vec<String>,
+    pub message: String,
+}
+
+// What launch does when duplicates are enabled
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum DuplicatePolicy {
+    #[default]
+    Refuse,
+    HighestVersion,
+    NewestFile,
+}
+
+// Dedicated server launch
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerLaunchOptions {
+    pub server_name: Option<String>,
+    #[serde(skip_serializing)]
+    pub admin_password: Option<String>,
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub steam: bool,
+    pub mem_mb: Option<u64>,
+}
+
+// Running game/server processes
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "mode", rename_all = "camelCase")]
+pub enum LaunchRequest {
+    Client {
+        steam: bool,
+        #[serde(rename = "memMb")]
+        mem_mb: Option<u64>,
+    },
+    Server(ServerLaunchOptions),
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum InstanceStatus {
+    Running,
+    Stopping,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GameInstance {
+    pub id: u64,
+    pub pid: u32,
+    pub started_at: u64,
+    pub request: LaunchRequest,
+    pub status: InstanceStatus,
+    /// Session log under `logs/sessions`, when it could be created.
+    pub log_file: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GameSession {
+    pub file_name: String,
+    pub mode: String,
+    pub size: u64,
+    pub modified: u64,
+    pub has_crash_report: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum CrashKind {
+    OutOfMemory,
+    NativeCrash,
+    UnsatisfiedLink,
+    ClassNotFound,
+    AbnormalExit,
+}
+
+/// Installed plugin owning the topmost stack frame of a crash.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashCulprit {
+    pub plugin_id: Option<String>,
+    pub file_name: String,
+    pub package: String,
+    pub frame: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReport {
+    pub kind: CrashKind,
+    pub mode: String,
+    pub exit_code: Option<i32>,
+    pub summary: String,
+    pub culprit: Option<CrashCulprit>,
+    pub evidence: Vec<String>,
+    pub suggestions: Vec<String>,
+    pub hs_err_file: Option<String>,
+    pub session_log: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ClassPathOrigin {
+    GameRoot,
+    VersionDir,
+    WorkingDir,
+    CoreJar,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassPathEntry {
+    pub path: String,
+    pub origin: ClassPathOrigin,
+    pub exists: bool,
+}
+
+/// Where the Java executable used for launch was found.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum JavaSource {
+    Pinned,
+    VersionJre,
+    WorkingDirJre,
+    BundledJre,
+    GameJre,
+    JavaHome,
+    Path,
+    System,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct JavaRuntime {
+    /// Binary used for launch (`javaw.exe` on Windows when present).
+    pub path: String,
+    pub home: String,
+    pub source: JavaSource,
+    pub version: Option<String>,
+    pub major: Option<u32>,
+    pub vendor: Option<String>,
+    pub arch: Option<String>,
+    pub bits: Option<u32>,
+    pub meets_requirement: bool,
+    pub problem: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct JavaRuntimesResult {
+    pub runtimes: Vec<JavaRuntime>,
+    pub required_major: u32,
+    /// Where the requirement comes from: the core jar metadata or the launcher default.
+    pub required_by: String,
+    pub pinned: Option<String>,
+    /// Runtime a launch would use, if any is suitable.
+    pub selected: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RejectedCandidate {
+    pub path: String,
+    pub reason: String,
+}
+
+/// Everything a launch would use, resolved without starting the game.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchPlan {
+    pub mode: String,
+    pub java_path: String,
+    pub java_source: JavaSource,
+    pub java: JavaRuntime,
+    pub work_dir: String,
+    pub core_jar: Option<String>,
+    pub rejected_core_jars: Vec<RejectedCandidate>,
+    pub class_path: Vec<ClassPathEntry>,
+    pub library_path: Vec<String>,
+    pub jvm_args: Vec<String>,
+    pub main_class: String,
+    pub program_args: Vec<String>,
+    pub env: BTreeMap<String, String>,
+    /// Full command line, secrets masked.
+    pub command_line: String,
+    /// What would make the launch fail (missing game files, no core jar).
+    pub problems: Vec<String>,
+}
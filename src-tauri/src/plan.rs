@@ -0,0 +1,213 @@
+use crate::launch::{self, Emit, PreparedCommand, MAIN_CLASS};
+use crate::logger::{info, log};
+use crate::models::{LaunchPlan, LaunchRequest};
+use crate::processes::mode_of;
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::Path;
+use tauri::{Manager, Window};
+
+#[derive(Clone, Copy, PartialEq)]
+enum ScriptFormat {
+    Shell,
+    Batch,
+}
+
+impl ScriptFormat {
+    fn parse(format: Option<&str>) -> Result<ScriptFormat> {
+        match format.map(|f| f.trim().to_lowercase()).as_deref() {
+            None | Some("") => Ok(if cfg!(windows) {
+                ScriptFormat::Batch
+            } else {
+                ScriptFormat::Shell
+            }),
+            Some("sh") | Some("shell") => Ok(ScriptFormat::Shell),
+            Some("bat") | Some("cmd") | Some("batch") => Ok(ScriptFormat::Batch),
+            Some(other) => Err(anyhow!("Unknown script format '{}' (sh or bat)", other)),
+        }
+    }
+}
+
+/// Resolves the launch context, game files, Java and the full command for `request`, without
+/// touching the plugins folder or spawning anything.
+fn build_plan(window: &Window, request: &LaunchRequest) -> Result<(LaunchPlan, PreparedCommand)> {
+    let app = window.app_handle();
+    let ctx = launch::resolve_launch_context(app.clone())?;
+    let mode = mode_of(request);
+    let mut problems = Vec::new();
+    let binaries = match request {
+        LaunchRequest::Client { .. } => launch::ensure_game_binaries_exist(&ctx.game_root),
+        LaunchRequest::Server(_) => launch::ensure_server_binaries_exist(&ctx.game_root),
+    };
+    if let Err(e) = binaries {
+        problems.push(e.to_string());
+    }
+    if ctx.core_jar.is_none() {
+        problems.push("[Error] Avrix-Core.jar not found.".into());
+    }
+    // Plan warnings go to the launcher log only, not to the launch console
+    let emit: Emit = Box::new(|lvl, m| log(lvl, "plan", m));
+    let command = launch::prepare_command(app, &emit, &ctx, request)?;
+    let separator = if cfg!(windows) { ';' } else { ':' };
+    let plan = LaunchPlan {
+        mode: mode.as_str().to_string(),
+        java_path: ctx.java_path.to_string_lossy().to_string(),
+        java_source: ctx.java_source,
+        java: ctx.java.clone(),
+        work_dir: ctx.work_dir.to_string_lossy().to_string(),
+        core_jar: ctx
+            .core_jar
+            .as_ref()
+            .map(|p| p.to_string_lossy().to_string()),
+        rejected_core_jars: ctx.rejected_core_jars.clone(),
+        class_path: ctx.class_path_entries.clone(),
+        library_path: ctx
+            .library_path
+            .split(separator)
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string())
+            .collect(),
+        jvm_args: command.jvm_args.clone(),
+        main_class: MAIN_CLASS.to_string(),
+        program_args: command.shown[command.args.len() - command.program_args.len()..].to_vec(),
+        env: command.env.clone(),
+        command_line: format!("{} {}", ctx.java_path.display(), command.shown.join(" ")),
+        problems,
+    };
+    Ok((plan, command))
+}
+
+fn quote_sh(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+fn quote_bat(arg: &str) -> String {
+    let escaped = arg.replace('%', "%%");
+    if !escaped.is_empty()
+        && escaped
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_.:=+/\\".contains(c))
+    {
+        escaped
+    } else {
+        format!("\"{}\"", escaped.replace('"', "\\\""))
+    }
+}
+
+/// A script running the planned command from the game root. The admin password is left out,
+/// and plugins excluded for the mode are not set aside as the launcher does.
+fn render_script(plan: &LaunchPlan, command: &PreparedCommand, format: ScriptFormat) -> String {
+    let mut args: Vec<&String> = Vec::new();
+    let mut skip_next = false;
+    for a in command.args.iter() {
+        if skip_next {
+            skip_next = false;
+            continue;
+        }
+        if a == "-adminpassword" {
+            skip_next = true;
+            continue;
+        }
+        args.push(a);
+    }
+    let generated = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
+    let mut out = String::new();
+    match format {
+        ScriptFormat::Shell => {
+            out.push_str("#!/bin/sh\n");
+            out.push_str(&format!(
+                "# Avrix {} launch, generated {}\n# Plugins excluded for this mode are not set aside; the admin password is not included.\n",
+                plan.mode, generated
+            ));
+            out.push_str(&format!("cd {} || exit 1\n", quote_sh(&plan.work_dir)));
+            for (k, v) in command.env.iter() {
+                out.push_str(&format!("export {}={}\n", k, quote_sh(v)));
+            }
+            out.push_str(&format!("exec {}", quote_sh(&plan.java_path)));
+            for a in args {
+                out.push_str(" \\\n  ");
+                out.push_str(&quote_sh(a));
+            }
+            out.push('\n');
+        }
+        ScriptFormat::Batch => {
+            out.push_str("@echo off\r\n");
+            out.push_str(&format!(
+                "rem Avrix {} launch, generated {}\r\nrem Plugins excluded for this mode are not set aside; the admin password is not included.\r\n",
+                plan.mode, generated
+            ));
+            out.push_str("setlocal\r\n");
+            out.push_str(&format!(
+                "cd /d {} || exit /b 1\r\n",
+                quote_bat(&plan.work_dir)
+            ));
+            for (k, v) in command.env.iter() {
+                out.push_str(&format!("set \"{}={}\"\r\n", k, v.replace('%', "%%")));
+            }
+            // Console java.exe rather than javaw.exe so the output stays visible
+            let java = plan
+                .java_path
+                .strip_suffix("javaw.exe")
+                .map(|p| format!("{}java.exe", p))
+                .unwrap_or_else(|| plan.java_path.clone());
+            out.push_str(&quote_bat(&java));
+            for a in args {
+                out.push_str(" ^\r\n  ");
+                out.push_str(&quote_bat(a));
+            }
+            out.push_str("\r\n");
+        }
+    }
+    out
+}
+
+#[tauri::command]
+pub fn plan_launch(request: LaunchRequest, window: Window) -> Result<LaunchPlan, String> {
+    info(
+        "plan",
+        &format!("plan_launch invoked (mode={})", mode_of(&request).as_str()),
+    );
+    build_plan(&window, &request)
+        .map(|(plan, _)| plan)
+        .map_err(|e| e.to_string())
+}
+
+/// Renders the planned launch as a shell (`sh`) or batch (`bat`) script, by default for the
+/// current OS. The script is returned and, when `path` is given, also written there.
+#[tauri::command]
+pub fn export_launch_script(
+    request: LaunchRequest,
+    format: Option<String>,
+    path: Option<String>,
+    window: Window,
+) -> Result<String, String> {
+    info(
+        "plan",
+        &format!(
+            "export_launch_script invoked (mode={}, format={:?}, path={:?})",
+            mode_of(&request).as_str(),
+            format,
+            path
+        ),
+    );
+    let run = || -> Result<String> {
+        let format = ScriptFormat::parse(format.as_deref())?;
+        let (plan, command) = build_plan(&window, &request)?;
+        let script = render_script(&plan, &command, format);
+        if let Some(path) = path.as_deref().filter(|p| !p.trim().is_empty()) {
+            let path = Path::new(path);
+            fs::write(path, &script)?;
+            #[cfg(unix)]
+            if format == ScriptFormat::Shell {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(path, fs::Permissions::from_mode(0o755))?;
+            }
+            info(
+                "plan",
+                &format!("Launch script written: {}", path.display()),
+            );
+        }
+        Ok(script)
+    };
+    run().map_err(|e| e.to_string())
+}
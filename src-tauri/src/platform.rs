@@ -0,0 +1,295 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Operating system a Project Zomboid install was built for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GamePlatform {
+    Windows,
+    Linux,
+    MacOs,
+}
+
+impl GamePlatform {
+    pub fn current() -> GamePlatform {
+        if cfg!(windows) {
+            GamePlatform::Windows
+        } else if cfg!(target_os = "macos") {
+            GamePlatform::MacOs
+        } else {
+            GamePlatform::Linux
+        }
+    }
+}
+
+/// Folders shipped next to the game classes on every platform.
+const GAME_DIRS: [&str; 4] = ["zombie", "se", "fmod", "javax"];
+
+/// Client launchers; any complete group marks an install.
+const WINDOWS_LAUNCHERS: [&[&str]; 2] = [
+    &["ProjectZomboid64.exe", "ProjectZomboid64.bat"],
+    &["ProjectZomboid32.exe", "ProjectZomboid32.bat"],
+];
+const LINUX_LAUNCHERS: [&[&str]; 3] = [
+    &["ProjectZomboid64.sh"],
+    &["ProjectZomboid32.sh"],
+    &["ProjectZomboid64", "ProjectZomboid64.json"],
+];
+const MAC_LAUNCHER: &str = "ProjectZomboid";
+const MAC_BUNDLE: &str = "Project Zomboid.app";
+
+/// Dedicated server start scripts, whatever the platform.
+const SERVER_SCRIPTS: [&str; 4] = [
+    "StartServer64.bat",
+    "StartServer32.bat",
+    "StartServer64_nosteam.bat",
+    "start-server.sh",
+];
+
+pub fn has_game_dirs(dir: &Path) -> bool {
+    GAME_DIRS.iter().all(|d| dir.join(d).is_dir())
+}
+
+/// Folder holding the game classes, used as working directory and class path root. Linux
+/// Steam installs keep it in `projectzomboid/` next to the start scripts, macOS in the app
+/// bundle's `Contents/Java`. Falls back to `root` itself.
+pub fn content_dir(root: &Path, platform: GamePlatform) -> PathBuf {
+    let mut candidates = vec![root.to_path_buf()];
+    match platform {
+        GamePlatform::Windows => {}
+        GamePlatform::Linux => candidates.push(root.join("projectzomboid")),
+        GamePlatform::MacOs => {
+            candidates.push(root.join("Contents").join("Java"));
+            candidates.push(root.join(MAC_BUNDLE).join("Contents").join("Java"));
+        }
+    }
+    candidates
+        .into_iter()
+        .find(|d| has_game_dirs(d))
+        .unwrap_or_else(|| root.to_path_buf())
+}
+
+/// Whether the client launcher for `platform` is present in the install at `root`.
+pub fn has_client_launcher(root: &Path, platform: GamePlatform) -> bool {
+    let content = content_dir(root, platform);
+    match platform {
+        GamePlatform::Windows => has_group(root, &WINDOWS_LAUNCHERS),
+        GamePlatform::Linux => {
+            has_group(root, &LINUX_LAUNCHERS) || has_group(&content, &LINUX_LAUNCHERS)
+        }
+        GamePlatform::MacOs => content
+            .parent()
+            .map(|contents| contents.join("MacOS").join(MAC_LAUNCHER).is_file())
+            .unwrap_or(false),
+    }
+}
+
+fn has_group(dir: &Path, groups: &[&[&str]]) -> bool {
+    groups
+        .iter()
+        .any(|files| files.iter().all(|f| dir.join(f).is_file()))
+}
+
+/// Whether a dedicated server start script is present in the install at `root`.
+pub fn has_server_launcher(root: &Path, platform: GamePlatform) -> bool {
+    let content = content_dir(root, platform);
+    SERVER_SCRIPTS
+        .iter()
+        .any(|f| root.join(f).is_file() || content.join(f).is_file())
+}
+
+/// Light-weight validity check: the game folders or the platform's launcher are present.
+pub fn is_valid_game_root(dir: &Path, platform: GamePlatform) -> bool {
+    dir.is_dir()
+        && (has_game_dirs(&content_dir(dir, platform)) || has_client_launcher(dir, platform))
+}
+
+/// Folder of native libraries matching the platform and the launcher's bitness. macOS builds
+/// keep their libraries directly in the content folder.
+pub fn native_dir_name(platform: GamePlatform) -> Option<&'static str> {
+    let is_64 = cfg!(target_pointer_width = "64");
+    match platform {
+        GamePlatform::Windows => Some(if is_64 { "win64" } else { "win32" }),
+        GamePlatform::Linux => Some(if is_64 { "linux64" } else { "linux32" }),
+        GamePlatform::MacOs => None,
+    }
+}
+
+/// Directories to add to `java.library.path` for the install at `root`, existing ones only.
+pub fn native_dirs(root: &Path, platform: GamePlatform) -> Vec<PathBuf> {
+    let content = content_dir(root, platform);
+    let mut out = vec![content.clone()];
+    if let Some(name) = native_dir_name(platform) {
+        out.push(content.join(name));
+    }
+    out.into_iter().filter(|d| d.is_dir()).collect()
+}
+
+/// Java homes bundled with the game, best first: `jre64`/`jre` in the content folder, plus the
+/// runtime bundles under `Contents/PlugIns` on macOS.
+pub fn game_jre_homes(root: &Path, platform: GamePlatform) -> Vec<PathBuf> {
+    let content = content_dir(root, platform);
+    let mut out = Vec::new();
+    for dir in [&content, &root.to_path_buf()] {
+        for name in ["jre64", "jre"] {
+            let home = dir.join(name);
+            if !out.contains(&home) {
+                out.push(home);
+            }
+        }
+    }
+    if platform == GamePlatform::MacOs {
+        if let Some(plugins) = content.parent().map(|c| c.join("PlugIns")) {
+            if let Ok(entries) = fs::read_dir(&plugins) {
+                let mut bundles: Vec<PathBuf> = entries
+                    .flatten()
+                    .map(|e| e.path().join("Contents").join("Home"))
+                    .filter(|h| h.is_dir())
+                    .collect();
+                bundles.sort();
+                out.extend(bundles);
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Temporary fake install, removed on drop.
+    struct Fixture(PathBuf);
+
+    impl Fixture {
+        fn new(name: &str) -> Fixture {
+            let dir = std::env::temp_dir().join(format!(
+                "avrix-platform-{}-{}",
+                name,
+                std::process::id()
+            ));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Fixture(dir)
+        }
+
+        fn dirs(&self, rel: &str) -> &Fixture {
+            for d in GAME_DIRS {
+                fs::create_dir_all(self.0.join(rel).join(d)).unwrap();
+            }
+            self
+        }
+
+        fn file(&self, rel: &str) -> &Fixture {
+            let p = self.0.join(rel);
+            fs::create_dir_all(p.parent().unwrap()).unwrap();
+            fs::write(p, b"").unwrap();
+            self
+        }
+
+        fn mkdir(&self, rel: &str) -> &Fixture {
+            fs::create_dir_all(self.0.join(rel)).unwrap();
+            self
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn windows_install() {
+        let fx = Fixture::new("win");
+        fx.file("ProjectZomboid64.exe")
+            .file("ProjectZomboid64.bat")
+            .file("StartServer64.bat");
+        assert!(is_valid_game_root(&fx.0, GamePlatform::Windows));
+        assert!(has_client_launcher(&fx.0, GamePlatform::Windows));
+        assert!(has_server_launcher(&fx.0, GamePlatform::Windows));
+        assert!(!has_client_launcher(&fx.0, GamePlatform::Linux));
+        assert_eq!(content_dir(&fx.0, GamePlatform::Windows), fx.0);
+    }
+
+    #[test]
+    fn windows_install_needs_both_exe_and_bat() {
+        let fx = Fixture::new("win-partial");
+        fx.file("ProjectZomboid64.exe");
+        assert!(!is_valid_game_root(&fx.0, GamePlatform::Windows));
+    }
+
+    #[test]
+    fn linux_steam_install() {
+        let fx = Fixture::new("linux");
+        fx.file("ProjectZomboid64.sh")
+            .file("start-server.sh")
+            .dirs("projectzomboid")
+            .mkdir("projectzomboid/linux64")
+            .mkdir("projectzomboid/jre64");
+        let content = fx.0.join("projectzomboid");
+        assert!(is_valid_game_root(&fx.0, GamePlatform::Linux));
+        assert!(has_client_launcher(&fx.0, GamePlatform::Linux));
+        assert!(has_server_launcher(&fx.0, GamePlatform::Linux));
+        assert_eq!(content_dir(&fx.0, GamePlatform::Linux), content);
+        assert_eq!(
+            game_jre_homes(&fx.0, GamePlatform::Linux)[0],
+            content.join("jre64")
+        );
+        let natives = native_dirs(&fx.0, GamePlatform::Linux);
+        assert_eq!(natives[0], content);
+        if cfg!(target_pointer_width = "64") {
+            assert_eq!(natives[1], content.join("linux64"));
+        }
+        // The Windows layout is not mistaken for this one
+        assert!(!has_client_launcher(&fx.0, GamePlatform::Windows));
+    }
+
+    #[test]
+    fn linux_content_folder_picked_directly() {
+        let fx = Fixture::new("linux-content");
+        fx.dirs("")
+            .file("ProjectZomboid64")
+            .file("ProjectZomboid64.json");
+        assert!(is_valid_game_root(&fx.0, GamePlatform::Linux));
+        assert!(has_client_launcher(&fx.0, GamePlatform::Linux));
+        assert_eq!(content_dir(&fx.0, GamePlatform::Linux), fx.0);
+    }
+
+    #[test]
+    fn mac_app_bundle() {
+        let fx = Fixture::new("mac");
+        let app = format!("{}/Contents", MAC_BUNDLE);
+        fx.dirs(&format!("{}/Java", app))
+            .file(&format!("{}/MacOS/ProjectZomboid", app))
+            .mkdir(&format!("{}/PlugIns/jre.bundle/Contents/Home", app));
+        let contents = fx.0.join(MAC_BUNDLE).join("Contents");
+        for root in [fx.0.clone(), fx.0.join(MAC_BUNDLE)] {
+            assert!(is_valid_game_root(&root, GamePlatform::MacOs));
+            assert!(has_client_launcher(&root, GamePlatform::MacOs));
+            assert_eq!(
+                content_dir(&root, GamePlatform::MacOs),
+                contents.join("Java")
+            );
+        }
+        let homes = game_jre_homes(&fx.0, GamePlatform::MacOs);
+        assert_eq!(
+            homes.last().unwrap(),
+            &contents.join("PlugIns/jre.bundle/Contents/Home")
+        );
+    }
+
+    #[test]
+    fn empty_or_missing_folder_is_rejected() {
+        let fx = Fixture::new("empty");
+        fx.mkdir("linux64");
+        for platform in [
+            GamePlatform::Windows,
+            GamePlatform::Linux,
+            GamePlatform::MacOs,
+        ] {
+            assert!(!is_valid_game_root(&fx.0, platform));
+            assert!(!has_server_launcher(&fx.0, platform));
+            assert!(!is_valid_game_root(&fx.0.join("missing"), platform));
+        }
+    }
+}
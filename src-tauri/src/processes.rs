@@ -0,0 +1,211 @@
+use crate::environment::LaunchMode;
+use crate::logger::{info, warn};
+use crate::models::{GameInstance, InstanceStatus, LaunchRequest};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::process::{Child, Command, ExitStatus};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+const GRACEFUL_TIMEOUT: Duration = Duration::from_secs(15);
+
+struct Tracked {
+    info: GameInstance,
+    child: Arc<Mutex<Child>>,
+    /// Launched next to other instances on purpose; a restart keeps that choice
+    allow_multiple: bool,
+}
+
+/// Game and server processes started by the launcher, kept in Tauri managed state.
+#[derive(Default)]
+pub struct ProcessRegistry {
+    instances: Mutex<Vec<Tracked>>,
+    /// Exit watcher of each instance, until it has restored the plugins and sent its events
+    watchers: Mutex<HashMap<u64, JoinHandle<()>>>,
+    /// Held by a launch from plugin preparation until its process is registered, and by
+    /// background writers of `plugins/` so they never replace jars in between
+    plugins: Mutex<()>,
+    next_id: AtomicU64,
+}
+
+impl ProcessRegistry {
+    pub fn register(
+        &self,
+        child: Arc<Mutex<Child>>,
+        request: LaunchRequest,
+        log_file: Option<String>,
+        allow_multiple: bool,
+    ) -> GameInstance {
+        let pid = child.lock().map(|c| c.id()).unwrap_or(0);
+        let info = GameInstance {
+            id: self.next_id.fetch_add(1, Ordering::SeqCst) + 1,
+            pid,
+            started_at: chrono::Utc::now().timestamp() as u64,
+            request,
+            status: InstanceStatus::Running,
+            log_file,
+        };
+        if let Ok(mut list) = self.instances.lock() {
+            list.push(Tracked {
+                info: info.clone(),
+                child,
+                allow_multiple,
+            });
+        }
+        info
+    }
+
+    pub fn list(&self) -> Vec<GameInstance> {
+        self.instances
+            .lock()
+            .map(|l| l.iter().map(|t| t.info.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn lock_plugins(&self) -> MutexGuard<'_, ()> {
+        self.plugins.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn allows_multiple(&self, id: u64) -> bool {
+        self.instances
+            .lock()
+            .map(|l| l.iter().any(|t| t.info.id == id && t.allow_multiple))
+            .unwrap_or(false)
+    }
+
+    pub fn get(&self, id: u64) -> Option<GameInstance> {
+        self.list().into_iter().find(|i| i.id == id)
+    }
+
+    /// Removes an instance once its process has exited.
+    pub fn finish(&self, id: u64) -> Option<GameInstance> {
+        let mut list = self.instances.lock().ok()?;
+        let pos = list.iter().position(|t| t.info.id == id)?;
+        Some(list.remove(pos).info)
+    }
+
+    pub fn set_watcher(&self, id: u64, watcher: JoinHandle<()>) {
+        if let Ok(mut w) = self.watchers.lock() {
+            w.insert(id, watcher);
+        }
+    }
+
+    /// Called by the watcher itself as its last step.
+    pub fn watcher_done(&self, id: u64) {
+        if let Ok(mut w) = self.watchers.lock() {
+            w.remove(&id);
+        }
+    }
+
+    /// Waits until the exit watcher of `id` has finished its cleanup.
+    pub fn join_watcher(&self, id: u64) {
+        let watcher = self.watchers.lock().ok().and_then(|mut w| w.remove(&id));
+        if let Some(w) = watcher {
+            let _ = w.join();
+        }
+    }
+
+    fn child_of(&self, id: u64) -> Option<Arc<Mutex<Child>>> {
+        self.instances
+            .lock()
+            .ok()?
+            .iter()
+            .find(|t| t.info.id == id)
+            .map(|t| t.child.clone())
+    }
+
+    fn set_status(&self, id: u64, status: InstanceStatus) {
+        if let Ok(mut list) = self.instances.lock() {
+            if let Some(t) = list.iter_mut().find(|t| t.info.id == id) {
+                t.info.status = status;
+            }
+        }
+    }
+}
+
+pub fn mode_of(request: &LaunchRequest) -> LaunchMode {
+    match request {
+        LaunchRequest::Client { .. } => LaunchMode::Client,
+        LaunchRequest::Server(_) => LaunchMode::Server,
+    }
+}
+
+/// Polls the child until it exits; the lock is only held for each `try_wait` so the
+/// process can be stopped meanwhile.
+pub fn wait_for_exit(child: &Arc<Mutex<Child>>) -> Option<ExitStatus> {
+    loop {
+        match child.lock().ok()?.try_wait() {
+            Ok(Some(status)) => return Some(status),
+            Ok(None) => {}
+            Err(_) => return None,
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+// Asks the process to close: WM_CLOSE through `taskkill` on Windows, SIGTERM elsewhere.
+fn request_termination(pid: u32) -> bool {
+    #[cfg(windows)]
+    let mut command = {
+        use std::os::windows::process::CommandExt;
+        let mut c = Command::new("taskkill");
+        c.args(["/PID", &pid.to_string()]);
+        c.creation_flags(0x08000000);
+        c
+    };
+    #[cfg(not(windows))]
+    let mut command = {
+        let mut c = Command::new("kill");
+        c.args(["-TERM", &pid.to_string()]);
+        c
+    };
+    command.status().map(|s| s.success()).unwrap_or(false)
+}
+
+/// Stops an instance: graceful request first, then a forced kill after a timeout.
+/// Returns once the process is gone.
+pub fn stop(registry: &ProcessRegistry, id: u64) -> Result<GameInstance> {
+    let instance = registry
+        .get(id)
+        .ok_or_else(|| anyhow!("No running instance with id {}", id))?;
+    let child = registry
+        .child_of(id)
+        .ok_or_else(|| anyhow!("No running instance with id {}", id))?;
+    registry.set_status(id, InstanceStatus::Stopping);
+    info(
+        "process",
+        &format!("Stopping instance {} (pid {})", id, instance.pid),
+    );
+    if !request_termination(instance.pid) {
+        warn(
+            "process",
+            &format!("Graceful stop request failed for pid {}", instance.pid),
+        );
+    }
+    let deadline = Instant::now() + GRACEFUL_TIMEOUT;
+    loop {
+        let exited = match child.lock() {
+            Ok(mut c) => !matches!(c.try_wait(), Ok(None)),
+            Err(_) => true,
+        };
+        if exited {
+            return Ok(instance);
+        }
+        if Instant::now() >= deadline {
+            break;
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+    warn(
+        "process",
+        &format!("pid {} still running, killing it", instance.pid),
+    );
+    if let Ok(mut c) = child.lock() {
+        c.kill()?;
+        let _ = c.wait();
+    }
+    Ok(instance)
+}
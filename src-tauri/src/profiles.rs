@@ -0,0 +1,321 @@
+use crate::logger::{info, warn};
+use crate::models::{PluginProfile, ProfileActivationResult, ProfilePlugin, ProfilesResult};
+use crate::store::settings_store;
+use crate::util::{resolve_plugins_dir, sha256_file, DISABLED_DIR_NAME};
+use anyhow::{anyhow, Result};
+use serde_json::json;
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, Manager, Window};
+
+const STORE_PROFILES_KEY: &str = "pluginProfiles";
+const STORE_ACTIVE_KEY: &str = "activeProfile";
+
+struct InstalledJar {
+    path: PathBuf,
+    file_name: String,
+    id: Option<String>,
+    version: Option<String>,
+    sha256: Option<String>,
+    enabled: bool,
+}
+
+fn load_profiles(app: &AppHandle) -> Result<Vec<PluginProfile>> {
+    let store = settings_store(app)?;
+    Ok(store
+        .get(STORE_PROFILES_KEY)
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default())
+}
+
+fn save_profiles(app: &AppHandle, profiles: &[PluginProfile]) -> Result<()> {
+    let store = settings_store(app)?;
+    store.set(STORE_PROFILES_KEY, serde_json::to_value(profiles)?);
+    store.save().map_err(|e| anyhow!(e.to_string()))
+}
+
+fn load_active(app: &AppHandle) -> Option<String> {
+    settings_store(app)
+        .ok()?
+        .get(STORE_ACTIVE_KEY)
+        .and_then(|v| v.as_str().map(|s| s.to_string()))
+}
+
+fn save_active(app: &AppHandle, name: Option<&str>) -> Result<()> {
+    let store = settings_store(app)?;
+    match name {
+        Some(n) => store.set(STORE_ACTIVE_KEY, n),
+        None => {
+            store.delete(STORE_ACTIVE_KEY);
+        }
+    }
+    store.save().map_err(|e| anyhow!(e.to_string()))
+}
+
+// Every external plugin jar, enabled ones first so they win when several jars match a profile entry.
+fn installed_jars() -> Result<Vec<InstalledJar>> {
+    let plugins_dir = resolve_plugins_dir();
+    let mut out = Vec::new();
+    for (dir, enabled) in [
+        (plugins_dir.clone(), true),
+        (plugins_dir.join(DISABLED_DIR_NAME), false),
+    ] {
+        if !dir.is_dir() {
+            continue;
+        }
+        for entry in fs::read_dir(&dir)?.flatten() {
+            let path = entry.path();
+            let file_name = match path.file_name().and_then(|n| n.to_str()) {
+                Some(n) => n.to_string(),
+                None => continue,
+            };
+            let lower = file_name.to_lowercase();
+            if !path.is_file()
+                || !lower.ends_with(".jar")
+                || lower.contains("launcher")
+                || file_name.starts_with("Avrix-Core")
+            {
+                continue;
+            }
+            let meta = crate::metadata::extract_metadata_from_jar(&path).ok();
+            out.push(InstalledJar {
+                sha256: sha256_file(&path).ok(),
+                id: meta.as_ref().and_then(|m| m.id.clone()),
+                version: meta.as_ref().and_then(|m| m.version.clone()),
+                path,
+                file_name,
+                enabled,
+            });
+        }
+    }
+    Ok(out)
+}
+
+// A profile entry matches on sha256 first, then on id (+ version when recorded), then on file name
+// for plugins without an id.
+fn jar_matches(entry: &ProfilePlugin, jar: &InstalledJar) -> bool {
+    if let (Some(a), Some(b)) = (&entry.sha256, &jar.sha256) {
+        if a.eq_ignore_ascii_case(b) {
+            return true;
+        }
+    }
+    match (&entry.id, &jar.id) {
+        (Some(a), Some(b)) if a == b => entry.version.is_none() || entry.version == jar.version,
+        (None, _) => entry.file_name == jar.file_name,
+        _ => false,
+    }
+}
+
+fn snapshot_current(app: &AppHandle, name: &str) -> Result<PluginProfile> {
+    let plugins = installed_jars()?
+        .into_iter()
+        .filter(|j| j.enabled)
+        .map(|j| ProfilePlugin {
+            file_name: j.file_name,
+            id: j.id,
+            version: j.version,
+            sha256: j.sha256,
+        })
+        .collect();
+    let memory_mb = settings_store(app)?
+        .get("memoryMB")
+        .and_then(|v| v.as_u64());
+    Ok(PluginProfile {
+        name: name.to_string(),
+        plugins,
+        version_id: crate::versions::load_selected_id(app),
+        memory_mb,
+        created_at: chrono::Utc::now().timestamp() as u64,
+    })
+}
+
+fn validate_name(name: &str) -> Result<String> {
+    let n = name.trim();
+    if n.is_empty() {
+        return Err(anyhow!("Nom de profil vide"));
+    }
+    Ok(n.to_string())
+}
+
+#[tauri::command]
+pub fn list_profiles(window: Window) -> Result<ProfilesResult, String> {
+    info("profiles", "list_profiles invoked");
+    let app = window.app_handle();
+    let profiles = load_profiles(app).map_err(|e| e.to_string())?;
+    Ok(ProfilesResult {
+        profiles,
+        active: load_active(app),
+    })
+}
+
+/// Creates a profile from the current state: enabled plugins, selected version and memory.
+#[tauri::command]
+pub fn create_profile(name: String, window: Window) -> Result<PluginProfile, String> {
+    info(
+        "profiles",
+        &format!("create_profile invoked (name={})", name),
+    );
+    let app = window.app_handle();
+    let name = validate_name(&name).map_err(|e| e.to_string())?;
+    let mut profiles = load_profiles(app).map_err(|e| e.to_string())?;
+    if profiles.iter().any(|p| p.name == name) {
+        return Err(format!("Le profil {} existe déjà", name));
+    }
+    let profile = snapshot_current(app, &name).map_err(|e| e.to_string())?;
+    profiles.push(profile.clone());
+    save_profiles(app, &profiles).map_err(|e| e.to_string())?;
+    let _ = window.emit(
+        "profiles-log",
+        format!("Profil {} créé ({} plugins)", name, profile.plugins.len()),
+    );
+    Ok(profile)
+}
+
+#[tauri::command]
+pub fn clone_profile(
+    source: String,
+    name: String,
+    window: Window,
+) -> Result<PluginProfile, String> {
+    info(
+        "profiles",
+        &format!("clone_profile invoked (source={}, name={})", source, name),
+    );
+    let app = window.app_handle();
+    let name = validate_name(&name).map_err(|e| e.to_string())?;
+    let mut profiles = load_profiles(app).map_err(|e| e.to_string())?;
+    if profiles.iter().any(|p| p.name == name) {
+        return Err(format!("Le profil {} existe déjà", name));
+    }
+    let mut profile = profiles
+        .iter()
+        .find(|p| p.name == source)
+        .cloned()
+        .ok_or_else(|| "Profil introuvable".to_string())?;
+    profile.name = name;
+    profile.created_at = chrono::Utc::now().timestamp() as u64;
+    profiles.push(profile.clone());
+    save_profiles(app, &profiles).map_err(|e| e.to_string())?;
+    Ok(profile)
+}
+
+#[tauri::command]
+pub fn delete_profile(name: String, window: Window) -> Result<String, String> {
+    info(
+        "profiles",
+        &format!("delete_profile invoked (name={})", name),
+    );
+    let app = window.app_handle();
+    let mut profiles = load_profiles(app).map_err(|e| e.to_string())?;
+    let before = profiles.len();
+    profiles.retain(|p| p.name != name);
+    if profiles.len() == before {
+        return Err("Profil introuvable".into());
+    }
+    save_profiles(app, &profiles).map_err(|e| e.to_string())?;
+    if load_active(app).as_deref() == Some(name.as_str()) {
+        save_active(app, None).map_err(|e| e.to_string())?;
+    }
+    Ok(format!("Profil supprimé: {}", name))
+}
+
+/// Activates a profile: moves matching jars into `plugins/`, every other jar into `plugins/.disabled`,
+/// then applies the profile's version selection and memory setting.
+#[tauri::command]
+pub fn activate_profile(name: String, window: Window) -> Result<ProfileActivationResult, String> {
+    info(
+        "profiles",
+        &format!("activate_profile invoked (name={})", name),
+    );
+    let app = window.app_handle();
+    let profile = load_profiles(app)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| "Profil introuvable".to_string())?;
+    let emit = |m: &str| {
+        info("profiles", m);
+        let _ = window.emit("profiles-log", m.to_string());
+    };
+    let result = reconcile_plugins(&profile, &emit).map_err(|e| e.to_string())?;
+
+    if let Some(vid) = &profile.version_id {
+        crate::versions::save_selected_id(app, Some(vid.clone())).map_err(|e| e.to_string())?;
+        emit(&format!("Version sélectionnée: {}", vid));
+    }
+    if let Some(mb) = profile.memory_mb {
+        let store = settings_store(app).map_err(|e| e.to_string())?;
+        store.set("memoryMB", json!(mb));
+        store.save().map_err(|e| e.to_string())?;
+        emit(&format!("Mémoire: {} MB", mb));
+    }
+    save_active(app, Some(&profile.name)).map_err(|e| e.to_string())?;
+    emit(&format!(
+        "Profil {} activé ({} activés, {} désactivés, {} manquants)",
+        profile.name,
+        result.enabled.len(),
+        result.disabled.len(),
+        result.missing.len()
+    ));
+    Ok(result)
+}
+
+fn reconcile_plugins(
+    profile: &PluginProfile,
+    emit: &dyn Fn(&str),
+) -> Result<ProfileActivationResult> {
+    let jars = installed_jars()?;
+    let plugins_dir = resolve_plugins_dir();
+    let disabled_dir = plugins_dir.join(DISABLED_DIR_NAME);
+    let mut wanted = vec![false; jars.len()];
+    let mut result = ProfileActivationResult {
+        profile: profile.name.clone(),
+        ..Default::default()
+    };
+    for entry in profile.plugins.iter() {
+        match jars
+            .iter()
+            .enumerate()
+            .find(|(i, j)| !wanted[*i] && jar_matches(entry, j))
+        {
+            Some((i, _)) => wanted[i] = true,
+            None => {
+                let label = match (&entry.id, &entry.version) {
+                    (Some(id), Some(v)) => format!("{}@{}", id, v),
+                    (Some(id), None) => id.clone(),
+                    _ => entry.file_name.clone(),
+                };
+                warn(
+                    "profiles",
+                    &format!("Plugin missing for profile: {}", label),
+                );
+                result.missing.push(label);
+            }
+        }
+    }
+    for (jar, want) in jars.iter().zip(wanted) {
+        if want == jar.enabled {
+            continue;
+        }
+        let dest_dir = if want { &plugins_dir } else { &disabled_dir };
+        fs::create_dir_all(dest_dir)?;
+        let dest = dest_dir.join(&jar.file_name);
+        if dest.exists() {
+            emit(&format!(
+                "Ignoré: {} existe déjà dans {}",
+                jar.file_name,
+                dest_dir.to_string_lossy()
+            ));
+            continue;
+        }
+        fs::rename(&jar.path, &dest)?;
+        if want {
+            emit(&format!("Activé: {}", jar.file_name));
+            result.enabled.push(jar.file_name.clone());
+        } else {
+            emit(&format!("Désactivé: {}", jar.file_name));
+            result.disabled.push(jar.file_name.clone());
+        }
+    }
+    Ok(result)
+}
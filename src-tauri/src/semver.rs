@@ -0,0 +1,480 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Semantic version (`MAJOR.MINOR.PATCH[-PRERELEASE][+BUILD]`).
+/// Parsing is lenient: a leading `v` is accepted and missing minor/patch parts default to 0.
+#[derive(Debug, Clone)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    pub pre: Vec<Identifier>,
+    pub build: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Identifier {
+    Numeric(u64),
+    Alpha(String),
+}
+
+impl Identifier {
+    fn parse(s: &str) -> Self {
+        match s.parse::<u64>() {
+            Ok(n) => Identifier::Numeric(n),
+            Err(_) => Identifier::Alpha(s.to_string()),
+        }
+    }
+}
+
+impl Ord for Identifier {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Identifier::Numeric(a), Identifier::Numeric(b)) => a.cmp(b),
+            (Identifier::Numeric(_), Identifier::Alpha(_)) => Ordering::Less,
+            (Identifier::Alpha(_), Identifier::Numeric(_)) => Ordering::Greater,
+            (Identifier::Alpha(a), Identifier::Alpha(b)) => a.cmp(b),
+        }
+    }
+}
+
+impl PartialOrd for Identifier {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Identifier::Numeric(n) => write!(f, "{}", n),
+            Identifier::Alpha(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+impl Version {
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Version {
+            major,
+            minor,
+            patch,
+            pre: Vec::new(),
+            build: None,
+        }
+    }
+
+    pub fn parse(input: &str) -> Option<Version> {
+        match parse_partial(input)? {
+            (_, _, true) => None,
+            (v, _, false) => Some(v),
+        }
+    }
+
+    pub fn is_prerelease(&self) -> bool {
+        !self.pre.is_empty()
+    }
+
+    fn triple(&self) -> (u64, u64, u64) {
+        (self.major, self.minor, self.patch)
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Version {}
+
+impl Ord for Version {
+    // Build metadata does not take part in precedence
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.triple().cmp(&other.triple()).then_with(|| {
+            match (self.pre.is_empty(), other.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => self.pre.cmp(&other.pre),
+            }
+        })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if !self.pre.is_empty() {
+            let pre: Vec<String> = self.pre.iter().map(|i| i.to_string()).collect();
+            write!(f, "-{}", pre.join("."))?;
+        }
+        if let Some(b) = &self.build {
+            write!(f, "+{}", b)?;
+        }
+        Ok(())
+    }
+}
+
+// Parses a possibly partial version (`1`, `1.2`, `1.x`, `1.2.*`). Each of the three numeric parts
+// is returned as `Some(n)` when given, `None` when omitted or a wildcard; the flag tells
+// whether a wildcard was used.
+fn parse_partial(input: &str) -> Option<(Version, [Option<u64>; 3], bool)> {
+    let s = input.trim().trim_start_matches(['v', 'V']);
+    if s.is_empty() {
+        return None;
+    }
+    let (s, build) = match s.split_once('+') {
+        Some((a, b)) if !b.is_empty() => (a, Some(b.to_string())),
+        Some(_) => return None,
+        None => (s, None),
+    };
+    let (core, pre) = match s.split_once('-') {
+        Some((a, b)) if !b.is_empty() => (a, b.split('.').map(Identifier::parse).collect()),
+        Some(_) => return None,
+        None => (s, Vec::new()),
+    };
+    let mut parts: [Option<u64>; 3] = [None, None, None];
+    let mut wildcard = false;
+    for (i, seg) in core.split('.').enumerate() {
+        if i >= 3 {
+            return None;
+        }
+        if matches!(seg, "x" | "X" | "*") {
+            wildcard = true;
+            continue;
+        }
+        // Nothing concrete may follow a wildcard (`1.x.3` is invalid)
+        if wildcard {
+            return None;
+        }
+        parts[i] = Some(seg.parse::<u64>().ok()?);
+    }
+    parts[0]?;
+    let v = Version {
+        major: parts[0].unwrap_or(0),
+        minor: parts[1].unwrap_or(0),
+        patch: parts[2].unwrap_or(0),
+        pre,
+        build,
+    };
+    Some((v, parts, wildcard))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+#[derive(Debug, Clone)]
+struct Comparator {
+    op: Op,
+    version: Version,
+}
+
+impl Comparator {
+    fn matches(&self, v: &Version) -> bool {
+        let ord = v.cmp(&self.version);
+        match self.op {
+            Op::Eq => ord == Ordering::Equal,
+            Op::Gt => ord == Ordering::Greater,
+            Op::Ge => ord != Ordering::Less,
+            Op::Lt => ord == Ordering::Less,
+            Op::Le => ord != Ordering::Greater,
+        }
+    }
+}
+
+/// Version requirement: comparator sets joined by `||`, each set being a whitespace/comma
+/// separated list of comparators that must all match.
+/// Supports `*`, exact (`1.2.3`, `=1.2.3`), `>`, `>=`, `<`, `<=`, caret (`^1.2`), tilde (`~1.2`)
+/// and wildcards (`1.x`, `1.2.*`).
+#[derive(Debug, Clone)]
+pub struct VersionReq {
+    sets: Vec<Vec<Comparator>>,
+}
+
+impl VersionReq {
+    pub fn parse(input: &str) -> Result<VersionReq, String> {
+        let mut sets = Vec::new();
+        for alt in input.split("||") {
+            let mut set = Vec::new();
+            let mut pending_op: Option<&str> = None;
+            for tok in alt
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|t| !t.is_empty())
+            {
+                // Allow an operator separated from its version (`>= 1.0`)
+                let tok_owned;
+                let tok = match pending_op.take() {
+                    Some(op) => {
+                        tok_owned = format!("{}{}", op, tok);
+                        tok_owned.as_str()
+                    }
+                    None => tok,
+                };
+                if matches!(tok, ">" | ">=" | "<" | "<=" | "=" | "^" | "~") {
+                    pending_op = Some(match tok {
+                        ">" => ">",
+                        ">=" => ">=",
+                        "<" => "<",
+                        "<=" => "<=",
+                        "=" => "=",
+                        "^" => "^",
+                        _ => "~",
+                    });
+                    continue;
+                }
+                parse_comparator(tok, &mut set)
+                    .ok_or_else(|| format!("Invalid version requirement: '{}'", input.trim()))?;
+            }
+            if pending_op.is_some() {
+                return Err(format!("Invalid version requirement: '{}'", input.trim()));
+            }
+            sets.push(set);
+        }
+        Ok(VersionReq { sets })
+    }
+
+    pub fn matches(&self, v: &Version) -> bool {
+        self.sets.iter().any(|set| {
+            if !set.iter().all(|c| c.matches(v)) {
+                return false;
+            }
+            // Pre-releases only match when a comparator explicitly opts into that exact release line
+            if v.is_prerelease() {
+                return set
+                    .iter()
+                    .any(|c| c.version.is_prerelease() && c.version.triple() == v.triple());
+            }
+            true
+        })
+    }
+}
+
+fn parse_comparator(tok: &str, out: &mut Vec<Comparator>) -> Option<()> {
+    if matches!(tok, "*" | "x" | "X") || tok.eq_ignore_ascii_case("any") {
+        return Some(());
+    }
+    let (prefix, rest) = [">=", "<=", ">", "<", "=", "^", "~"]
+        .iter()
+        .find_map(|p| tok.strip_prefix(p).map(|r| (*p, r)))
+        .unwrap_or(("", tok));
+    if matches!(rest, "*" | "x" | "X") {
+        return Some(());
+    }
+    let (v, parts, _) = parse_partial(rest)?;
+    let given = parts.iter().take_while(|p| p.is_some()).count();
+    let push =
+        |out: &mut Vec<Comparator>, op: Op, version: Version| out.push(Comparator { op, version });
+    // Upper bound for a partial version (`1` -> 2.0.0, `1.2` -> 1.3.0)
+    let next_partial = |v: &Version| match given {
+        1 => Version::new(v.major + 1, 0, 0),
+        _ => Version::new(v.major, v.minor + 1, 0),
+    };
+    match prefix {
+        "^" => {
+            let upper = if v.major > 0 || given == 1 {
+                Version::new(v.major + 1, 0, 0)
+            } else if v.minor > 0 || given == 2 {
+                Version::new(0, v.minor + 1, 0)
+            } else {
+                Version::new(0, 0, v.patch + 1)
+            };
+            push(out, Op::Ge, v);
+            push(out, Op::Lt, upper);
+        }
+        "~" => {
+            let upper = if given == 1 {
+                Version::new(v.major + 1, 0, 0)
+            } else {
+                Version::new(v.major, v.minor + 1, 0)
+            };
+            push(out, Op::Ge, v);
+            push(out, Op::Lt, upper);
+        }
+        ">=" => push(out, Op::Ge, v),
+        "<" => push(out, Op::Lt, v),
+        ">" if given < 3 => push(out, Op::Ge, next_partial(&v)),
+        ">" => push(out, Op::Gt, v),
+        "<=" if given < 3 => push(out, Op::Lt, next_partial(&v)),
+        "<=" => push(out, Op::Le, v),
+        _ if given < 3 => {
+            let upper = next_partial(&v);
+            push(out, Op::Ge, v);
+            push(out, Op::Lt, upper);
+        }
+        _ => push(out, Op::Eq, v),
+    }
+    Some(())
+}
+
+/// Orders two version strings semantically; unparsable strings sort before valid versions
+/// and are compared as plain text between themselves.
+pub fn compare_str(a: &str, b: &str) -> Ordering {
+    match (Version::parse(a), Version::parse(b)) {
+        (Some(va), Some(vb)) => va.cmp(&vb),
+        (Some(_), None) => Ordering::Greater,
+        (None, Some(_)) => Ordering::Less,
+        (None, None) => a.cmp(b),
+    }
+}
+
+/// Highest stable version among `candidates` matching `req`; pre-releases are only
+/// considered when the requirement explicitly targets them.
+pub fn max_satisfying<'a, I>(candidates: I, req: &VersionReq) -> Option<&'a str>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    candidates
+        .into_iter()
+        .filter_map(|s| Version::parse(s).map(|v| (v, s)))
+        .filter(|(v, _)| req.matches(v))
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, s)| s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(s: &str) -> Version {
+        Version::parse(s).unwrap_or_else(|| panic!("cannot parse {}", s))
+    }
+
+    /// Asserts which of `versions` match `req`.
+    fn check(req: &str, matching: &[&str], not_matching: &[&str]) {
+        let r = VersionReq::parse(req).unwrap();
+        for s in matching {
+            assert!(r.matches(&v(s)), "{} should match {}", req, s);
+        }
+        for s in not_matching {
+            assert!(!r.matches(&v(s)), "{} should not match {}", req, s);
+        }
+    }
+
+    #[test]
+    fn parses_full_and_partial_versions() {
+        let full = v("v1.2.3-beta.1+build.5");
+        assert_eq!((full.major, full.minor, full.patch), (1, 2, 3));
+        assert_eq!(
+            full.pre,
+            vec![Identifier::Alpha("beta".into()), Identifier::Numeric(1)]
+        );
+        assert_eq!(full.build.as_deref(), Some("build.5"));
+        assert_eq!(full.to_string(), "1.2.3-beta.1+build.5");
+        assert_eq!(v("1").to_string(), "1.0.0");
+        assert_eq!(v(" V2.5 ").to_string(), "2.5.0");
+        for bad in [
+            "", "v", "1.x", "1.2.*", "a.b", "1.2.3.4", "1.-2", "1.2.3-", "1.2.3+",
+        ] {
+            assert!(Version::parse(bad).is_none(), "{:?} should not parse", bad);
+        }
+    }
+
+    #[test]
+    fn orders_by_precedence() {
+        let ordered = [
+            "0.9.9",
+            "1.0.0-alpha",
+            "1.0.0-alpha.1",
+            "1.0.0-alpha.beta",
+            "1.0.0-beta",
+            "1.0.0-beta.2",
+            "1.0.0-beta.11",
+            "1.0.0-rc.1",
+            "1.0.0",
+            "1.0.1",
+            "1.10.0",
+            "2.0.0",
+        ];
+        for pair in ordered.windows(2) {
+            assert!(v(pair[0]) < v(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+        // Build metadata and missing parts do not change precedence
+        assert_eq!(v("1.0.0+a"), v("1.0.0+b"));
+        assert_eq!(v("1.2"), v("1.2.0"));
+        assert_eq!(compare_str("1.10.0", "1.9.0"), Ordering::Greater);
+        assert_eq!(compare_str("snapshot", "0.0.1"), Ordering::Less);
+        assert_eq!(compare_str("abc", "abd"), Ordering::Less);
+    }
+
+    #[test]
+    fn exact_and_comparison_operators() {
+        check("1.2.3", &["1.2.3", "1.2.3+meta"], &["1.2.4", "1.2.2"]);
+        check("=1.2.3", &["1.2.3"], &["1.2.4"]);
+        check("1.2", &["1.2.0", "1.2.9"], &["1.3.0", "1.1.9"]);
+        check(">1.2.3", &["1.2.4", "2.0.0"], &["1.2.3"]);
+        check(">1.2", &["1.3.0"], &["1.2.9"]);
+        check(">=1.2.3", &["1.2.3", "1.3.0"], &["1.2.2"]);
+        check("<1.2.3", &["1.2.2", "0.1.0"], &["1.2.3"]);
+        check("<=1.2.3", &["1.2.3"], &["1.2.4"]);
+        check("<=1.2", &["1.2.9"], &["1.3.0"]);
+        check(">= 1.0, < 2.0", &["1.0.0", "1.9.9"], &["0.9.0", "2.0.0"]);
+        check(">=1.0 <2.0", &["1.5.0"], &["2.0.0"]);
+    }
+
+    #[test]
+    fn caret_and_tilde() {
+        check("^1.2.3", &["1.2.3", "1.9.0"], &["1.2.2", "2.0.0"]);
+        check("^1", &["1.0.0", "1.9.9"], &["2.0.0", "0.9.0"]);
+        check("^0.2.3", &["0.2.3", "0.2.9"], &["0.3.0", "0.2.2"]);
+        check("^0.0.3", &["0.0.3"], &["0.0.4"]);
+        check("^0.0", &["0.0.5"], &["0.1.0"]);
+        check("~1.2.3", &["1.2.3", "1.2.9"], &["1.3.0", "1.2.2"]);
+        check("~1.2", &["1.2.0"], &["1.3.0"]);
+        check("~1", &["1.0.0", "1.9.0"], &["2.0.0"]);
+    }
+
+    #[test]
+    fn wildcards_and_alternatives() {
+        check("*", &["0.0.1", "9.9.9"], &[]);
+        check("", &["1.0.0"], &[]);
+        check("1.x", &["1.0.0", "1.9.9"], &["2.0.0", "0.9.9"]);
+        check("1.2.*", &["1.2.0", "1.2.7"], &["1.3.0"]);
+        check("^1.0 || ^3.0", &["1.4.0", "3.1.0"], &["2.0.0", "4.0.0"]);
+        for bad in [">=", "^abc", "1.x.3", "1.2 || >", "=>1.0"] {
+            assert!(
+                VersionReq::parse(bad).is_err(),
+                "{:?} should not parse",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn prereleases_need_an_explicit_opt_in() {
+        check(">=1.0.0", &["1.1.0"], &["1.1.0-beta", "2.0.0-rc.1"]);
+        check("*", &[], &["1.0.0-alpha"]);
+        check(
+            ">=1.1.0-alpha",
+            &["1.1.0-alpha", "1.1.0-beta", "1.2.0"],
+            &["1.2.0-beta", "1.0.9"],
+        );
+        check(
+            "^1.2.3-beta.1",
+            &["1.2.3-beta.2", "1.2.3", "1.5.0"],
+            &["1.2.3-alpha", "1.2.4-alpha", "2.0.0"],
+        );
+    }
+
+    #[test]
+    fn picks_highest_stable_match() {
+        let candidates = ["1.0.0", "1.2.0", "1.3.0-beta", "2.0.0", "junk"];
+        let pick = |req: &str| max_satisfying(candidates, &VersionReq::parse(req).unwrap());
+        assert_eq!(pick("^1"), Some("1.2.0"));
+        assert_eq!(pick("*"), Some("2.0.0"));
+        assert_eq!(pick("^1.3.0-alpha"), Some("1.3.0-beta"));
+        assert_eq!(pick(">2.0.0"), None);
+    }
+}
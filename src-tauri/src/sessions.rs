@@ -0,0 +1,278 @@
+use crate::environment::LaunchMode;
+use crate::logger::{info, logs_dir, warn};
+use crate::models::GameSession;
+use anyhow::{anyhow, Result};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const SESSIONS_DIR_NAME: &str = "sessions";
+const FILE_PREFIX: &str = "game-";
+const KEEP_SESSIONS: usize = 30;
+const MAX_TOTAL_BYTES: u64 = 200 * 1024 * 1024;
+// A session stops being written past this size so a log loop cannot fill the disk
+const MAX_SESSION_BYTES: u64 = 50 * 1024 * 1024;
+const MAX_READ_BYTES: u64 = 5 * 1024 * 1024;
+const DEFAULT_TAIL_LINES: usize = 200;
+
+pub fn sessions_dir() -> PathBuf {
+    let dir = logs_dir().join(SESSIONS_DIR_NAME);
+    let _ = fs::create_dir_all(&dir);
+    dir
+}
+
+/// What the session header records about the launched process.
+pub struct SessionHeader<'a> {
+    pub mode: LaunchMode,
+    pub java_path: &'a Path,
+    pub work_dir: &'a Path,
+    pub class_path: &'a str,
+    pub command_line: &'a str,
+}
+
+/// Log file of one game or server process; stdout and stderr threads share it.
+pub struct SessionLog {
+    file_name: String,
+    inner: Mutex<(File, u64)>,
+}
+
+impl SessionLog {
+    /// Creates `logs/sessions/game-<mode>-<timestamp>.log`, writes the header and applies
+    /// retention to older sessions, except `running` ones (session files still being written).
+    pub fn create(header: &SessionHeader, running: &[String]) -> Result<SessionLog> {
+        let dir = sessions_dir();
+        let now = chrono::Local::now();
+        let stamp = now.format("%Y%m%d-%H%M%S").to_string();
+        let mut file_name = format!("{}{}-{}.log", FILE_PREFIX, header.mode.as_str(), stamp);
+        // Two launches within the same second (client and server together)
+        let mut n = 1;
+        while dir.join(&file_name).exists() {
+            n += 1;
+            file_name = format!(
+                "{}{}-{}-{}.log",
+                FILE_PREFIX,
+                header.mode.as_str(),
+                stamp,
+                n
+            );
+        }
+        let mut file = OpenOptions::new()
+            .create_new(true)
+            .append(true)
+            .open(dir.join(&file_name))?;
+        let text = format!(
+            "# Avrix {} session\n# Started: {}\n# Java: {}\n# Working dir: {}\n# Classpath: {}\n# Command: {}\n\n",
+            header.mode.as_str(),
+            now.format("%Y-%m-%d %H:%M:%S"),
+            header.java_path.display(),
+            header.work_dir.display(),
+            header.class_path,
+            header.command_line
+        );
+        file.write_all(text.as_bytes())?;
+        prune(&dir, &file_name, running);
+        info("sessions", &format!("Session log: {}", file_name));
+        Ok(SessionLog {
+            file_name,
+            inner: Mutex::new((file, text.len() as u64)),
+        })
+    }
+
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    /// Appends a process output line; stderr lines are prefixed with `[stderr]`.
+    pub fn write_line(&self, line: &str, stderr: bool) {
+        let mut guard = match self.inner.lock() {
+            Ok(g) => g,
+            Err(_) => return,
+        };
+        let (file, written) = &mut *guard;
+        if *written >= MAX_SESSION_BYTES {
+            return;
+        }
+        let text = if stderr {
+            format!("[stderr] {}\n", line)
+        } else {
+            format!("{}\n", line)
+        };
+        let text = if *written + text.len() as u64 >= MAX_SESSION_BYTES {
+            format!(
+                "{}# Session log size limit reached, output truncated\n",
+                text
+            )
+        } else {
+            text
+        };
+        if file.write_all(text.as_bytes()).is_ok() {
+            *written += text.len() as u64;
+        }
+    }
+
+    pub fn write_exit(&self, code: Option<i32>) {
+        if let Ok(mut guard) = self.inner.lock() {
+            let line = match code {
+                Some(c) => format!("\n# Exited with code {} at {}\n", c, now_str()),
+                None => format!("\n# Exited at {}\n", now_str()),
+            };
+            let _ = guard.0.write_all(line.as_bytes());
+        }
+    }
+}
+
+fn now_str() -> String {
+    chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// Crash report stored next to a session log.
+pub fn report_path(log: &Path) -> PathBuf {
+    log.with_extension("crash.json")
+}
+
+fn is_session_file(name: &str) -> bool {
+    name.starts_with(FILE_PREFIX) && name.ends_with(".log")
+}
+
+// Session files, newest first (by modification time, then name).
+fn session_files(dir: &Path) -> Vec<(PathBuf, fs::Metadata)> {
+    let mut out: Vec<(PathBuf, fs::Metadata)> = match fs::read_dir(dir) {
+        Ok(rd) => rd
+            .flatten()
+            .filter(|e| e.file_name().to_str().map(is_session_file).unwrap_or(false))
+            .filter_map(|e| {
+                let meta = e.metadata().ok()?;
+                meta.is_file().then(|| (e.path(), meta))
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    out.sort_by(|(pa, ma), (pb, mb)| {
+        mb.modified()
+            .ok()
+            .cmp(&ma.modified().ok())
+            .then_with(|| pb.cmp(pa))
+    });
+    out
+}
+
+// Keeps the newest KEEP_SESSIONS files within MAX_TOTAL_BYTES; the current session and those
+// of instances still running are never removed.
+fn prune(dir: &Path, current: &str, running: &[String]) {
+    let mut total = 0u64;
+    let mut kept = 0usize;
+    for (path, meta) in session_files(dir) {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let in_use = name == current || running.iter().any(|r| r == name);
+        if in_use || (kept < KEEP_SESSIONS && total + meta.len() <= MAX_TOTAL_BYTES) {
+            kept += 1;
+            total += meta.len();
+            continue;
+        }
+        let _ = fs::remove_file(report_path(&path));
+        if let Err(e) = fs::remove_file(&path) {
+            warn(
+                "sessions",
+                &format!("Cannot remove old session {}: {}", path.display(), e),
+            );
+        }
+    }
+}
+
+pub fn session_path(file_name: &str) -> Result<PathBuf> {
+    if file_name.contains(['/', '\\']) || !is_session_file(file_name) {
+        return Err(anyhow!("Invalid session name: {}", file_name));
+    }
+    let path = sessions_dir().join(file_name);
+    if !path.is_file() {
+        return Err(anyhow!("Session not found: {}", file_name));
+    }
+    Ok(path)
+}
+
+// Last `max` bytes of a file, starting at a line boundary when truncated.
+fn read_tail_bytes(path: &Path, max: u64) -> Result<String> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let start = len.saturating_sub(max);
+    file.seek(SeekFrom::Start(start))?;
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf)?;
+    let mut text = String::from_utf8_lossy(&buf).into_owned();
+    if start > 0 {
+        if let Some(pos) = text.find('\n') {
+            text.drain(..=pos);
+        }
+    }
+    Ok(text)
+}
+
+/// Past and running sessions, newest first.
+#[tauri::command]
+pub fn list_game_sessions() -> Result<Vec<GameSession>, String> {
+    info("sessions", "list_game_sessions invoked");
+    Ok(session_files(&sessions_dir())
+        .into_iter()
+        .filter_map(|(path, meta)| {
+            let file_name = path.file_name()?.to_str()?.to_string();
+            let mode = file_name
+                .trim_start_matches(FILE_PREFIX)
+                .split('-')
+                .next()
+                .unwrap_or("")
+                .to_string();
+            let modified = meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            Some(GameSession {
+                has_crash_report: report_path(&path).is_file(),
+                file_name,
+                mode,
+                size: meta.len(),
+                modified,
+            })
+        })
+        .collect())
+}
+
+/// Content of a session log (only the last few MB for very large sessions).
+#[tauri::command]
+pub fn read_game_session(file_name: String) -> Result<String, String> {
+    info(
+        "sessions",
+        &format!("read_game_session invoked (file_name={})", file_name),
+    );
+    session_path(&file_name)
+        .and_then(|p| read_tail_bytes(&p, MAX_READ_BYTES))
+        .map_err(|e| e.to_string())
+}
+
+/// Last `lines` lines of a session log (200 by default).
+#[tauri::command]
+pub fn tail_game_session(file_name: String, lines: Option<usize>) -> Result<Vec<String>, String> {
+    info(
+        "sessions",
+        &format!(
+            "tail_game_session invoked (file_name={}, lines={:?})",
+            file_name, lines
+        ),
+    );
+    let wanted = lines.unwrap_or(DEFAULT_TAIL_LINES).max(1);
+    let path = session_path(&file_name).map_err(|e| e.to_string())?;
+    let len = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    // Read a growing window from the end until it holds enough lines
+    let mut window = 64 * 1024u64;
+    loop {
+        let text = read_tail_bytes(&path, window).map_err(|e| e.to_string())?;
+        let all: Vec<&str> = text.lines().collect();
+        if all.len() >= wanted || window >= len || window >= MAX_READ_BYTES {
+            let skip = all.len().saturating_sub(wanted);
+            return Ok(all[skip..].iter().map(|s| s.to_string()).collect());
+        }
+        window *= 4;
+    }
+}
@@ -0,0 +1,329 @@
+use crate::logger::{info, warn};
+use crate::models::SteamInfo;
+use anyhow::{anyhow, Result};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Steam app id of Project Zomboid.
+pub const PZ_APP_ID: &str = "108600";
+
+/// A node of Valve's KeyValues text format (`.vdf`, `.acf`).
+#[derive(Debug, Clone)]
+pub enum KeyValue {
+    Value(String),
+    Section(Vec<(String, KeyValue)>),
+}
+
+impl KeyValue {
+    /// Child named `key`, compared case-insensitively like Steam does.
+    pub fn get(&self, key: &str) -> Option<&KeyValue> {
+        match self {
+            KeyValue::Section(entries) => entries
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|(_, v)| v),
+            KeyValue::Value(_) => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            KeyValue::Value(v) => Some(v),
+            KeyValue::Section(_) => None,
+        }
+    }
+
+    pub fn entries(&self) -> &[(String, KeyValue)] {
+        match self {
+            KeyValue::Section(entries) => entries,
+            KeyValue::Value(_) => &[],
+        }
+    }
+}
+
+enum Token {
+    Open,
+    Close,
+    Text(String),
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '{' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            '}' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            '/' => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    for c in chars.by_ref() {
+                        if c == '\n' {
+                            break;
+                        }
+                    }
+                } else {
+                    return Err(anyhow!("unexpected '/'"));
+                }
+            }
+            '"' => {
+                chars.next();
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => s.push('\n'),
+                            Some('t') => s.push('\t'),
+                            Some(other) => s.push(other),
+                            None => return Err(anyhow!("unterminated string")),
+                        },
+                        Some(other) => s.push(other),
+                        None => return Err(anyhow!("unterminated string")),
+                    }
+                }
+                tokens.push(Token::Text(s));
+            }
+            _ => {
+                let mut s = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == '{' || c == '}' || c == '"' {
+                        break;
+                    }
+                    s.push(c);
+                    chars.next();
+                }
+                // Platform conditionals such as `[$WIN32]` do not carry data
+                if !(s.starts_with('[') && s.ends_with(']')) {
+                    tokens.push(Token::Text(s));
+                }
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+fn parse_section(tokens: &mut std::vec::IntoIter<Token>, nested: bool) -> Result<KeyValue> {
+    let mut entries = Vec::new();
+    loop {
+        let key = match tokens.next() {
+            Some(Token::Text(k)) => k,
+            Some(Token::Close) if nested => return Ok(KeyValue::Section(entries)),
+            None if !nested => return Ok(KeyValue::Section(entries)),
+            None => return Err(anyhow!("missing '}}'")),
+            Some(_) => return Err(anyhow!("expected a key")),
+        };
+        match tokens.next() {
+            Some(Token::Text(v)) => entries.push((key, KeyValue::Value(v))),
+            Some(Token::Open) => entries.push((key, parse_section(tokens, true)?)),
+            _ => return Err(anyhow!("missing value for '{}'", key)),
+        }
+    }
+}
+
+/// Parses a KeyValues document into its root section.
+pub fn parse_keyvalues(text: &str) -> Result<KeyValue> {
+    let mut tokens = tokenize(text)?.into_iter();
+    parse_section(&mut tokens, false)
+}
+
+fn read_keyvalues(path: &Path) -> Option<KeyValue> {
+    let text = fs::read_to_string(path).ok()?;
+    match parse_keyvalues(&text) {
+        Ok(kv) => Some(kv),
+        Err(e) => {
+            warn("steam", &format!("Cannot parse {}: {}", path.display(), e));
+            None
+        }
+    }
+}
+
+/// Steam installation folders for the current OS, existing ones only.
+fn steam_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    if cfg!(windows) {
+        for var in ["ProgramFiles(x86)", "ProgramFiles"] {
+            if let Ok(pf) = std::env::var(var) {
+                dirs.push(PathBuf::from(pf).join("Steam"));
+            }
+        }
+        if let Ok(home) = std::env::var("USERPROFILE") {
+            dirs.push(PathBuf::from(home).join("AppData/Local/Steam"));
+        }
+        for drive_letter in 'C'..='Z' {
+            for pf in ["Program Files (x86)", "Program Files"] {
+                dirs.push(PathBuf::from(format!("{}:/{}/Steam", drive_letter, pf)));
+            }
+        }
+    } else if let Some(home) = std::env::var_os("HOME").map(PathBuf::from) {
+        if cfg!(target_os = "macos") {
+            dirs.push(home.join("Library/Application Support/Steam"));
+        } else {
+            dirs.push(home.join(".steam/steam"));
+            dirs.push(home.join(".steam/root"));
+            dirs.push(home.join(".local/share/Steam"));
+            // Flatpak and Snap packages
+            dirs.push(home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam"));
+            dirs.push(home.join("snap/steam/common/.local/share/Steam"));
+        }
+    }
+    dedup_existing(dirs.into_iter().filter(|d| d.join("steamapps").is_dir()))
+}
+
+// The canonical path is only the dedup key: on Windows it is a `\\?\` verbatim path, which
+// Java does not reliably accept on its classpath.
+fn dedup_existing(paths: impl Iterator<Item = PathBuf>) -> Vec<PathBuf> {
+    let mut seen = HashSet::new();
+    paths
+        .filter(|p| p.is_dir())
+        .filter(|p| seen.insert(fs::canonicalize(p).unwrap_or(p.clone())))
+        .collect()
+}
+
+/// Library folders listed in `steamapps/libraryfolders.vdf`, in both the current format
+/// (`"0" { "path" "..." }`) and the older one (`"1" "D:\\SteamLibrary"`).
+fn library_folders(steam_dir: &Path) -> Vec<PathBuf> {
+    let file = steam_dir.join("steamapps").join("libraryfolders.vdf");
+    let Some(root) = read_keyvalues(&file) else {
+        return Vec::new();
+    };
+    let Some((_, folders)) = root.entries().first() else {
+        return Vec::new();
+    };
+    folders
+        .entries()
+        .iter()
+        .filter(|(k, _)| k.chars().all(|c| c.is_ascii_digit()))
+        .filter_map(|(_, v)| match v {
+            KeyValue::Value(path) => Some(PathBuf::from(path)),
+            KeyValue::Section(_) => v.get("path").and_then(|p| p.as_str()).map(PathBuf::from),
+        })
+        .collect()
+}
+
+/// Install of an app as described by its `appmanifest_<id>.acf`.
+#[derive(Debug, Clone)]
+pub struct SteamApp {
+    pub library: PathBuf,
+    pub install_dir: PathBuf,
+    pub build_id: Option<String>,
+}
+
+fn read_app_manifest(library: &Path, app_id: &str) -> Option<SteamApp> {
+    let steamapps = library.join("steamapps");
+    let manifest = read_keyvalues(&steamapps.join(format!("appmanifest_{}.acf", app_id)))?;
+    let state = manifest.get("AppState")?;
+    let install_dir = steamapps
+        .join("common")
+        .join(state.get("installdir")?.as_str()?);
+    if !install_dir.is_dir() {
+        return None;
+    }
+    Some(SteamApp {
+        library: library.to_path_buf(),
+        install_dir,
+        build_id: state
+            .get("buildid")
+            .and_then(|b| b.as_str())
+            .map(|b| b.to_string()),
+    })
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SteamDiscovery {
+    pub steam_dirs: Vec<PathBuf>,
+    pub libraries: Vec<PathBuf>,
+    pub game: Option<SteamApp>,
+    /// Existing `steamapps/workshop/content/108600` folders, the game's library first.
+    pub workshop_roots: Vec<PathBuf>,
+}
+
+/// Finds the Steam installs, their libraries, Project Zomboid and its workshop content.
+/// `STEAM_LIBRARY` adds an extra library folder.
+pub fn discover() -> SteamDiscovery {
+    let steam_dirs = steam_dirs();
+    let mut libraries: Vec<PathBuf> = Vec::new();
+    for dir in steam_dirs.iter() {
+        libraries.push(dir.clone());
+        libraries.extend(library_folders(dir));
+    }
+    if let Ok(extra) = std::env::var("STEAM_LIBRARY") {
+        let p = PathBuf::from(extra);
+        libraries.push(if p.ends_with("steamapps") {
+            p.parent().map(|p| p.to_path_buf()).unwrap_or(p)
+        } else {
+            p
+        });
+    }
+    let libraries = dedup_existing(libraries.into_iter());
+    let game = libraries
+        .iter()
+        .find_map(|lib| read_app_manifest(lib, PZ_APP_ID));
+    let mut ordered: Vec<&PathBuf> = Vec::new();
+    if let Some(g) = &game {
+        ordered.push(&g.library);
+    }
+    ordered.extend(libraries.iter());
+    let workshop_roots = dedup_existing(ordered.into_iter().map(|lib| {
+        lib.join("steamapps")
+            .join("workshop")
+            .join("content")
+            .join(PZ_APP_ID)
+    }));
+    SteamDiscovery {
+        steam_dirs,
+        libraries,
+        game,
+        workshop_roots,
+    }
+}
+
+/// Project Zomboid install folder found through the Steam libraries.
+pub fn game_dir() -> Option<PathBuf> {
+    discover().game.map(|g| g.install_dir)
+}
+
+#[tauri::command]
+pub fn detect_steam() -> Result<SteamInfo, String> {
+    info("steam", "detect_steam invoked");
+    let found = discover();
+    let to_strings = |paths: &[PathBuf]| -> Vec<String> {
+        paths
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect()
+    };
+    info(
+        "steam",
+        &format!(
+            "Steam: {} install(s), {} librar(y/ies), game={}",
+            found.steam_dirs.len(),
+            found.libraries.len(),
+            found
+                .game
+                .as_ref()
+                .map(|g| g.install_dir.display().to_string())
+                .unwrap_or_else(|| "not found".into())
+        ),
+    );
+    Ok(SteamInfo {
+        steam_dirs: to_strings(&found.steam_dirs),
+        libraries: to_strings(&found.libraries),
+        game_dir: found
+            .game
+            .as_ref()
+            .map(|g| g.install_dir.to_string_lossy().to_string()),
+        build_id: found.game.as_ref().and_then(|g| g.build_id.clone()),
+        workshop_roots: to_strings(&found.workshop_roots),
+    })
+}
@@ -1,8 +1,19 @@
 use crate::logger::{info, warn};
 use anyhow::{anyhow, Context, Result};
 use serde_json::json;
-use tauri::AppHandle;
-use tauri_plugin_store::StoreExt;
+use std::sync::Arc;
+use tauri::{AppHandle, Wry};
+use tauri_plugin_store::{Store, StoreExt};
+
+/// Opens the settings store that lives next to the launcher executable.
+pub fn settings_store(app: &AppHandle) -> Result<Arc<Store<Wry>>> {
+    let exe_dir = std::env::current_exe()
+        .ok()
+        .and_then(|p| p.parent().map(|p| p.to_path_buf()))
+        .context("Cannot determine executable directory")?;
+    app.store(exe_dir.join("avrix-settings.json"))
+        .map_err(|e| anyhow!(e.to_string()))
+}
 
 pub fn setup_stores(app: &AppHandle) -> Result<()> {
     let exe_dir = std::env::current_exe()
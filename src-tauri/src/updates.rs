@@ -0,0 +1,358 @@
+use crate::download::{self, DownloadOptions, DownloadTask, Downloaded};
+use crate::logger::{emit_app_log, error, info, warn, Level};
+use crate::models::{
+    DownloadProgress, LockEntry, LockSource, PluginUpdateInfo, PluginUpdatesResult,
+};
+use crate::util::{resolve_plugins_dir, sha256_file, DISABLED_DIR_NAME};
+use crate::{backup, install, lockfile, metadata};
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{Emitter, Window};
+
+const MAX_SIZE: u64 = 25 * 1024 * 1024;
+
+// Lock entries installed from a URL or the workshop, plus legacy workshop-map.json
+// mappings that predate plugins.lock.
+fn tracked_entries(plugins_dir: &Path) -> Vec<LockEntry> {
+    let mut out: Vec<LockEntry> = lockfile::load(plugins_dir)
+        .plugins
+        .into_iter()
+        .filter(|e| !matches!(e.source, LockSource::Local { .. }))
+        .collect();
+    let map_path = plugins_dir.join("workshop-map.json");
+    if let Ok(s) = fs::read_to_string(&map_path) {
+        if let Ok(serde_json::Value::Object(map)) = serde_json::from_str::<serde_json::Value>(&s) {
+            for (file_name, v) in map.iter() {
+                let ws_id = match v.as_str() {
+                    Some(id) => id.to_string(),
+                    None => continue,
+                };
+                if out.iter().any(|e| &e.file_name == file_name) {
+                    continue;
+                }
+                let installed = match installed_path(plugins_dir, file_name) {
+                    Some(p) => p,
+                    None => continue,
+                };
+                let meta = metadata::extract_metadata_from_jar(&installed).ok();
+                out.push(LockEntry {
+                    file_name: file_name.clone(),
+                    id: meta.as_ref().and_then(|m| m.id.clone()),
+                    version: meta.as_ref().and_then(|m| m.version.clone()),
+                    source: LockSource::Workshop {
+                        workshop_id: ws_id,
+                        path: String::new(),
+                    },
+                    sha256: sha256_file(&installed).unwrap_or_default(),
+                    installed_at: 0,
+                    source_sha256: None,
+                    etag: None,
+                    last_modified: None,
+                });
+            }
+        }
+    }
+    out.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+    out
+}
+
+fn installed_path(plugins_dir: &Path, file_name: &str) -> Option<PathBuf> {
+    [
+        plugins_dir.join(file_name),
+        plugins_dir.join(DISABLED_DIR_NAME).join(file_name),
+    ]
+    .into_iter()
+    .find(|p| p.is_file())
+}
+
+/// Finds the jar of a workshop item: the recorded path if it still exists, otherwise a jar in
+/// the item folder with the same file name or the same plugin id.
+pub fn locate_workshop_jar(
+    workshop_id: &str,
+    path_hint: &str,
+    file_name: &str,
+    plugin_id: Option<&str>,
+) -> Option<PathBuf> {
+    let hint = PathBuf::from(path_hint);
+    if !path_hint.is_empty() && hint.is_file() {
+        return Some(hint);
+    }
+    let mut by_id: Option<PathBuf> = None;
+    for root in crate::workshop::workshop_roots() {
+        let item_dir = root.join(workshop_id);
+        if !item_dir.is_dir() {
+            continue;
+        }
+        let mut stack = vec![item_dir.clone()];
+        while let Some(dir) = stack.pop() {
+            let rd = match fs::read_dir(&dir) {
+                Ok(rd) => rd,
+                Err(_) => continue,
+            };
+            for entry in rd.flatten() {
+                let p = entry.path();
+                if p.is_dir() {
+                    if p.components().count() - item_dir.components().count() <= 5 {
+                        stack.push(p);
+                    }
+                    continue;
+                }
+                let name = p.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                if !name.to_lowercase().ends_with(".jar") {
+                    continue;
+                }
+                if name == file_name {
+                    return Some(p);
+                }
+                if by_id.is_none() && plugin_id.is_some() {
+                    if let Ok(m) = metadata::extract_metadata_from_jar(&p) {
+                        if m.id.as_deref() == plugin_id {
+                            by_id = Some(p);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    by_id
+}
+
+// Streams a plugin body to `dest`; the result carries its sha256, ETag and Last-Modified.
+fn fetch_plugin(
+    url: &str,
+    dest: &Path,
+    on_progress: &dyn Fn(&DownloadProgress),
+) -> Result<Downloaded> {
+    let task = DownloadTask::start();
+    download::download(
+        &DownloadOptions::new(url, dest).max_size(MAX_SIZE),
+        &task,
+        on_progress,
+    )
+}
+
+fn check_entry(plugins_dir: &Path, entry: &LockEntry) -> Result<PluginUpdateInfo> {
+    let installed = installed_path(plugins_dir, &entry.file_name)
+        .ok_or_else(|| anyhow!("{}: not installed", entry.file_name))?;
+    let installed_version = metadata::extract_metadata_from_jar(&installed)
+        .ok()
+        .and_then(|m| m.version)
+        .or(entry.version.clone());
+    let known_sha = entry.source_sha256.as_deref().unwrap_or(&entry.sha256);
+    let mut info = PluginUpdateInfo {
+        file_name: entry.file_name.clone(),
+        id: entry.id.clone(),
+        installed_version: installed_version.clone(),
+        available_version: None,
+        source: entry.source.clone(),
+        outdated: false,
+        reason: String::new(),
+    };
+    match &entry.source {
+        LockSource::Url { url } => {
+            let head = minreq::head(url)
+                .with_header("User-Agent", "AvrixLauncher/1.0")
+                .send()
+                .ok()
+                .filter(|r| r.status_code >= 200 && r.status_code < 300);
+            let etag = head.as_ref().and_then(|r| r.headers.get("etag").cloned());
+            let last_modified = head
+                .as_ref()
+                .and_then(|r| r.headers.get("last-modified").cloned());
+            if let (Some(old), Some(new)) = (&entry.etag, &etag) {
+                info.outdated = old != new;
+                info.reason = if info.outdated {
+                    "ETag changed".into()
+                } else {
+                    "ETag unchanged".into()
+                };
+            } else if let (Some(old), Some(new)) = (&entry.last_modified, &last_modified) {
+                info.outdated = old != new;
+                info.reason = if info.outdated {
+                    format!("Last-Modified changed ({})", new)
+                } else {
+                    "Last-Modified unchanged".into()
+                };
+            } else {
+                // No usable validators: fetch the content and compare hashes
+                let scratch = download::Scratch::new(url);
+                let remote = fetch_plugin(url, scratch.path(), &|_| {})?;
+                info.outdated = !remote.sha256.eq_ignore_ascii_case(known_sha);
+                info.reason = if info.outdated {
+                    "Remote content changed (sha256)".into()
+                } else {
+                    "Remote content unchanged (sha256)".into()
+                };
+                info.available_version = metadata::extract_metadata_from_jar(&remote.path)
+                    .ok()
+                    .and_then(|m| m.version);
+            }
+        }
+        LockSource::Workshop { workshop_id, path } => {
+            let jar = locate_workshop_jar(workshop_id, path, &entry.file_name, entry.id.as_deref())
+                .ok_or_else(|| {
+                    anyhow!(
+                        "{}: workshop item {} not found (unsubscribed?)",
+                        entry.file_name,
+                        workshop_id
+                    )
+                })?;
+            info.available_version = metadata::extract_metadata_from_jar(&jar)
+                .ok()
+                .and_then(|m| m.version);
+            match &entry.source_sha256 {
+                Some(src_sha) => {
+                    let ws_sha = sha256_file(&jar)?;
+                    info.outdated = !ws_sha.eq_ignore_ascii_case(src_sha);
+                    info.reason = if info.outdated {
+                        "Workshop jar changed (sha256)".into()
+                    } else {
+                        "Workshop jar unchanged (sha256)".into()
+                    };
+                }
+                None => {
+                    // Legacy mapping without a recorded source hash: compare versions
+                    let newer = match (&info.available_version, &installed_version) {
+                        (Some(a), Some(i)) => {
+                            crate::semver::compare_str(a, i) == std::cmp::Ordering::Greater
+                        }
+                        _ => false,
+                    };
+                    info.outdated = newer;
+                    info.reason = if newer {
+                        "Newer version in workshop".into()
+                    } else {
+                        "Same version in workshop".into()
+                    };
+                }
+            }
+        }
+        LockSource::Local { .. } => {
+            info.reason = "Local install (no update source)".into();
+        }
+    }
+    Ok(info)
+}
+
+pub fn check_plugin_updates(window: &Window) -> Result<PluginUpdatesResult> {
+    let emit = |lvl: Level, m: &str| {
+        match lvl {
+            Level::Info => info("updates", m),
+            Level::Warn => warn("updates", m),
+            Level::Error => error("updates", m),
+        }
+        let _ = window.emit("plugin-update-log", m.to_string());
+        let _ = emit_app_log(window, lvl, "updates", m);
+    };
+    let plugins_dir = resolve_plugins_dir();
+    let entries = tracked_entries(&plugins_dir);
+    emit(
+        Level::Info,
+        &format!("Checking {} plugin(s) with an update source", entries.len()),
+    );
+    let mut updates = Vec::new();
+    let mut errors = Vec::new();
+    for entry in entries.iter() {
+        match check_entry(&plugins_dir, entry) {
+            Ok(u) => {
+                emit(
+                    if u.outdated { Level::Warn } else { Level::Info },
+                    &format!(" - {}: {}", u.file_name, u.reason),
+                );
+                updates.push(u);
+            }
+            Err(e) => {
+                emit(Level::Error, &format!(" - {}", e));
+                errors.push(e.to_string());
+            }
+        }
+    }
+    emit(
+        Level::Info,
+        &format!(
+            "{} update(s) available",
+            updates.iter().filter(|u| u.outdated).count()
+        ),
+    );
+    Ok(PluginUpdatesResult { updates, errors })
+}
+
+/// Staged update file, deleted on every early return; once installed it has been moved away.
+struct StagedFile(PathBuf);
+
+impl Drop for StagedFile {
+    fn drop(&mut self) {
+        if self.0.exists() {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+}
+
+/// Fetches the new jar from the recorded source next to the installed one, then swaps it in
+/// with a single rename so the plugin is never half-written.
+pub fn update_plugin(file_name: String, window: &Window) -> Result<String> {
+    let emit = |m: &str| {
+        info("updates", m);
+        let _ = window.emit("plugin-update-log", m.to_string());
+        let _ = emit_app_log(window, Level::Info, "updates", m);
+    };
+    let plugins_dir = resolve_plugins_dir();
+    let entry = tracked_entries(&plugins_dir)
+        .into_iter()
+        .find(|e| e.file_name == file_name)
+        .ok_or_else(|| anyhow!("No update source recorded for {}", file_name))?;
+    let installed =
+        installed_path(&plugins_dir, &file_name).ok_or_else(|| anyhow!("Plugin not found"))?;
+    let dir = installed
+        .parent()
+        .map(|p| p.to_path_buf())
+        .unwrap_or(plugins_dir.clone());
+    let staged = StagedFile(dir.join(format!("{}.update", file_name)));
+    let tmp = &staged.0;
+    let (source_sha256, etag, last_modified) = match &entry.source {
+        LockSource::Url { url } => {
+            emit(&format!("Downloading {}", url));
+            let fetched = fetch_plugin(url, tmp, &download::window_progress(window))?;
+            (fetched.sha256, fetched.etag, fetched.last_modified)
+        }
+        LockSource::Workshop { workshop_id, path } => {
+            let jar = locate_workshop_jar(workshop_id, path, &file_name, entry.id.as_deref())
+                .ok_or_else(|| anyhow!("Workshop item {} not found", workshop_id))?;
+            emit(&format!("Copying {}", jar.to_string_lossy()));
+            fs::copy(&jar, tmp)?;
+            (sha256_file(tmp)?, None, None)
+        }
+        LockSource::Local { .. } => {
+            return Err(anyhow!("{} was installed from a local file", file_name));
+        }
+    };
+    let new_meta = metadata::extract_metadata_from_jar(tmp)
+        .map_err(|e| anyhow!("Invalid update (metadata.yml): {}", e))?;
+    if let (Some(old_id), Some(new_id)) = (&entry.id, &new_meta.id) {
+        if old_id != new_id {
+            return Err(anyhow!(
+                "Update declares id {} instead of {}",
+                new_id,
+                old_id
+            ));
+        }
+    }
+    if let LockSource::Workshop { workshop_id, .. } = &entry.source {
+        install::rewrite_metadata_workshop_id(tmp, workshop_id)?;
+    }
+    let installed = backup::install_atomic(&plugins_dir, tmp, &dir, &file_name, &emit)?;
+    let mut new_entry = lockfile::entry_for(&installed, entry.source.clone())?;
+    new_entry.source_sha256 = Some(source_sha256);
+    new_entry.etag = etag;
+    new_entry.last_modified = last_modified;
+    lockfile::record(&plugins_dir, new_entry)?;
+    let msg = format!(
+        "Updated {} ({} -> {})",
+        file_name,
+        entry.version.as_deref().unwrap_or("?"),
+        new_meta.version.as_deref().unwrap_or("?")
+    );
+    emit(&msg);
+    Ok(msg)
+}
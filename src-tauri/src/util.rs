@@ -72,8 +72,9 @@ fn data_url_payload_len_bytes(url: &str) -> Option<usize> {
 
 pub fn find_game_root(start: &PathBuf) -> Option<PathBuf> {
     let mut cur = start.clone();
+    let platform = crate::platform::GamePlatform::current();
     for _ in 0..6 {
-        if contains_game_dirs(&cur) {
+        if crate::platform::has_game_dirs(&crate::platform::content_dir(&cur, platform)) {
             return Some(cur);
         }
         if !cur.pop() {
@@ -83,20 +84,10 @@ pub fn find_game_root(start: &PathBuf) -> Option<PathBuf> {
     None
 }
 
-fn contains_game_dirs(base: &PathBuf) -> bool {
-    ["zombie", "se", "fmod", "javax"]
-        .iter()
-        .all(|d| base.join(d).is_dir())
-}
-
 /// Light-weight validity check for a Project Zomboid installation directory.
 pub fn is_valid_game_root_dir(dir: &Path) -> bool {
-    if !dir.is_dir() { return false; }
-    // Accept if we find common subdirs OR the startup binaries.
-    let has_dirs = ["zombie", "se", "fmod", "javax"].iter().all(|d| dir.join(d).is_dir());
-    let has_bins64 = dir.join("ProjectZomboid64.exe").is_file() && dir.join("ProjectZomboid64.bat").is_file();
-    let has_bins32 = dir.join("ProjectZomboid32.exe").is_file() && dir.join("ProjectZomboid32.bat").is_file();
-    has_dirs || has_bins64 || has_bins32
+    // Accept if we find common subdirs OR the startup binaries of the current platform.
+    crate::platform::is_valid_game_root(dir, crate::platform::GamePlatform::current())
 }
 
 /// Returns the effective game root, preferring a user override stored in the settings store.
@@ -118,20 +109,44 @@ pub fn get_effective_game_root(app: &tauri::AppHandle) -> PathBuf {
             }
         }
     }
-    // Fallback to autodetect from current dir
+    // Fallback to autodetect from current dir, then the Steam libraries
     let base = std::env::current_dir().unwrap_or_else(|_| std::env::temp_dir());
-    find_game_root(&base).unwrap_or(base)
+    detect_game_root(&base).unwrap_or(base)
+}
+
+/// Autodetected game root: walking up from `base`, else the install listed by Steam.
+pub fn detect_game_root(base: &PathBuf) -> Option<PathBuf> {
+    find_game_root(base).or_else(|| crate::steam::game_dir().filter(|p| is_valid_game_root_dir(p)))
 }
 
 pub fn parse_name_version_simple(file_name: &str) -> Option<(String, String)> {
     let base = file_name.trim_end_matches(".jar");
-    let re = regex::Regex::new(r"(?i)^(.+?)[-_]v?(\d+\.\d+(?:\.\d+)?)(?:[-_].*)?$").ok()?;
-    if let Some(caps) = re.captures(base) {
-        let disp = caps.get(1)?.as_str().to_string();
-        let ver = caps.get(2)?.as_str().to_string();
-        return Some((disp, ver));
-    }
-    None
+    // Prefer a full semantic version (with pre-release/build), e.g. foo-1.2.0-beta.1+build5
+    let full = regex::Regex::new(
+        r"(?i)^(.+?)[-_]v?(\d+\.\d+(?:\.\d+)?-(?:alpha|beta|rc|pre|snapshot)[0-9A-Za-z.]*(?:\+[0-9A-Za-z.]+)?)$",
+    )
+    .ok()?;
+    let simple = regex::Regex::new(r"(?i)^(.+?)[-_]v?(\d+\.\d+(?:\.\d+)?)(?:[-_].*)?$").ok()?;
+    let caps = full.captures(base).or_else(|| simple.captures(base))?;
+    let disp = caps.get(1)?.as_str().to_string();
+    let ver = caps.get(2)?.as_str();
+    crate::semver::Version::parse(ver)?;
+    Some((disp, ver.to_string()))
+}
+
+// Sub-folder of the plugins directory holding disabled jars. Avrix only loads jars placed
+// directly in `plugins/`, so anything in here stays off the game's plugin set.
+pub const DISABLED_DIR_NAME: &str = ".disabled";
+/// Jars set aside for the running game because they target the other environment.
+pub const SKIPPED_DIR_NAME: &str = ".skipped";
+
+/// Hex-encoded SHA-256 of a file, streamed so large jars are not loaded in memory.
+pub fn sha256_file(path: &Path) -> Result<String> {
+    use sha2::{Digest, Sha256};
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(hex::encode(hasher.finalize()))
 }
 
 pub fn resolve_plugins_dir() -> PathBuf {
@@ -151,6 +166,16 @@ pub fn resolve_plugins_dir() -> PathBuf {
 }
 
 pub fn scan_plugins(window: &Window) -> Result<crate::models::PluginsResult> {
+    let base = std::env::current_dir()?;
+    scan_plugins_with_core(window, &base.join("Avrix-Core.jar"))
+}
+
+/// Same as `scan_plugins`, but reads core metadata and internal plugins from the given core jar
+/// (e.g. the one picked for launch from the selected version).
+pub fn scan_plugins_with_core(
+    window: &Window,
+    core_jar: &Path,
+) -> Result<crate::models::PluginsResult> {
     use crate::{
         metadata,
         models::{PluginEntry, PluginsResult},
@@ -181,7 +206,7 @@ pub fn scan_plugins(window: &Window) -> Result<crate::models::PluginsResult> {
         plugins_dir.to_string_lossy()
     ));
 
-    let core_jar = base.join("Avrix-Core.jar");
+    let core_jar = core_jar.to_path_buf();
     emit(&format!(
         "Trying to load core: {}",
         core_jar.to_string_lossy()
@@ -314,6 +339,10 @@ pub fn scan_plugins(window: &Window) -> Result<crate::models::PluginsResult> {
                     workshop_id: None,
                     internal: Some(false),
                     parent_id: None,
+                    enabled: Some(true),
+                    sha256: sha256_file(&core_jar).ok(),
+                    lock_status: None,
+                    duplicate: None,
                 });
             } else {
                 warn(
@@ -481,6 +510,10 @@ pub fn scan_plugins(window: &Window) -> Result<crate::models::PluginsResult> {
                                                     .parent
                                                     .clone()
                                                     .or(Some("avrix-core".into())),
+                                                enabled: Some(true),
+                                                sha256: None,
+                                                lock_status: None,
+                                                duplicate: None,
                                             });
                                             emit(&format!(
                                                 "   -> Internal plugin registered: {}",
@@ -515,15 +548,36 @@ pub fn scan_plugins(window: &Window) -> Result<crate::models::PluginsResult> {
                 }
             }
         }
+        let lock = crate::lockfile::load(&plugins_dir);
+        let disabled_dir = plugins_dir.join(DISABLED_DIR_NAME);
+        let mut jar_files: Vec<(PathBuf, bool)> = Vec::new();
         for entry in fs::read_dir(&plugins_dir)? {
-            let e = entry?;
-            let p = e.path();
+            jar_files.push((entry?.path(), true));
+        }
+        if disabled_dir.is_dir() {
+            emit("Scanning disabled plugins...");
+            for entry in fs::read_dir(&disabled_dir)? {
+                jar_files.push((entry?.path(), false));
+            }
+        }
+        // Set aside only while the game runs: still enabled
+        for p in crate::environment::skipped_jars(&plugins_dir) {
+            jar_files.push((p, true));
+        }
+        for (p, enabled) in jar_files {
+            if !p.is_file() {
+                continue;
+            }
             if let Some(name) = p.file_name().and_then(|n| n.to_str()) {
                 if name.to_lowercase().ends_with(".jar")
                     && !name.to_lowercase().contains("launcher")
                     && !name.starts_with("Avrix-Core")
                 {
-                    emit(&format!(" - Detected plugin: {}", name));
+                    if enabled {
+                        emit(&format!(" - Detected plugin: {}", name));
+                    } else {
+                        emit(&format!(" - Detected plugin: {} (disabled)", name));
+                    }
                     let meta = fs::metadata(&p)?;
                     let modified = meta
                         .modified()
@@ -535,8 +589,31 @@ pub fn scan_plugins(window: &Window) -> Result<crate::models::PluginsResult> {
                         name: name.to_string(),
                         size_kb: (meta.len() / 1024).max(1),
                         modified,
+                        enabled: Some(enabled),
                         ..Default::default()
                     };
+                    match sha256_file(&p) {
+                        Ok(sha) => {
+                            let status = crate::lockfile::status_of(&lock, name, &sha);
+                            match status {
+                                crate::models::LockStatus::Modified => warn(
+                                    "scan",
+                                    &format!(
+                                        "{} does not match plugins.lock (sha256 {})",
+                                        name, sha
+                                    ),
+                                ),
+                                crate::models::LockStatus::Untracked => warn(
+                                    "scan",
+                                    &format!("{} is not tracked in plugins.lock", name),
+                                ),
+                                crate::models::LockStatus::Locked => {}
+                            }
+                            entry.sha256 = Some(sha);
+                            entry.lock_status = Some(status);
+                        }
+                        Err(e) => warn("scan", &format!("Cannot hash {}: {}", name, e)),
+                    }
                     // If path under a workshop folder, attempt to infer workshop item ID
                     if let Some(ws) = infer_workshop_id(&p) {
                         entry.workshop_id = Some(ws);
@@ -678,9 +755,15 @@ pub fn scan_plugins(window: &Window) -> Result<crate::models::PluginsResult> {
     emit(&format!("Total detected plugins: {}", out.len()));
 
     out.sort_by(|a, b| a.name.cmp(&b.name));
+    let warnings = crate::duplicates::detect(&mut out);
+    for w in warnings.iter() {
+        warn("scan", &w.message);
+        let _ = window.emit("plugin-scan-log", w.message.clone());
+    }
     Ok(PluginsResult {
         plugins: out,
         dir: plugins_dir.to_string_lossy().to_string(),
+        warnings,
     })
 }
 
@@ -714,7 +797,11 @@ pub fn delete_plugin(name: String) -> Result<String> {
     if name.contains('/') || name.contains('\\') {
         anyhow::bail!("Invalid name");
     }
-    let target = plugins_dir.join(&name);
+    let mut target = plugins_dir.join(&name);
+    if !target.exists() {
+        // Disabled plugins can be deleted as well
+        target = plugins_dir.join(DISABLED_DIR_NAME).join(&name);
+    }
     if !target.exists() {
         anyhow::bail!("Plugin not found");
     }
@@ -728,8 +815,102 @@ pub fn delete_plugin(name: String) -> Result<String> {
         anyhow::bail!("File is not a .jar");
     }
     std::fs::remove_file(&target)?;
+    if let Err(e) = crate::lockfile::remove(&plugins_dir, &name) {
+        warn("plugins", &format!("Cannot update plugins.lock: {}", e));
+    }
     Ok(format!(
         "Deleted: {}",
         target.file_name().and_then(|n| n.to_str()).unwrap_or("?")
     ))
 }
+
+/// Moves plugin jars between `plugins/` and `plugins/.disabled`.
+/// `target` is either a jar file name or a plugin `id` (every jar declaring that id is moved).
+pub fn set_plugin_enabled(target: String, enabled: bool) -> Result<String> {
+    if target.contains('/') || target.contains('\\') || target.trim().is_empty() {
+        anyhow::bail!("Invalid name");
+    }
+    let plugins_dir = resolve_plugins_dir();
+    let disabled_dir = plugins_dir.join(DISABLED_DIR_NAME);
+    let (from, to) = if enabled {
+        (&disabled_dir, &plugins_dir)
+    } else {
+        (&plugins_dir, &disabled_dir)
+    };
+    let sources = find_plugin_jars(from, &target)?;
+    if sources.is_empty() {
+        if !find_plugin_jars(to, &target)?.is_empty() {
+            return Ok(format!(
+                "Already {}: {}",
+                if enabled { "enabled" } else { "disabled" },
+                target
+            ));
+        }
+        anyhow::bail!("Plugin not found");
+    }
+    fs::create_dir_all(to)?;
+    let mut moved: Vec<String> = Vec::new();
+    for src in sources {
+        let file_name = src
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default()
+            .to_string();
+        let dest = to.join(&file_name);
+        if dest.exists() {
+            anyhow::bail!(
+                "A plugin named {} already exists in {}",
+                file_name,
+                to.to_string_lossy()
+            );
+        }
+        fs::rename(&src, &dest)?;
+        info(
+            "plugins",
+            &format!(
+                "{} {} -> {}",
+                if enabled { "Enabled" } else { "Disabled" },
+                src.display(),
+                dest.display()
+            ),
+        );
+        moved.push(file_name);
+    }
+    Ok(format!(
+        "{}: {}",
+        if enabled { "Enabled" } else { "Disabled" },
+        moved.join(", ")
+    ))
+}
+
+// Jars in `dir` whose file name equals `target`, or whose metadata.yml declares `id: target`.
+pub fn find_plugin_jars(dir: &Path, target: &str) -> Result<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let is_plugin_jar = |p: &Path| {
+        p.is_file()
+            && p.file_name()
+                .and_then(|n| n.to_str())
+                .map(|n| n.to_lowercase().ends_with(".jar") && !n.starts_with("Avrix-Core"))
+                .unwrap_or(false)
+    };
+    let direct = dir.join(target);
+    if is_plugin_jar(&direct) {
+        return Ok(vec![direct]);
+    }
+    let mut out = Vec::new();
+    for entry in fs::read_dir(dir)?.flatten() {
+        let p = entry.path();
+        if !is_plugin_jar(&p) {
+            continue;
+        }
+        if let Ok(meta) = crate::metadata::extract_metadata_from_jar(&p) {
+            if meta.id.as_deref() == Some(target) {
+                out.push(p);
+            }
+        }
+    }
+    out.sort();
+    Ok(out)
+}
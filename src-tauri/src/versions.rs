@@ -1,7 +1,12 @@
-use crate::logger::info;
-use crate::models::{AvailableVersion, VersionEntry, VersionsResult};
+use crate::download::{self, DownloadOptions, DownloadTask};
+use crate::logger::{info, warn};
+use crate::models::{AvailableVersion, ReleaseChannel, VersionEntry, VersionsResult};
+use crate::store::settings_store;
 use crate::util::find_game_root;
 use anyhow::{anyhow, Context, Result};
+use base64::Engine as _;
+use minisign_verify::{PublicKey, Signature};
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Emitter, Manager, Window};
@@ -9,6 +14,7 @@ use tauri_plugin_store::StoreExt;
 use zip::ZipArchive;
 
 const STORE_SELECTED_KEY: &str = "selectedVersionId";
+const STORE_CHANNEL_KEY: &str = "releaseChannel";
 
 fn versions_root() -> Result<PathBuf> {
     // Prefer resolving from the launcher executable directory
@@ -85,7 +91,7 @@ fn dir_modified_secs(dir: &Path) -> u64 {
         .unwrap_or(0)
 }
 
-fn load_selected_id(app: &AppHandle) -> Option<String> {
+pub fn load_selected_id(app: &AppHandle) -> Option<String> {
     let exe_dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
     let path = exe_dir.join("avrix-settings.json");
     let store = app.store(&path).ok()?;
@@ -94,7 +100,7 @@ fn load_selected_id(app: &AppHandle) -> Option<String> {
         .and_then(|v| v.as_str().map(|s| s.to_string()))
 }
 
-fn save_selected_id(app: &AppHandle, id: Option<String>) -> Result<()> {
+pub fn save_selected_id(app: &AppHandle, id: Option<String>) -> Result<()> {
     let exe_dir = std::env::current_exe()
         .ok()
         .and_then(|p| p.parent().map(|p| p.to_path_buf()))
@@ -146,8 +152,12 @@ pub fn list_versions(window: Window) -> Result<VersionsResult, String> {
             });
         }
     }
-    // Sort by id asc
-    entries.sort_by(|a, b| a.id.cmp(&b.id));
+    // Newest first (semantic order on detected version, falling back to the folder id)
+    entries.sort_by(|a, b| {
+        let va = a.version.as_deref().unwrap_or(&a.id);
+        let vb = b.version.as_deref().unwrap_or(&b.id);
+        crate::semver::compare_str(vb, va).then_with(|| a.id.cmp(&b.id))
+    });
     let app = window.app_handle();
     let selected_id = load_selected_id(&app);
     let res = VersionsResult {
@@ -159,9 +169,8 @@ pub fn list_versions(window: Window) -> Result<VersionsResult, String> {
     Ok(res).map_err(|e: String| e)
 }
 
-fn unzip_to(bytes: &[u8], dest: &Path) -> Result<()> {
-    let reader = std::io::Cursor::new(bytes);
-    let mut zip = ZipArchive::new(reader)?;
+fn unzip_file(path: &Path, dest: &Path) -> Result<()> {
+    let mut zip = ZipArchive::new(fs::File::open(path)?)?;
     for i in 0..zip.len() {
         let mut file = zip.by_index(i)?;
         let out_path = dest.join(file.mangled_name());
@@ -239,29 +248,190 @@ struct ManifestVersion {
     core_url: String,
     #[serde(rename = "jreUrl")]
     jre_url: Option<String>,
+    #[serde(rename = "coreSha256")]
+    core_sha256: Option<String>,
+    #[serde(rename = "coreSize")]
+    core_size: Option<u64>,
+    #[serde(rename = "jreSha256")]
+    jre_sha256: Option<String>,
+    #[serde(rename = "jreSize")]
+    jre_size: Option<u64>,
     #[serde(rename = "publishedAt")]
     published_at: Option<String>,
+    channel: Option<String>,
 }
+
+impl ManifestVersion {
+    /// Declared channel; untagged entries are stable unless their version is a pre-release.
+    /// Unknown channel names give `None` so the entry is offered nowhere.
+    fn channel(&self) -> Option<ReleaseChannel> {
+        match self.channel.as_deref() {
+            Some(name) => ReleaseChannel::parse(name),
+            None => match crate::semver::Version::parse(&self.version) {
+                Some(v) if v.is_prerelease() => Some(ReleaseChannel::Beta),
+                _ => Some(ReleaseChannel::Stable),
+            },
+        }
+    }
+
+    fn offered_on(&self, channel: ReleaseChannel) -> bool {
+        self.channel().map(|c| c <= channel).unwrap_or(false)
+    }
+}
+
+/// `latest` is either the latest stable version or one version per channel name.
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum ManifestLatest {
+    Stable(String),
+    PerChannel(HashMap<String, String>),
+}
+
 #[derive(serde::Deserialize)]
 struct Manifest {
-    #[allow(dead_code)]
-    latest: Option<String>,
+    latest: Option<ManifestLatest>,
     versions: Vec<ManifestVersion>,
 }
 
-fn http_get(url: &str) -> Result<Vec<u8>> {
-    let mut req = minreq::get(url);
-    req = req.with_header("User-Agent", "AvrixLauncher/1.0");
-    if let Ok(token) = std::env::var("GITHUB_TOKEN") {
-        if !token.is_empty() {
-            req = req.with_header("Authorization", format!("Bearer {}", token));
+impl Manifest {
+    fn latest_pointer(&self, channel: ReleaseChannel) -> Option<&str> {
+        match self.latest.as_ref()? {
+            ManifestLatest::Stable(v) if channel == ReleaseChannel::Stable => Some(v),
+            ManifestLatest::Stable(_) => None,
+            ManifestLatest::PerChannel(map) => map
+                .iter()
+                .find(|(k, _)| ReleaseChannel::parse(k) == Some(channel))
+                .map(|(_, v)| v.as_str()),
         }
     }
-    let resp = req.send()?;
-    if resp.status_code < 200 || resp.status_code >= 300 {
-        return Err(anyhow!(format!("HTTP {}", resp.status_code)));
+
+    /// What to install on `channel`: the highest of the heads of the channels it offers, a
+    /// head being the entry `latest` points to or else the channel's highest version. A stable
+    /// release newer than the last beta wins on the beta channel.
+    fn latest_for(&self, channel: ReleaseChannel) -> Option<&ManifestVersion> {
+        ReleaseChannel::ALL
+            .into_iter()
+            .filter(|c| *c <= channel)
+            .filter_map(|c| {
+                // The pointer may name any entry the channel offers
+                self.latest_pointer(c)
+                    .map(normalize_tag_to_version)
+                    .and_then(|wanted| {
+                        self.versions.iter().find(|v| {
+                            v.offered_on(c) && normalize_tag_to_version(&v.version) == wanted
+                        })
+                    })
+                    .or_else(|| {
+                        self.versions
+                            .iter()
+                            .filter(|v| v.channel() == Some(c))
+                            .max_by(|a, b| crate::semver::compare_str(&a.version, &b.version))
+                    })
+            })
+            .max_by(|a, b| crate::semver::compare_str(&a.version, &b.version))
+    }
+}
+
+fn to_available(v: &ManifestVersion) -> AvailableVersion {
+    AvailableVersion {
+        tag: v.tag.clone().unwrap_or_else(|| format!("v{}", v.version)),
+        version: normalize_tag_to_version(&v.version),
+        core_url: v.core_url.clone(),
+        jre_url: v.jre_url.clone(),
+        core_size: v.core_size,
+        jre_size: v.jre_size,
+        channel: v.channel().unwrap_or_default(),
+        published_at: v.published_at.clone(),
+    }
+}
+
+pub fn load_release_channel(app: &AppHandle) -> ReleaseChannel {
+    settings_store(app)
+        .ok()
+        .and_then(|s| s.get(STORE_CHANNEL_KEY))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+fn save_release_channel(app: &AppHandle, channel: ReleaseChannel) -> Result<()> {
+    let store = settings_store(app)?;
+    store.set(STORE_CHANNEL_KEY, serde_json::to_value(channel)?);
+    store.save().map_err(|e| anyhow!(e.to_string()))
+}
+
+/// Whether `url` points at GitHub itself, the only host `GITHUB_TOKEN` may be sent to.
+fn is_github_url(url: &str) -> bool {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| h.to_ascii_lowercase()))
+        .map(|h| h == "github.com" || h == "api.github.com")
+        .unwrap_or(false)
+}
+
+pub fn http_get(url: &str) -> Result<Vec<u8>> {
+    let auth = std::env::var("GITHUB_TOKEN")
+        .ok()
+        .filter(|t| !t.is_empty() && is_github_url(url))
+        .map(|t| format!("Bearer {}", t));
+    match auth {
+        Some(auth) => download::fetch_bytes(url, &[("Authorization", &auth)]),
+        None => download::fetch_bytes(url, &[]),
+    }
+}
+
+/// Downloads the core jar (and the zipped JRE when the release has one) of a manifest
+/// version into `dest`, reporting `download-progress` to the window. Each file is checked
+/// against the manifest's SHA-256 and size before it replaces anything in `dest`.
+fn download_release_files(window: &Window, mver: &ManifestVersion, dest: &Path) -> Result<()> {
+    let core_sha = mver.core_sha256.as_deref().ok_or_else(|| {
+        anyhow!(
+            "Le manifest ne fournit pas coreSha256 pour {}",
+            mver.version
+        )
+    })?;
+    let progress = download::window_progress(window);
+    // One task for the whole release, so a cancel stops both files
+    let task = DownloadTask::start();
+    download::download(
+        &DownloadOptions::new(&mver.core_url, &dest.join("Avrix-Core.jar"))
+            .expect(Some(core_sha), mver.core_size),
+        &task,
+        &progress,
+    )
+    .context("Avrix-Core.jar")?;
+    let Some(jre) = mver.jre_url.as_deref() else {
+        return Ok(());
+    };
+    let jre_sha = mver
+        .jre_sha256
+        .as_deref()
+        .ok_or_else(|| anyhow!("Le manifest ne fournit pas jreSha256 pour {}", mver.version))?;
+    let _ = window.emit("versions-log", "Téléchargement jre.zip…".to_string());
+    let jre_zip = download::temp_target(jre);
+    download::download(
+        &DownloadOptions::new(jre, &jre_zip).expect(Some(jre_sha), mver.jre_size),
+        &task,
+        &progress,
+    )
+    .context("jre.zip")?;
+    let final_jre = dest.join("jre");
+    if final_jre.exists() {
+        let _ = fs::remove_dir_all(&final_jre);
+    }
+    let tmp = dest.join(format!("_jre_tmp_{}", rand_suffix()));
+    fs::create_dir_all(&tmp).ok();
+    let unzipped = unzip_file(&jre_zip, &tmp);
+    let _ = fs::remove_file(&jre_zip);
+    unzipped?;
+    // If zip contains jre/ at root (as produced by CI), move that into dest/jre, else if contents is already jre, handle gracefully
+    let tmp_jre = tmp.join("jre");
+    if tmp_jre.exists() {
+        let _ = fs::rename(&tmp_jre, &final_jre);
+    } else {
+        let _ = fs::rename(&tmp, &final_jre);
     }
-    Ok(resp.as_bytes().to_vec())
+    let _ = fs::remove_dir_all(&tmp);
+    Ok(())
 }
 
 fn normalize_tag_to_version(tag: &str) -> String {
@@ -282,23 +452,92 @@ fn get_manifest_url() -> String {
     DEFAULT_MANIFEST_URL.to_string()
 }
 
+// Key of the release pipeline, the one the updater pins in tauri.conf.json
+// (override with AVRIX_MANIFEST_PUBKEY for a self-hosted manifest; every use is logged)
+const MANIFEST_PUBKEY: &str = "dW50cnVzdGVkIGNvbW1lbnQ6IG1pbmlzaWduIHB1YmxpYyBrZXk6IEQ0MDZEQzhEREQwQkEwNUMKUldSY29BdmRqZHdHMUVaR1ZFZWY3ZWJ6NnZ3T3Q5SU1GRGRXSVVOLzAxVGVtMTR4c1lyYmhGeEQK";
+
+fn get_manifest_pubkey() -> String {
+    match std::env::var("AVRIX_MANIFEST_PUBKEY") {
+        Ok(s) if !s.trim().is_empty() && s.trim() != MANIFEST_PUBKEY => {
+            warn(
+                "versions",
+                "AVRIX_MANIFEST_PUBKEY is set: verifying the manifest with a non-default key",
+            );
+            s.trim().to_string()
+        }
+        _ => MANIFEST_PUBKEY.to_string(),
+    }
+}
+
+/// Minisign key or signature text. Accepts the file content as is, or base64-encoded once
+/// more as produced by `tauri signer` and stored in tauri.conf.json.
+fn minisign_text(s: &str) -> String {
+    base64::engine::general_purpose::STANDARD
+        .decode(s.trim())
+        .ok()
+        .and_then(|b| String::from_utf8(b).ok())
+        .filter(|t| t.contains("untrusted comment:"))
+        .unwrap_or_else(|| s.trim().to_string())
+}
+
+/// Checks the manifest bytes against their minisign signature (`<manifest url>.sig`).
+fn verify_manifest(bytes: &[u8], signature: &str, pubkey: &str) -> Result<()> {
+    let key_text = minisign_text(pubkey);
+    let key = if key_text.contains("untrusted comment:") {
+        PublicKey::decode(&key_text)
+    } else {
+        PublicKey::from_base64(&key_text)
+    }
+    .map_err(|e| anyhow!("Clé publique du manifest invalide: {}", e))?;
+    let sig = Signature::decode(&minisign_text(signature))
+        .map_err(|e| anyhow!("Signature du manifest illisible: {}", e))?;
+    key.verify(bytes, &sig, false)
+        .map_err(|e| anyhow!("Signature du manifest invalide: {}", e))
+}
+
 fn try_fetch_manifest() -> Result<Manifest> {
     let url = get_manifest_url();
     let bytes = http_get(&url)?;
+    let sig_url = format!("{}.sig", url);
+    let sig = http_get(&sig_url).context("Signature du manifest introuvable")?;
+    verify_manifest(
+        &bytes,
+        &String::from_utf8_lossy(&sig),
+        &get_manifest_pubkey(),
+    )?;
     let mani: Manifest = serde_json::from_slice(&bytes)?;
     Ok(mani)
 }
 
+/// Looks up an exact version (`1.2` matches `1.2.0`) or, failing that, the highest stable
+/// entry matching a range expression (`^1.2`, `>=1.0 <2.0`, `1.x`).
 fn find_in_manifest<'a>(m: &'a Manifest, version: &str) -> Option<&'a ManifestVersion> {
+    use crate::semver::{Version, VersionReq};
     let needle = normalize_tag_to_version(version);
+    if let Some(wanted) = Version::parse(&needle) {
+        return m.versions.iter().find(|v| {
+            Version::parse(&v.version)
+                .map(|x| x == wanted)
+                .unwrap_or(false)
+        });
+    }
+    if let Ok(req) = VersionReq::parse(&needle) {
+        let best =
+            crate::semver::max_satisfying(m.versions.iter().map(|v| v.version.as_str()), &req)?;
+        return m.versions.iter().find(|v| v.version == best);
+    }
     m.versions
         .iter()
         .find(|v| normalize_tag_to_version(&v.version) == needle)
 }
 
 #[tauri::command]
-pub fn list_available_versions(window: Window) -> Result<Vec<AvailableVersion>, String> {
+pub fn list_available_versions(
+    channel: Option<ReleaseChannel>,
+    window: Window,
+) -> Result<Vec<AvailableVersion>, String> {
     info("versions", "list_available_versions invoked");
+    let channel = channel.unwrap_or_else(|| load_release_channel(window.app_handle()));
     // Manifest-only listing
     let mani = match try_fetch_manifest() {
         Ok(m) => m,
@@ -308,26 +547,69 @@ pub fn list_available_versions(window: Window) -> Result<Vec<AvailableVersion>,
             return Err(msg);
         }
     };
-    let out: Vec<AvailableVersion> = mani
+    let mut versions: Vec<&ManifestVersion> = mani
         .versions
-        .into_iter()
-        .map(|v| AvailableVersion {
-            tag: v.tag.unwrap_or_else(|| format!("v{}", v.version)),
-            version: normalize_tag_to_version(&v.version),
-            core_url: v.core_url,
-            jre_url: v.jre_url,
-            published_at: v.published_at,
-        })
+        .iter()
+        .filter(|v| v.offered_on(channel))
         .collect();
+    // Newest first, so the first entry is the latest release
+    versions.sort_by(|a, b| crate::semver::compare_str(&b.version, &a.version));
+    let out: Vec<AvailableVersion> = versions.into_iter().map(to_available).collect();
     let _ = window.emit(
         "versions-log",
-        format!("{} versions depuis le manifest", out.len()),
+        format!(
+            "{} versions depuis le manifest (canal {})",
+            out.len(),
+            channel.as_str()
+        ),
     );
     Ok(out)
 }
 
+/// The version to install on `channel` (the saved channel when omitted), `None` when the
+/// manifest has nothing for it.
+#[tauri::command]
+pub fn get_latest_version(
+    channel: Option<ReleaseChannel>,
+    window: Window,
+) -> Result<Option<AvailableVersion>, String> {
+    let channel = channel.unwrap_or_else(|| load_release_channel(window.app_handle()));
+    info(
+        "versions",
+        &format!("get_latest_version invoked (channel={})", channel.as_str()),
+    );
+    let mani =
+        try_fetch_manifest().map_err(|e| format!("Manifest introuvable ou inaccessible: {}", e))?;
+    Ok(mani.latest_for(channel).map(to_available))
+}
+
+#[tauri::command]
+pub fn get_release_channel(window: Window) -> ReleaseChannel {
+    load_release_channel(window.app_handle())
+}
+
+#[tauri::command]
+pub fn set_release_channel(channel: ReleaseChannel, window: Window) -> Result<(), String> {
+    info(
+        "versions",
+        &format!("set_release_channel invoked (channel={})", channel.as_str()),
+    );
+    save_release_channel(window.app_handle(), channel).map_err(|e| e.to_string())
+}
+
+// Runs on a blocking thread so progress events reach the UI while the files download and
+// `cancel_download` is not queued behind the download
 #[tauri::command]
-pub fn install_version_from_release(version: String, window: Window) -> Result<String, String> {
+pub async fn install_version_from_release(
+    version: String,
+    window: Window,
+) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || install_release(version, window))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn install_release(version: String, window: Window) -> Result<String, String> {
     info(
         "versions",
         &format!("install_version_from_release invoked (version={})", version),
@@ -338,8 +620,6 @@ pub fn install_version_from_release(version: String, window: Window) -> Result<S
     let mver = find_in_manifest(&mani, &needle_ver)
         .ok_or_else(|| "Version introuvable dans le manifest".to_string())?;
     let ver = normalize_tag_to_version(&mver.version);
-    let core_url = mver.core_url.clone();
-    let jre_url = mver.jre_url.clone(); // JRE peut être optionnel
 
     let root = versions_root().map_err(|e| e.to_string())?;
     fs::create_dir_all(&root).map_err(|e| e.to_string())?;
@@ -350,30 +630,15 @@ pub fn install_version_from_release(version: String, window: Window) -> Result<S
     }
     fs::create_dir_all(&dest).map_err(|e| e.to_string())?;
 
-    // Download core jar
+    // Download core jar, then jre.zip if present
     let _ = window.emit(
         "versions-log",
         format!("Téléchargement Avrix-Core-{}.jar…", ver),
     );
-    let core_bytes = http_get(&core_url).map_err(|e| e.to_string())?;
-    fs::write(dest.join("Avrix-Core.jar"), &core_bytes).map_err(|e| e.to_string())?;
-
-    // Download and unzip jre.zip if present
-    if let Some(jre) = jre_url {
-        let _ = window.emit("versions-log", "Téléchargement jre.zip…".to_string());
-        let jre_bytes = http_get(&jre).map_err(|e| e.to_string())?;
-        let tmp = dest.join(format!("_jre_tmp_{}", rand_suffix()));
-        fs::create_dir_all(&tmp).ok();
-        unzip_to(&jre_bytes, &tmp).map_err(|e| e.to_string())?;
-        // If zip contains jre/ at root (as produced by CI), move that into dest/jre, else if contents is already jre, handle gracefully
-        let tmp_jre = tmp.join("jre");
-        let final_jre = dest.join("jre");
-        if tmp_jre.exists() {
-            let _ = fs::rename(&tmp_jre, &final_jre);
-        } else {
-            let _ = fs::rename(&tmp, &final_jre);
-        }
-        let _ = fs::remove_dir_all(&tmp);
+    if let Err(e) = download_release_files(&window, mver, &dest) {
+        // Do not leave a half-installed version behind
+        let _ = fs::remove_dir_all(&dest);
+        return Err(format!("{:#}", e));
     }
 
     let msg = format!("Version {} installée dans {}", id, dest.to_string_lossy());
@@ -382,7 +647,16 @@ pub fn install_version_from_release(version: String, window: Window) -> Result<S
 }
 
 #[tauri::command]
-pub fn repair_version_from_release(version: String, window: Window) -> Result<String, String> {
+pub async fn repair_version_from_release(
+    version: String,
+    window: Window,
+) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || repair_release(version, window))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn repair_release(version: String, window: Window) -> Result<String, String> {
     info(
         "versions",
         &format!("repair_version_from_release invoked (version={})", version),
@@ -391,38 +665,19 @@ pub fn repair_version_from_release(version: String, window: Window) -> Result<St
     let mani = try_fetch_manifest().map_err(|e| e.to_string())?;
     let mver = find_in_manifest(&mani, &needle_ver)
         .ok_or_else(|| "Version introuvable dans le manifest".to_string())?;
-    let core_url = mver.core_url.clone();
-    let target_jre = mver.jre_url.clone();
+    // The folder follows the manifest entry, not the input (`1.2` or `^1.2` may match `1.2.0`)
+    let ver = normalize_tag_to_version(&mver.version);
     let root = versions_root().map_err(|e| e.to_string())?;
     fs::create_dir_all(&root).map_err(|e| e.to_string())?;
-    let id = normalize_version_id(&needle_ver);
+    let id = normalize_version_id(&ver);
     let dest = root.join(&id);
     fs::create_dir_all(&dest).map_err(|e| e.to_string())?;
 
     let _ = window.emit(
         "versions-log",
-        format!("Réparation Avrix-Core-{}.jar…", needle_ver),
+        format!("Réparation Avrix-Core-{}.jar…", ver),
     );
-    let core_bytes = http_get(&core_url).map_err(|e| e.to_string())?;
-    fs::write(dest.join("Avrix-Core.jar"), &core_bytes).map_err(|e| e.to_string())?;
-    if let Some(jre) = target_jre {
-        let _ = window.emit("versions-log", "Téléchargement jre.zip…".to_string());
-        let jre_bytes = http_get(&jre).map_err(|e| e.to_string())?;
-        let final_jre = dest.join("jre");
-        if final_jre.exists() {
-            let _ = fs::remove_dir_all(&final_jre);
-        }
-        let tmp = dest.join(format!("_jre_tmp_{}", rand_suffix()));
-        fs::create_dir_all(&tmp).ok();
-        unzip_to(&jre_bytes, &tmp).map_err(|e| e.to_string())?;
-        let tmp_jre = tmp.join("jre");
-        if tmp_jre.exists() {
-            let _ = fs::rename(&tmp_jre, &final_jre);
-        } else {
-            let _ = fs::rename(&tmp, &final_jre);
-        }
-        let _ = fs::remove_dir_all(&tmp);
-    }
+    download_release_files(&window, mver, &dest).map_err(|e| format!("{:#}", e))?;
     let msg = format!("Version {} réparée dans {}", id, dest.to_string_lossy());
     let _ = window.emit("versions-log", msg.clone());
     Ok(msg)
@@ -469,13 +724,12 @@ pub fn install_version_local(path: String, window: Window) -> Result<String, Str
         .unwrap_or("")
         .to_ascii_lowercase();
     if ext == "zip" {
-        let data = fs::read(&src).map_err(|e| e.to_string())?;
         // Extract to temp, detect version, move to root/v<ver>
         let mut tmp = std::env::temp_dir();
         let rand = format!("avrix-version-{}", rand_suffix());
         tmp.push(rand);
         fs::create_dir_all(&tmp).map_err(|e| e.to_string())?;
-        unzip_to(&data, &tmp).map_err(|e| e.to_string())?;
+        unzip_file(&src, &tmp).map_err(|e| e.to_string())?;
         let ver = detect_version_from_dir(&tmp)
             .ok_or_else(|| "Impossible de détecter la version dans l'archive".to_string())?;
         let id = normalize_version_id(ver);
@@ -521,31 +775,36 @@ pub fn install_version_local(path: String, window: Window) -> Result<String, Str
 }
 
 #[tauri::command]
-pub fn install_version_from_url(url: String, window: Window) -> Result<String, String> {
+pub async fn install_version_from_url(url: String, window: Window) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || install_from_url(url, window))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn install_from_url(url: String, window: Window) -> Result<String, String> {
     info(
         "versions",
         &format!("install_version_from_url invoked (url={})", url),
     );
     const MAX_SIZE: u64 = 200 * 1024 * 1024; // 200 MiB
-    if let Ok(head) = minreq::head(&url).send() {
-        if let Some(len) = head
-            .headers
-            .get("content-length")
-            .and_then(|v| v.parse::<u64>().ok())
-        {
-            if len > MAX_SIZE {
-                return Err("Archive trop volumineuse".into());
-            }
-        }
-    }
-    let resp = minreq::get(&url).send().map_err(|e| e.to_string())?;
-    if resp.status_code < 200 || resp.status_code >= 300 {
-        return Err(format!("HTTP {}", resp.status_code));
-    }
-    let bytes = resp.as_bytes();
-    if (bytes.len() as u64) > MAX_SIZE {
-        return Err("Archive dépasse la taille maximale".into());
-    }
+    let archive = download::temp_target(&url);
+    let task = DownloadTask::start();
+    download::download(
+        &DownloadOptions::new(&url, &archive).max_size(MAX_SIZE),
+        &task,
+        &download::window_progress(&window),
+    )
+    .map_err(|e| e.to_string())?;
+    let result = install_downloaded_version(&url, &archive, &window);
+    let _ = fs::remove_file(&archive);
+    result
+}
+
+fn install_downloaded_version(
+    url: &str,
+    archive: &Path,
+    window: &Window,
+) -> Result<String, String> {
     // Heuristic: if ends with .zip -> unzip, if .jar -> create folder, else try unzip first then fallback
     let root = versions_root().map_err(|e| e.to_string())?;
     fs::create_dir_all(&root).map_err(|e| e.to_string())?;
@@ -556,7 +815,7 @@ pub fn install_version_from_url(url: String, window: Window) -> Result<String, S
         let rand = format!("avrix-version-{}", rand_suffix());
         tmp.push(rand);
         fs::create_dir_all(&tmp).map_err(|e| e.to_string())?;
-        unzip_to(bytes, &tmp).map_err(|e| e.to_string())?;
+        unzip_file(archive, &tmp).map_err(|e| e.to_string())?;
         let ver = detect_version_from_dir(&tmp)
             .ok_or_else(|| "Impossible de détecter la version dans l'archive".to_string())?;
         let id = normalize_version_id(ver);
@@ -582,20 +841,16 @@ pub fn install_version_from_url(url: String, window: Window) -> Result<String, S
         let _ = window.emit("versions-log", msg.clone());
         return Ok(msg);
     }
-    // Treat otherwise as jar: write temp file, read metadata, then create version dir
-    let mut tmp_file = std::env::temp_dir();
-    tmp_file.push(format!("_avrix_core_{}.jar", rand_suffix()));
-    fs::write(&tmp_file, bytes).map_err(|e| e.to_string())?;
-    let ver = detect_version_from_jar(&tmp_file)
+    // Treat otherwise as jar: read metadata, then create version dir
+    let ver = detect_version_from_jar(archive)
         .ok_or_else(|| "Impossible de détecter la version du .jar".to_string())?;
     let id = normalize_version_id(ver);
     let dest = root.join(&id);
     if dest.exists() {
-        fs::remove_file(&tmp_file).ok();
         return Err(format!("La version {} est déjà installée", id));
     }
     fs::create_dir_all(&dest).map_err(|e| e.to_string())?;
-    fs::rename(&tmp_file, dest.join("Avrix-Core.jar")).map_err(|e| e.to_string())?;
+    fs::copy(archive, dest.join("Avrix-Core.jar")).map_err(|e| e.to_string())?;
     let msg = format!("Version {} installée: {}", id, dest.to_string_lossy());
     let _ = window.emit("versions-log", msg.clone());
     Ok(msg)
@@ -653,3 +908,100 @@ fn rand_suffix() -> String {
         .as_nanos();
     format!("{:x}", nanos)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Throwaway test key; the signature covers MANIFEST exactly
+    const TEST_PUBKEY: &str = "dW50cnVzdGVkIGNvbW1lbnQ6IG1pbmlzaWduIHB1YmxpYyBrZXk6IDA4MDcwNjA1MDQwMzAyMDEKUldRQkFnTUVCUVlIQ09wS2JHUGluRklLdnZWUWV4TXV4Zm1WUjNhdXZyNTdra0llNm1rVVJ0SXMK";
+    const MANIFEST: &str = r#"{"latest":"1.0.0","versions":[{"version":"1.0.0","coreUrl":"https://example.com/Avrix-Core.jar","coreSha256":"00","coreSize":1}]}"#;
+    const SIGNATURE: &str = "untrusted comment: signature from minisign secret key
+RUQBAgMEBQYHCM/b3L1gGdkAias5elVeFqGed0W5Kbeg9uRpsjOo7yxZUpj13EhyjJ/Mk8GZtm5DUQ6l0EIk2eFoYW6CW8zIMAA=
+trusted comment: timestamp:1760000000\tfile:manifest.json
+NLGx9SJpbcPuaGWZlXlG/VG9u+udvbqGbYPph4ILAzxXnv3UWS5hUuFVuqWBnb+uBEsX9zYARAutDrkXJVkoCQ==
+";
+
+    #[test]
+    fn signed_manifest_is_accepted() {
+        verify_manifest(MANIFEST.as_bytes(), SIGNATURE, TEST_PUBKEY).unwrap();
+        // Same signature, base64-encoded like `tauri signer` output
+        let encoded = base64::engine::general_purpose::STANDARD.encode(SIGNATURE);
+        verify_manifest(MANIFEST.as_bytes(), &encoded, TEST_PUBKEY).unwrap();
+        let mani: Manifest = serde_json::from_str(MANIFEST).unwrap();
+        assert_eq!(mani.versions[0].core_sha256.as_deref(), Some("00"));
+        assert_eq!(mani.versions[0].core_size, Some(1));
+    }
+
+    #[test]
+    fn tampered_or_foreign_manifest_is_rejected() {
+        let tampered = MANIFEST.replace("example.com", "example.org");
+        assert!(verify_manifest(tampered.as_bytes(), SIGNATURE, TEST_PUBKEY).is_err());
+        assert!(verify_manifest(MANIFEST.as_bytes(), SIGNATURE, MANIFEST_PUBKEY).is_err());
+        assert!(verify_manifest(MANIFEST.as_bytes(), "not a signature", TEST_PUBKEY).is_err());
+    }
+
+    #[test]
+    fn latest_version_per_channel() {
+        let entry = |version: &str, channel: Option<&str>| {
+            format!(
+                r#"{{"version":"{}","coreUrl":"u","channel":{}}}"#,
+                version,
+                channel
+                    .map(|c| format!("\"{}\"", c))
+                    .unwrap_or_else(|| "null".into())
+            )
+        };
+        let versions = [
+            entry("1.0.0", None),
+            entry("1.1.0", Some("stable")),
+            entry("1.2.0-beta.1", None),
+            entry("1.2.0-nightly.5", Some("nightly")),
+            entry("9.0.0", Some("alpha")),
+        ]
+        .join(",");
+        let parse = |latest: &str| -> Manifest {
+            serde_json::from_str(&format!(
+                r#"{{"latest":{},"versions":[{}]}}"#,
+                latest, versions
+            ))
+            .unwrap()
+        };
+        let latest = |m: &Manifest, c: ReleaseChannel| m.latest_for(c).map(|v| v.version.clone());
+
+        let mani = parse("null");
+        assert_eq!(
+            latest(&mani, ReleaseChannel::Stable).as_deref(),
+            Some("1.1.0")
+        );
+        assert_eq!(
+            latest(&mani, ReleaseChannel::Beta).as_deref(),
+            Some("1.2.0-beta.1")
+        );
+        assert_eq!(
+            latest(&mani, ReleaseChannel::Nightly).as_deref(),
+            Some("1.2.0-nightly.5")
+        );
+        let offered = |c| mani.versions.iter().filter(|v| v.offered_on(c)).count();
+        assert_eq!(offered(ReleaseChannel::Stable), 2);
+        assert_eq!(offered(ReleaseChannel::Nightly), 4);
+
+        // A plain `latest` pins stable only
+        let mani = parse(r#""1.0.0""#);
+        assert_eq!(
+            latest(&mani, ReleaseChannel::Stable).as_deref(),
+            Some("1.0.0")
+        );
+        assert_eq!(
+            latest(&mani, ReleaseChannel::Beta).as_deref(),
+            Some("1.2.0-beta.1")
+        );
+
+        // A stable release newer than the pinned beta wins on the beta channel
+        let mani = parse(r#"{"stable":"v1.1.0","beta":"1.0.0"}"#);
+        assert_eq!(
+            latest(&mani, ReleaseChannel::Beta).as_deref(),
+            Some("1.1.0")
+        );
+    }
+}
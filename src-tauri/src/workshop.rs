@@ -1,7 +1,12 @@
 use crate::logger::{emit_app_log, info, warn};
-use crate::{metadata, models::WorkshopScanResult, util::find_game_root};
+use crate::models::{WorkshopItem, WorkshopModInfo, WorkshopScanResult};
+use crate::{install, lockfile, metadata, steam, util, util::find_game_root};
 use anyhow::Result;
-use std::{collections::HashSet, fs, path::PathBuf};
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
 use tauri::Emitter;
 
 pub fn scan_workshop(window: tauri::Window) -> Result<WorkshopScanResult> {
@@ -10,58 +15,7 @@ pub fn scan_workshop(window: tauri::Window) -> Result<WorkshopScanResult> {
         let _ = window.emit("workshop-scan-log", m.to_string());
         let _ = emit_app_log(&window, crate::logger::Level::Info, "workshop", m);
     };
-    let mut roots_raw: Vec<PathBuf> = Vec::new();
-
-    if let Ok(override_path) = std::env::var("PZ_WORKSHOP_ROOT") {
-        roots_raw.push(PathBuf::from(override_path));
-    }
-    if let Ok(override_path) = std::env::var("AVRIX_WORKSHOP_ROOT") {
-        roots_raw.push(PathBuf::from(override_path));
-    }
-    if let Ok(base) = std::env::current_dir() {
-        if let Some(game_root) = find_game_root(&base) {
-            if let Some(steamapps) = game_root.parent().and_then(|p| p.parent()) {
-                let candidate = steamapps.join("workshop").join("content/108600");
-                roots_raw.push(candidate);
-            }
-        }
-    }
-    if let Ok(home) = std::env::var("USERPROFILE") {
-        roots_raw.push(
-            PathBuf::from(home).join("AppData/Local/Steam/steamapps/workshop/content/108600"),
-        );
-    }
-    if let Ok(steam) = std::env::var("STEAM_LIBRARY") {
-        let p = PathBuf::from(&steam);
-        let candidate = if p.ends_with("steamapps") {
-            p.join("workshop/content/108600")
-        } else {
-            p.join("steamapps/workshop/content/108600")
-        };
-        roots_raw.push(candidate);
-    }
-    #[cfg(target_os = "windows")]
-    {
-        for drive_letter in 'C'..='Z' {
-            let candidate = PathBuf::from(format!(
-                "{}:/Program Files/Steam/steamapps/workshop/content/108600",
-                drive_letter
-            ));
-            if candidate.exists() {
-                roots_raw.push(candidate);
-            }
-        }
-    }
-    let mut roots: Vec<PathBuf> = Vec::new();
-    for r in roots_raw.into_iter() {
-        if let Ok(canon) = r.canonicalize() {
-            roots.push(canon);
-        } else {
-            roots.push(r);
-        }
-    }
-    let mut seen_root = HashSet::new();
-    roots.retain(|p| seen_root.insert(p.to_string_lossy().to_string()));
+    let roots = workshop_roots();
 
     let roots_list = roots
         .iter()
@@ -69,7 +23,7 @@ pub fn scan_workshop(window: tauri::Window) -> Result<WorkshopScanResult> {
         .collect::<Vec<_>>()
         .join(" | ");
     if roots.is_empty() {
-        let msg = "Workshop scan started but no roots detected (check environment variables or Steam installation)";
+        let msg = "Workshop scan started but no roots detected (check environment variables or Steam libraries)";
         warn("workshop", msg);
         let _ = emit_app_log(&window, crate::logger::Level::Warn, "workshop", msg);
         emit("Workshop scan: no roots");
@@ -127,5 +81,237 @@ pub fn scan_workshop(window: tauri::Window) -> Result<WorkshopScanResult> {
     } else {
         emit(&format!("Scan finished. {} valid plugin(s)", found.len()));
     }
-    Ok(WorkshopScanResult { found })
+    let items = describe_items(&found);
+    if items.len() < found.len() {
+        emit(&format!(
+            "{} distinct plugin(s), {} duplicate cop(y/ies)",
+            items.len(),
+            found.len() - items.len()
+        ));
+    }
+    Ok(WorkshopScanResult { found, items })
+}
+
+/// A jar already in the plugins folder (enabled, disabled or set aside).
+struct InstalledPlugin {
+    file_name: String,
+    id: Option<String>,
+    version: Option<String>,
+    /// Current hash, plus the source hash recorded in the lock when metadata was rewritten
+    hashes: Vec<String>,
+}
+
+fn installed_plugins() -> Vec<InstalledPlugin> {
+    let plugins_dir = util::resolve_plugins_dir();
+    let lock = lockfile::load(&plugins_dir);
+    let mut out = Vec::new();
+    let mut jars: Vec<PathBuf> = Vec::new();
+    for dir in [
+        plugins_dir.clone(),
+        plugins_dir.join(util::DISABLED_DIR_NAME),
+    ] {
+        if let Ok(rd) = fs::read_dir(&dir) {
+            jars.extend(rd.flatten().map(|e| e.path()));
+        }
+    }
+    jars.extend(crate::environment::skipped_jars(&plugins_dir));
+    for p in jars {
+        let Some(file_name) = p.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        if !p.is_file() || !file_name.to_lowercase().ends_with(".jar") {
+            continue;
+        }
+        let meta = metadata::extract_metadata_from_jar(&p).ok();
+        let mut hashes: Vec<String> = util::sha256_file(&p).ok().into_iter().collect();
+        if let Some(source) = lock
+            .plugins
+            .iter()
+            .find(|e| e.file_name == file_name)
+            .and_then(|e| e.source_sha256.clone())
+        {
+            hashes.push(source);
+        }
+        out.push(InstalledPlugin {
+            file_name: file_name.to_string(),
+            id: meta.as_ref().and_then(|m| m.id.clone()),
+            version: meta.as_ref().and_then(|m| m.version.clone()),
+            hashes,
+        });
+    }
+    out
+}
+
+/// `content/108600/<id>` folder holding `jar`.
+fn item_dir_of(jar: &Path) -> Option<PathBuf> {
+    jar.ancestors()
+        .find(|a| {
+            let parent = a.parent();
+            a.file_name()
+                .and_then(|n| n.to_str())
+                .map(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+                .unwrap_or(false)
+                && parent.and_then(|p| p.file_name()) == Some(std::ffi::OsStr::new("108600"))
+                && parent
+                    .and_then(|p| p.parent())
+                    .and_then(|p| p.file_name())
+                    .and_then(|n| n.to_str())
+                    .map(|n| n.eq_ignore_ascii_case("content"))
+                    .unwrap_or(false)
+        })
+        .map(|a| a.to_path_buf())
+}
+
+fn parse_mod_info(path: &Path) -> Option<WorkshopModInfo> {
+    let text = fs::read_to_string(path).ok()?;
+    let mut info = WorkshopModInfo::default();
+    for line in text.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        if value.is_empty() {
+            continue;
+        }
+        let slot = match key.trim().to_lowercase().as_str() {
+            "name" => &mut info.name,
+            "id" => &mut info.id,
+            "description" => &mut info.description,
+            "modversion" => &mut info.version,
+            "url" => &mut info.url,
+            "poster" => &mut info.poster,
+            _ => continue,
+        };
+        if slot.is_none() {
+            *slot = Some(value.to_string());
+        }
+    }
+    // The poster is relative to the mod folder
+    if let (Some(poster), Some(dir)) = (info.poster.clone(), path.parent()) {
+        let p = dir.join(poster);
+        info.poster = p.is_file().then(|| p.to_string_lossy().to_string());
+    }
+    Some(info)
+}
+
+/// `mod.info` of the mod containing `jar`: the closest one above the jar inside the item,
+/// else the first one under `mods/`.
+fn find_mod_info(jar: &Path, item_dir: &Path) -> Option<PathBuf> {
+    for dir in jar.ancestors().skip(1) {
+        let candidate = dir.join("mod.info");
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+        if dir == item_dir {
+            break;
+        }
+    }
+    let mut mods: Vec<PathBuf> = fs::read_dir(item_dir.join("mods"))
+        .ok()?
+        .flatten()
+        .map(|e| e.path().join("mod.info"))
+        .filter(|p| p.is_file())
+        .collect();
+    mods.sort();
+    mods.into_iter().next()
+}
+
+fn modified_secs(path: &Path) -> Option<u64> {
+    fs::metadata(path)
+        .ok()?
+        .modified()
+        .ok()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_secs())
+}
+
+/// Workshop item id, metadata, `mod.info` and install state of each distinct jar.
+fn describe_items(found: &[String]) -> Vec<WorkshopItem> {
+    let installed = installed_plugins();
+    let mut items: Vec<WorkshopItem> = Vec::new();
+    for path in found {
+        let jar = PathBuf::from(path);
+        let sha256 = util::sha256_file(&jar).ok();
+        if let Some(sha) = &sha256 {
+            if let Some(first) = items
+                .iter_mut()
+                .find(|i| i.sha256.as_deref() == Some(sha.as_str()))
+            {
+                first.duplicates.push(path.clone());
+                continue;
+            }
+        }
+        let meta = metadata::extract_metadata_from_jar(&jar).ok();
+        let id = meta.as_ref().and_then(|m| m.id.clone());
+        let item_dir = item_dir_of(&jar);
+        let mod_info = item_dir
+            .as_ref()
+            .and_then(|d| find_mod_info(&jar, d))
+            .and_then(|p| parse_mod_info(&p));
+        let match_found = installed.iter().find(|i| {
+            sha256
+                .as_ref()
+                .map(|s| i.hashes.iter().any(|h| h.eq_ignore_ascii_case(s)))
+                .unwrap_or(false)
+                || (id.is_some() && i.id == id)
+        });
+        items.push(WorkshopItem {
+            path: path.clone(),
+            file_name: jar
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default()
+                .to_string(),
+            workshop_id: install::infer_workshop_id(&jar),
+            item_modified: item_dir.as_deref().and_then(modified_secs),
+            item_dir: item_dir.map(|d| d.to_string_lossy().to_string()),
+            sha256,
+            name: meta.as_ref().and_then(|m| m.name.clone()),
+            id,
+            version: meta.as_ref().and_then(|m| m.version.clone()),
+            environment: meta.as_ref().and_then(|m| m.environment.clone()),
+            author: meta.as_ref().and_then(|m| m.author.clone()),
+            description: meta.as_ref().and_then(|m| m.description.clone()),
+            dependencies: meta.and_then(|m| m.dependencies),
+            mod_info,
+            installed: match_found.is_some(),
+            installed_file: match_found.map(|i| i.file_name.clone()),
+            installed_version: match_found.and_then(|i| i.version.clone()),
+            duplicates: Vec::new(),
+        });
+    }
+    items
+}
+
+/// Candidate `workshop/content/108600` folders (env overrides, Steam libraries, next to the game install).
+pub fn workshop_roots() -> Vec<PathBuf> {
+    let mut roots_raw: Vec<PathBuf> = Vec::new();
+
+    if let Ok(override_path) = std::env::var("PZ_WORKSHOP_ROOT") {
+        roots_raw.push(PathBuf::from(override_path));
+    }
+    if let Ok(override_path) = std::env::var("AVRIX_WORKSHOP_ROOT") {
+        roots_raw.push(PathBuf::from(override_path));
+    }
+    roots_raw.extend(steam::discover().workshop_roots);
+    if let Ok(base) = std::env::current_dir() {
+        if let Some(game_root) = find_game_root(&base) {
+            if let Some(steamapps) = game_root.parent().and_then(|p| p.parent()) {
+                let candidate = steamapps.join("workshop").join("content/108600");
+                roots_raw.push(candidate);
+            }
+        }
+    }
+    let mut roots: Vec<PathBuf> = Vec::new();
+    for r in roots_raw.into_iter() {
+        if let Ok(canon) = r.canonicalize() {
+            roots.push(canon);
+        } else {
+            roots.push(r);
+        }
+    }
+    let mut seen_root = HashSet::new();
+    roots.retain(|p| seen_root.insert(p.to_string_lossy().to_string()));
+    roots
 }
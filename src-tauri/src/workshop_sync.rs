@@ -0,0 +1,367 @@
+use crate::logger::{info, warn};
+use crate::models::{
+    LockSource, WorkshopSyncEntry, WorkshopSyncResult, WorkshopSyncSettings, WorkshopSyncStatus,
+};
+use crate::processes::ProcessRegistry;
+use crate::store::settings_store;
+use crate::{backup, install, lockfile, metadata, util, workshop};
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager, Window};
+
+const STORE_SETTINGS_KEY: &str = "workshopSync";
+
+pub fn load_settings(app: &AppHandle) -> WorkshopSyncSettings {
+    settings_store(app)
+        .ok()
+        .and_then(|s| s.get(STORE_SETTINGS_KEY))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+fn save_settings(app: &AppHandle, settings: &WorkshopSyncSettings) -> Result<()> {
+    let store = settings_store(app)?;
+    store.set(STORE_SETTINGS_KEY, serde_json::to_value(settings)?);
+    store.save().map_err(|e| anyhow!(e.to_string()))
+}
+
+fn is_jar(p: &Path) -> bool {
+    p.is_file()
+        && p.extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.eq_ignore_ascii_case("jar"))
+            .unwrap_or(false)
+}
+
+/// Valid plugin jars of a workshop item folder.
+fn item_jars(item_dir: &Path) -> Vec<PathBuf> {
+    let mut out = Vec::new();
+    let mut stack = vec![(item_dir.to_path_buf(), 0)];
+    while let Some((dir, depth)) = stack.pop() {
+        let Ok(rd) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for p in rd.flatten().map(|e| e.path()) {
+            if p.is_dir() && depth < 6 {
+                stack.push((p, depth + 1));
+            } else if is_jar(&p) && metadata::is_valid_avrix_plugin(&p) {
+                out.push(p);
+            }
+        }
+    }
+    out.sort();
+    out
+}
+
+/// Where the workshop copy of an installed jar is now: the recorded source path when it
+/// still exists, else a jar of the item with the same file name or plugin id.
+/// `Err(())` when the item folder is gone from every workshop root.
+fn locate_source(
+    roots: &[PathBuf],
+    workshop_id: &str,
+    recorded: Option<&str>,
+    file_name: &str,
+    plugin_id: Option<&str>,
+) -> std::result::Result<Option<PathBuf>, ()> {
+    if let Some(p) = recorded.map(PathBuf::from).filter(|p| is_jar(p)) {
+        if p.components().any(|c| c.as_os_str() == workshop_id) {
+            return Ok(Some(p));
+        }
+    }
+    let item_dirs: Vec<PathBuf> = roots
+        .iter()
+        .map(|r| r.join(workshop_id))
+        .filter(|d| d.is_dir())
+        .collect();
+    if item_dirs.is_empty() {
+        return Err(());
+    }
+    let jars: Vec<PathBuf> = item_dirs.iter().flat_map(|d| item_jars(d)).collect();
+    let by_name = jars
+        .iter()
+        .find(|j| j.file_name().and_then(|n| n.to_str()) == Some(file_name));
+    let by_id = || {
+        plugin_id.and_then(|id| {
+            jars.iter().find(|j| {
+                metadata::extract_metadata_from_jar(j)
+                    .ok()
+                    .and_then(|m| m.id)
+                    .as_deref()
+                    == Some(id)
+            })
+        })
+    };
+    Ok(by_name.or_else(by_id).cloned())
+}
+
+/// Copies the workshop jar over the installed one (previous version backed up), embeds the
+/// workshop id and pins the new content in `plugins.lock`.
+fn update_copy(
+    plugins_dir: &Path,
+    installed: &Path,
+    source: &Path,
+    source_sha256: &str,
+    workshop_id: &str,
+    emit: &dyn Fn(&str),
+) -> Result<()> {
+    let dest_dir = installed
+        .parent()
+        .ok_or_else(|| anyhow!("Invalid install path"))?;
+    let file_name = installed
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| anyhow!("Invalid install path"))?;
+    let staged = dest_dir.join(format!("{}.download", file_name));
+    fs::copy(source, &staged).map_err(|e| {
+        let _ = fs::remove_file(&staged);
+        anyhow!("Copy failed: {}", e)
+    })?;
+    let dest = backup::install_atomic(plugins_dir, &staged, dest_dir, file_name, emit)?;
+    if let Err(e) = install::rewrite_metadata_workshop_id(&dest, workshop_id) {
+        warn(
+            "workshop-sync",
+            &format!("Cannot embed workshopId in {}: {}", file_name, e),
+        );
+    }
+    let mut entry = lockfile::entry_for(
+        &dest,
+        LockSource::Workshop {
+            workshop_id: workshop_id.to_string(),
+            path: source.to_string_lossy().to_string(),
+        },
+    )?;
+    entry.source_sha256 = Some(source_sha256.to_string());
+    lockfile::record(plugins_dir, entry)
+}
+
+/// Compares every jar recorded in `workshop-map.json` with its workshop item and refreshes
+/// the changed ones. Nothing is written with `dry_run`, nor while a game instance runs or
+/// starts (the jars are in use); changed copies are then reported as `changed`.
+pub fn sync(app: &AppHandle, dry_run: bool) -> Result<WorkshopSyncResult> {
+    let emit = |m: &str| {
+        info("workshop-sync", m);
+        let _ = app.emit("workshop-sync-log", m.to_string());
+    };
+    let plugins_dir = util::resolve_plugins_dir();
+    let mapping = install::load_workshop_mapping(&plugins_dir);
+    if mapping.is_empty() {
+        emit("No workshop plugin installed");
+        return Ok(WorkshopSyncResult::default());
+    }
+    let roots: Vec<PathBuf> = workshop::workshop_roots()
+        .into_iter()
+        .filter(|r| r.is_dir())
+        .collect();
+    if roots.is_empty() {
+        return Err(anyhow!(
+            "No workshop folder found (check the Steam libraries or PZ_WORKSHOP_ROOT)"
+        ));
+    }
+    let registry = app.state::<ProcessRegistry>();
+    let in_use = !registry.list().is_empty();
+    if in_use && !dry_run {
+        emit("A game instance is running: changes are reported, not applied");
+    }
+    let lock = lockfile::load(&plugins_dir);
+    let mut result = WorkshopSyncResult::default();
+    for (file_name, workshop_id) in mapping {
+        let mut entry = WorkshopSyncEntry {
+            file_name: file_name.clone(),
+            workshop_id: workshop_id.clone(),
+            status: WorkshopSyncStatus::UpToDate,
+            source: None,
+            installed_version: None,
+            workshop_version: None,
+            message: None,
+        };
+        let installed = [
+            plugins_dir.join(&file_name),
+            plugins_dir.join(util::DISABLED_DIR_NAME).join(&file_name),
+        ]
+        .into_iter()
+        .find(|p| p.is_file());
+        let Some(installed) = installed else {
+            entry.status = WorkshopSyncStatus::NotInstalled;
+            result.entries.push(entry);
+            continue;
+        };
+        let installed_meta = metadata::extract_metadata_from_jar(&installed).ok();
+        entry.installed_version = installed_meta.as_ref().and_then(|m| m.version.clone());
+        let lock_entry = lock.plugins.iter().find(|e| e.file_name == file_name);
+        let recorded = lock_entry.and_then(|e| match &e.source {
+            LockSource::Workshop { path, .. } => Some(path.as_str()),
+            _ => None,
+        });
+        let source = match locate_source(
+            &roots,
+            &workshop_id,
+            recorded,
+            &file_name,
+            installed_meta.as_ref().and_then(|m| m.id.as_deref()),
+        ) {
+            Ok(Some(source)) => source,
+            Ok(None) => {
+                entry.status = WorkshopSyncStatus::SourceMissing;
+                entry.message = Some("No matching plugin jar in the workshop item".into());
+                emit(&format!(
+                    "{}: no matching jar in item {}",
+                    file_name, workshop_id
+                ));
+                result.entries.push(entry);
+                continue;
+            }
+            Err(()) => {
+                entry.status = WorkshopSyncStatus::Unsubscribed;
+                entry.message = Some("Workshop item no longer present (unsubscribed?)".into());
+                emit(&format!(
+                    "{}: workshop item {} is gone (unsubscribed?)",
+                    file_name, workshop_id
+                ));
+                result.entries.push(entry);
+                continue;
+            }
+        };
+        entry.source = Some(source.to_string_lossy().to_string());
+        entry.workshop_version = metadata::extract_metadata_from_jar(&source)
+            .ok()
+            .and_then(|m| m.version);
+        let (source_sha, installed_sha) =
+            match (util::sha256_file(&source), util::sha256_file(&installed)) {
+                (Ok(a), Ok(b)) => (a, b),
+                (Err(e), _) | (_, Err(e)) => {
+                    entry.status = WorkshopSyncStatus::Failed;
+                    entry.message = Some(format!("Hash error: {}", e));
+                    result.entries.push(entry);
+                    continue;
+                }
+            };
+        // The installed copy differs from its source once the workshop id is embedded, so
+        // compare with the source hash pinned at install time.
+        let known_source = lock_entry
+            .map(|e| e.source_sha256.clone().unwrap_or_else(|| e.sha256.clone()))
+            .unwrap_or_else(|| installed_sha.clone());
+        if known_source.eq_ignore_ascii_case(&source_sha) {
+            result.entries.push(entry);
+            continue;
+        }
+        if lock_entry
+            .map(|e| !e.sha256.eq_ignore_ascii_case(&installed_sha))
+            .unwrap_or(false)
+        {
+            entry.status = WorkshopSyncStatus::LocallyModified;
+            entry.message = Some("Installed jar was modified locally; not overwritten".into());
+            emit(&format!(
+                "{}: workshop copy changed but the installed jar was modified locally",
+                file_name
+            ));
+            result.entries.push(entry);
+            continue;
+        }
+        // Checked again under the launch lock: a launch may have started since
+        let _plugins = (!dry_run).then(|| registry.lock_plugins());
+        if dry_run || in_use || !registry.list().is_empty() {
+            entry.status = WorkshopSyncStatus::Changed;
+            emit(&format!("{}: update available", file_name));
+            result.entries.push(entry);
+            continue;
+        }
+        match update_copy(
+            &plugins_dir,
+            &installed,
+            &source,
+            &source_sha,
+            &workshop_id,
+            &emit,
+        ) {
+            Ok(()) => {
+                entry.status = WorkshopSyncStatus::Updated;
+                emit(&format!(
+                    "{}: updated {} -> {}",
+                    file_name,
+                    entry.installed_version.as_deref().unwrap_or("?"),
+                    entry.workshop_version.as_deref().unwrap_or("?")
+                ));
+            }
+            Err(e) => {
+                entry.status = WorkshopSyncStatus::Failed;
+                entry.message = Some(e.to_string());
+                warn(
+                    "workshop-sync",
+                    &format!("{}: update failed: {}", file_name, e),
+                );
+            }
+        }
+        result.entries.push(entry);
+    }
+    result.updated = result
+        .entries
+        .iter()
+        .filter(|e| e.status == WorkshopSyncStatus::Updated)
+        .count();
+    result.flagged = result
+        .entries
+        .iter()
+        .filter(|e| {
+            !matches!(
+                e.status,
+                WorkshopSyncStatus::UpToDate | WorkshopSyncStatus::Updated
+            )
+        })
+        .count();
+    emit(&format!(
+        "Workshop sync finished: {} plugin(s), {} updated, {} flagged",
+        result.entries.len(),
+        result.updated,
+        result.flagged
+    ));
+    Ok(result)
+}
+
+/// Startup hook: runs a sync in the background when enabled in the settings.
+pub fn sync_on_startup(app: &AppHandle) {
+    if !load_settings(app).on_startup {
+        return;
+    }
+    let app = app.clone();
+    std::thread::spawn(move || {
+        if let Err(e) = sync(&app, false) {
+            warn("workshop-sync", &format!("Startup sync failed: {}", e));
+        }
+    });
+}
+
+#[tauri::command]
+pub fn sync_workshop_plugins(
+    window: Window,
+    dry_run: Option<bool>,
+) -> Result<WorkshopSyncResult, String> {
+    info(
+        "workshop-sync",
+        &format!("sync_workshop_plugins invoked (dry_run={:?})", dry_run),
+    );
+    sync(window.app_handle(), dry_run.unwrap_or(false)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_workshop_sync_settings(window: Window) -> Result<WorkshopSyncSettings, String> {
+    info("workshop-sync", "get_workshop_sync_settings invoked");
+    Ok(load_settings(window.app_handle()))
+}
+
+/// `onStartup` syncs in the background when the launcher starts, `beforeLaunch` before each
+/// game or server launch.
+#[tauri::command]
+pub fn set_workshop_sync_settings(
+    settings: WorkshopSyncSettings,
+    window: Window,
+) -> Result<(), String> {
+    info(
+        "workshop-sync",
+        &format!(
+            "set_workshop_sync_settings invoked (on_startup={}, before_launch={})",
+            settings.on_startup, settings.before_launch
+        ),
+    );
+    save_settings(window.app_handle(), &settings).map_err(|e| e.to_string())
+}
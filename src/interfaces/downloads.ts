@@ -0,0 +1,21 @@
+export type DownloadState =
+  | 'started'
+  | 'downloading'
+  | 'retrying'
+  | 'completed'
+  | 'failed'
+  | 'cancelled';
+
+// Payload of the `download-progress` event
+export interface DownloadProgress {
+  taskId: string;
+  url: string;
+  fileName: string;
+  state: DownloadState;
+  downloaded: number;
+  total?: number | null;
+  speedBps: number;
+  etaSecs?: number | null;
+  attempt: number;
+  message?: string | null;
+}
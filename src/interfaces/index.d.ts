@@ -1,5 +1,9 @@
 export * from './plugins';
 export * from './logs';
+export * from './launch';
+export * from './steam';
+export * from './downloads';
+export * from './versions';
 export * from './news';
 
 export const __types: true;
@@ -0,0 +1,97 @@
+export type LaunchPreset = 'default' | 'lowMemory' | 'performance' | 'debug';
+
+export interface LaunchOptions {
+  preset: LaunchPreset;
+  jvmArgs: string[];
+  systemProperties: Record<string, string>;
+  programArgs: string[];
+  env: Record<string, string>;
+  debugPort?: number | null;
+}
+
+export interface LaunchOptionIssue {
+  field: string;
+  severity: 'error' | 'warning';
+  message: string;
+}
+
+export interface LaunchPresetInfo {
+  id: LaunchPreset;
+  label: string;
+  description: string;
+  jvmArgs: string[];
+  programArgs: string[];
+}
+
+export type LaunchRequest =
+  | { mode: 'client'; steam: boolean; memMb?: number | null }
+  | {
+      mode: 'server';
+      serverName?: string | null;
+      adminPassword?: string | null;
+      port?: number | null;
+      steam: boolean;
+      memMb?: number | null;
+    };
+
+export type ClassPathOrigin = 'gameRoot' | 'versionDir' | 'workingDir' | 'coreJar';
+
+export type JavaSource =
+  | 'pinned'
+  | 'versionJre'
+  | 'workingDirJre'
+  | 'bundledJre'
+  | 'gameJre'
+  | 'javaHome'
+  | 'path'
+  | 'system';
+
+export interface JavaRuntime {
+  path: string;
+  home: string;
+  source: JavaSource;
+  version?: string | null;
+  major?: number | null;
+  vendor?: string | null;
+  arch?: string | null;
+  bits?: number | null;
+  meetsRequirement: boolean;
+  problem?: string | null;
+}
+
+export interface JavaRuntimesResult {
+  runtimes: JavaRuntime[];
+  requiredMajor: number;
+  requiredBy: string;
+  pinned?: string | null;
+  selected?: string | null;
+}
+
+export interface ClassPathEntry {
+  path: string;
+  origin: ClassPathOrigin;
+  exists: boolean;
+}
+
+export interface RejectedCandidate {
+  path: string;
+  reason: string;
+}
+
+export interface LaunchPlan {
+  mode: 'client' | 'server';
+  javaPath: string;
+  javaSource: JavaSource;
+  java: JavaRuntime;
+  workDir: string;
+  coreJar?: string | null;
+  rejectedCoreJars: RejectedCandidate[];
+  classPath: ClassPathEntry[];
+  libraryPath: string[];
+  jvmArgs: string[];
+  mainClass: string;
+  programArgs: string[];
+  env: Record<string, string>;
+  commandLine: string;
+  problems: string[];
+}
@@ -9,3 +9,38 @@ export interface LogEntry {
 export interface LaunchExitEvent {
   code: number;
 }
+
+export interface GameSession {
+  fileName: string;
+  mode: 'client' | 'server' | string;
+  size: number;
+  modified: number;
+  hasCrashReport: boolean;
+}
+
+export type CrashKind =
+  | 'outOfMemory'
+  | 'nativeCrash'
+  | 'unsatisfiedLink'
+  | 'classNotFound'
+  | 'abnormalExit';
+
+export interface CrashCulprit {
+  pluginId?: string | null;
+  fileName: string;
+  package: string;
+  frame: string;
+}
+
+export interface CrashReport {
+  kind: CrashKind;
+  mode: string;
+  exitCode?: number | null;
+  summary: string;
+  culprit?: CrashCulprit | null;
+  evidence: string[];
+  suggestions: string[];
+  hsErrFile?: string | null;
+  sessionLog?: string | null;
+  createdAt: number;
+}
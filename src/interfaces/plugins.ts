@@ -15,11 +15,112 @@ export interface PluginInfo {
   imageUrl?: string;
   internal?: boolean;
   parentId?: string;
+  enabled?: boolean;
+  sha256?: string;
+  lockStatus?: 'locked' | 'modified' | 'untracked';
+  duplicate?: 'duplicate' | 'versionConflict';
+}
+
+export interface PluginWarning {
+  kind: 'duplicate' | 'versionConflict';
+  id: string;
+  files: string[];
+  versions: string[];
+  message: string;
 }
 
 export interface ScanPluginsResult {
   plugins?: PluginInfo[];
   dir?: string;
+  warnings?: PluginWarning[];
+}
+
+export interface CatalogPluginVersion {
+  version: string;
+  url: string;
+  sha256?: string;
+  size?: number;
+  fileName?: string;
+  dependencies?: Record<string, string>;
+  publishedAt?: string;
+}
+
+export interface CatalogPlugin {
+  id: string;
+  name: string;
+  description?: string;
+  author?: string;
+  environment?: string;
+  imageUrl?: string;
+  homepage?: string;
+  versions: CatalogPluginVersion[];
+  installedVersion?: string;
+}
+
+export interface WorkshopModInfo {
+  name?: string | null;
+  id?: string | null;
+  description?: string | null;
+  version?: string | null;
+  url?: string | null;
+  poster?: string | null;
+}
+
+export interface WorkshopItem {
+  path: string;
+  fileName: string;
+  workshopId?: string | null;
+  itemDir?: string | null;
+  itemModified?: number | null;
+  sha256?: string | null;
+  name?: string | null;
+  id?: string | null;
+  version?: string | null;
+  environment?: string | null;
+  author?: string | null;
+  description?: string | null;
+  dependencies?: Record<string, string> | null;
+  modInfo?: WorkshopModInfo | null;
+  installed: boolean;
+  installedFile?: string | null;
+  installedVersion?: string | null;
+  duplicates: string[];
+}
+
+export interface WorkshopScanResult {
+  found: string[];
+  items: WorkshopItem[];
+}
+
+export type WorkshopSyncStatus =
+  | 'upToDate'
+  | 'updated'
+  | 'changed'
+  | 'locallyModified'
+  | 'unsubscribed'
+  | 'sourceMissing'
+  | 'notInstalled'
+  | 'failed';
+
+export interface WorkshopSyncEntry {
+  fileName: string;
+  workshopId: string;
+  status: WorkshopSyncStatus;
+  source?: string | null;
+  installedVersion?: string | null;
+  workshopVersion?: string | null;
+  message?: string | null;
+}
+
+export interface WorkshopSyncResult {
+  entries: WorkshopSyncEntry[];
+  updated: number;
+  flagged: number;
+}
+
+export interface WorkshopSyncSettings {
+  onStartup: boolean;
+  beforeLaunch: boolean;
 }
 
 export {};
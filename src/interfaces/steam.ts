@@ -0,0 +1,7 @@
+export interface SteamInfo {
+  steamDirs: string[];
+  libraries: string[];
+  gameDir?: string | null;
+  buildId?: string | null;
+  workshopRoots: string[];
+}
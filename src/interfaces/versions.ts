@@ -0,0 +1,12 @@
+export type ReleaseChannel = 'stable' | 'beta' | 'nightly';
+
+export interface AvailableVersion {
+  tag: string;
+  version: string;
+  coreUrl: string;
+  jreUrl?: string | null;
+  coreSize?: number | null;
+  jreSize?: number | null;
+  channel: ReleaseChannel;
+  publishedAt?: string | null;
+}